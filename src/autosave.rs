@@ -0,0 +1,180 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::Settings;
+
+/// Bytes every saved board starts with.
+const MAGIC: &[u8; 8] = b"GOLBOARD";
+
+/// Name of the file boards are saved to by default, next to the settings file.
+const FILE_NAME: &str = "board.bin";
+
+/// Length of the header before values of cells.
+const HEADER_LEN: usize = MAGIC.len() + 4 + 4 + 8;
+
+/// This struct represents a board saved on exit and restored on the next launch.
+///
+/// It is stored as [`MAGIC`], the width, the height and the generation followed by
+/// value of every cell row after row, all of them little endian.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SavedBoard {
+    pub size: (u32, u32),
+    pub generation: u64,
+    /// Value of every cell row after row.
+    pub cells: Vec<u32>,
+}
+
+impl SavedBoard {
+    /// Returns path boards are saved to when none is given,
+    /// `None` when the platform config directory cannot be determined.
+    #[must_use]
+    pub fn default_path() -> Option<PathBuf> {
+        Settings::path().map(|path| path.with_file_name(FILE_NAME))
+    }
+
+    /// Loads a board from given file.
+    ///
+    /// # Errors
+    ///
+    /// - when the file cannot be read.
+    /// - when the file is not a saved board.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        Self::from_bytes(&fs::read(path)?)
+    }
+
+    /// Saves the board to given file, creating its directory when needed.
+    ///
+    /// # Errors
+    ///
+    /// - when the directory or the file cannot be written.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(directory) = path.parent() {
+            fs::create_dir_all(directory)?;
+        }
+        fs::write(path, self.to_bytes())
+    }
+
+    /// Returns the board encoded as stored in files.
+    #[must_use]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(HEADER_LEN + self.cells.len() * 4);
+        bytes.extend_from_slice(MAGIC);
+        bytes.extend_from_slice(&self.size.0.to_le_bytes());
+        bytes.extend_from_slice(&self.size.1.to_le_bytes());
+        bytes.extend_from_slice(&self.generation.to_le_bytes());
+        for cell in &self.cells {
+            bytes.extend_from_slice(&cell.to_le_bytes());
+        }
+        bytes
+    }
+
+    /// Decodes a board stored in a file.
+    ///
+    /// # Errors
+    ///
+    /// - when the bytes do not start with [`MAGIC`].
+    /// - when number of cells does not match the size.
+    pub fn from_bytes(bytes: &[u8]) -> io::Result<Self> {
+        let invalid = |message: &str| io::Error::new(io::ErrorKind::InvalidData, message);
+        let header = bytes
+            .get(..HEADER_LEN)
+            .filter(|header| header.starts_with(MAGIC))
+            .ok_or_else(|| invalid("not a saved board"))?;
+        let u32_at = |offset: usize| {
+            u32::from_le_bytes(
+                header[offset..offset + 4]
+                    .try_into()
+                    .expect("Header is long enough"),
+            )
+        };
+        let size = (u32_at(MAGIC.len()), u32_at(MAGIC.len() + 4));
+        let generation = u64::from_le_bytes(
+            header[MAGIC.len() + 8..HEADER_LEN]
+                .try_into()
+                .expect("Header is long enough"),
+        );
+        let cells = &bytes[HEADER_LEN..];
+        if cells.len() as u64 != u64::from(size.0) * u64::from(size.1) * 4 {
+            return Err(invalid(
+                "number of cells does not match the size of the board",
+            ));
+        }
+        let cells = cells
+            .chunks_exact(4)
+            .map(|cell| u32::from_le_bytes(cell.try_into().expect("Chunk has 4 bytes")))
+            .collect();
+        Ok(Self {
+            size,
+            generation,
+            cells,
+        })
+    }
+
+    /// Returns values of cells of a board with given dimensions, with the saved board
+    /// centred on it. Cells outside of the board are dropped.
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation)]
+    #[allow(clippy::cast_sign_loss)]
+    pub fn cells_for(&self, (width, height): (u32, u32)) -> Vec<u32> {
+        if self.size == (width, height) {
+            return self.cells.clone();
+        }
+        let (saved_width, saved_height) = self.size;
+        let offset = |size: u32, saved: u32| i64::from(size) / 2 - i64::from(saved) / 2;
+        let (left, top) = (offset(width, saved_width), offset(height, saved_height));
+        let mut cells = vec![0; width as usize * height as usize];
+        for y in 0..saved_height {
+            for x in 0..saved_width {
+                let (to_x, to_y) = (i64::from(x) + left, i64::from(y) + top);
+                if (0..i64::from(width)).contains(&to_x) && (0..i64::from(height)).contains(&to_y) {
+                    let from = (y * saved_width + x) as usize;
+                    cells[(to_y * i64::from(width) + to_x) as usize] = self.cells[from];
+                }
+            }
+        }
+        cells
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn board() -> SavedBoard {
+        SavedBoard {
+            size: (3, 2),
+            generation: 42,
+            cells: vec![0, 1, 0, 2, 0, 1],
+        }
+    }
+
+    #[test]
+    fn bytes_round_trip() {
+        let board = board();
+        assert_eq!(
+            SavedBoard::from_bytes(&board.to_bytes()).expect("Valid board"),
+            board
+        );
+    }
+
+    #[test]
+    fn truncated_board_is_rejected() {
+        let bytes = board().to_bytes();
+        assert!(SavedBoard::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        assert!(SavedBoard::from_bytes(b"GOLB").is_err());
+    }
+
+    #[test]
+    fn smaller_board_is_centred() {
+        #[rustfmt::skip]
+        let expected = [
+            0, 0, 0, 0, 0,
+            0, 0, 1, 0, 0,
+            0, 2, 0, 1, 0,
+            0, 0, 0, 0, 0,
+        ];
+        assert_eq!(board().cells_for((5, 4)), expected);
+    }
+}
@@ -0,0 +1,190 @@
+use std::{
+    fmt,
+    time::{Duration, Instant},
+};
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use vulkano::sync::{self, GpuFuture};
+
+use crate::{simulation, vulkan, BoardLayout, Config, Error, Readback, Simulation};
+
+/// Seed of the board both step implementations start from.
+const SEED: u64 = 0x5EED;
+
+/// Number of generations submitted at once, the host waits for them before the next ones.
+const BATCH: u32 = 256;
+
+/// Timings of the same generations computed by copying the board before every step
+/// and by alternating between two buffers.
+#[derive(Clone, Copy, Debug)]
+pub struct StepBenchmark {
+    /// Dimensions of the board in cells.
+    pub size: (u32, u32),
+    /// Number of generations computed by both implementations.
+    pub generations: u32,
+    /// Time taken by [`Simulation::step`], which copies the board before every dispatch.
+    pub copy: Duration,
+    /// Time taken by dispatches alternating between two buffers without any copies.
+    pub ping_pong: Duration,
+    /// Whether both implementations computed the same final board.
+    pub identical: bool,
+}
+
+impl fmt::Display for StepBenchmark {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (width, height) = self.size;
+        let per_generation =
+            |duration: Duration| duration.as_secs_f64() * 1e6 / f64::from(self.generations);
+        writeln!(
+            f,
+            "{} generations of a {width}x{height} board",
+            self.generations
+        )?;
+        writeln!(
+            f,
+            "copy step: {:.2} ms, {:.1} µs per generation",
+            self.copy.as_secs_f64() * 1e3,
+            per_generation(self.copy)
+        )?;
+        writeln!(
+            f,
+            "ping-pong: {:.2} ms, {:.1} µs per generation",
+            self.ping_pong.as_secs_f64() * 1e3,
+            per_generation(self.ping_pong)
+        )?;
+        writeln!(
+            f,
+            "ping-pong takes {:.1}% of the copy step time",
+            self.ping_pong.as_secs_f64() / self.copy.as_secs_f64().max(f64::EPSILON) * 100.0
+        )?;
+        if self.identical {
+            f.write_str("final states are identical")
+        } else {
+            f.write_str("final states differ")
+        }
+    }
+}
+
+/// Computes `generations` of a seeded random board with the board size, rule,
+/// work group and tiling given by `config`, once with [`Simulation::step`] and once
+/// alternating between two buffers, and compares the final boards.
+///
+/// # Errors
+///
+/// - when the device does not support the work group size.
+/// - when vulkan fails to create any of structures.
+///
+/// # Panics
+///
+/// - when the vulkan context fails to initialize.
+/// - when vulkan fails to execute or wait for any of commands.
+pub fn benchmark_steps(config: &Config, generations: u32) -> Result<StepBenchmark, Error> {
+    let context = vulkan::vulkano_context(config.validation());
+    let queue = context.compute_queue().clone();
+    let device = context.device();
+    let workgroup = config.workgroup();
+    vulkan::try_validate_workgroup(device, workgroup)?;
+    let layout = BoardLayout::new(config.size(), config.cell_bits());
+    let words = seeded_board(layout);
+
+    let buffer = vulkan::try_create_gpu_buffer(device, layout, true)?;
+    vulkan::upload_words(&queue, buffer.clone(), words.clone());
+    let mut simulation = Simulation::try_new(
+        queue.clone(),
+        buffer,
+        layout,
+        config.rule(),
+        config.neighbourhood(),
+        config.topology(),
+        workgroup,
+    )?;
+    simulation.set_tiled(config.tiled());
+    simulation.set_weights(config.weights());
+    let start = Instant::now();
+    for target in batch_targets(generations) {
+        simulation
+            .run_to(target, sync::now(device.clone()).boxed())
+            .then_signal_fence_and_flush()
+            .expect("Cannot execute generations")
+            .wait(None)
+            .expect("Cannot wait for generations");
+    }
+    let copy = start.elapsed();
+    let copied = simulation.try_readback()?.read();
+
+    let buffers = [
+        vulkan::try_create_gpu_buffer(device, layout, true)?,
+        vulkan::try_create_gpu_buffer(device, layout, true)?,
+    ];
+    vulkan::upload_words(&queue, buffers[0].clone(), words);
+    let record = |output: usize| {
+        simulation::create_simulation_buffer(
+            &queue,
+            buffers[output].clone(),
+            buffers[1 - output].clone(),
+            None,
+            None,
+            layout,
+            workgroup,
+            config.rule(),
+            config.neighbourhood(),
+            config.topology(),
+            config.tiled(),
+            config.weights().as_ref(),
+        )
+    };
+    let steps = [record(1)?, record(0)?];
+    let start = Instant::now();
+    let mut generation = 0;
+    for target in batch_targets(generations) {
+        let mut future = sync::now(device.clone()).boxed();
+        while generation < target {
+            future = future
+                .then_execute(
+                    queue.clone(),
+                    steps[usize::from(generation % 2 == 1)].clone(),
+                )
+                .expect("Cannot execute generation")
+                .boxed();
+            generation += 1;
+        }
+        future
+            .then_signal_fence_and_flush()
+            .expect("Cannot execute generations")
+            .wait(None)
+            .expect("Cannot wait for generations");
+    }
+    let ping_pong = start.elapsed();
+    let last = buffers[usize::from(generations % 2 == 1)].clone();
+    let alternated = Readback::try_new(queue, last, layout)?.read();
+
+    Ok(StepBenchmark {
+        size: layout.size(),
+        generations,
+        copy,
+        ping_pong,
+        identical: copied == alternated,
+    })
+}
+
+/// Returns generations reached after every batch.
+fn batch_targets(generations: u32) -> impl Iterator<Item = u64> {
+    (1..=generations)
+        .filter(move |&generation| generation % BATCH == 0 || generation == generations)
+        .map(u64::from)
+}
+
+/// Returns words of a board with every cell alive with probability of one half,
+/// always the same for given layout.
+fn seeded_board(layout: BoardLayout) -> Vec<u32> {
+    let len = usize::try_from(layout.len()).expect("Board does not fit in memory");
+    let mut words = vec![0; len];
+    let mut rng = StdRng::seed_from_u64(SEED);
+    let (width, height) = layout.size();
+    for y in 0..height {
+        for x in 0..width {
+            layout.set(&mut words, x, y, rng.gen_bool(0.5).into());
+        }
+    }
+    words
+}
@@ -0,0 +1,273 @@
+/// Number of bits in a single word of [`crate::GpuBuffer`].
+const WORD_BITS: u32 = u32::BITS;
+
+/// Number of bits taken by a single neighbour count.
+const COUNT_BITS: u32 = 4;
+
+/// This struct describes how cells of the board are stored in [`crate::GpuBuffer`].
+///
+/// Cells are packed into 32 bit words, each cell taking `cell_bits` bits.
+/// Every row starts with a new word, so the last word of a row may contain unused bits.
+/// The same layout is used by all shaders through `cells.glsl`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BoardLayout {
+    width: u32,
+    height: u32,
+    cell_bits: u32,
+}
+
+impl BoardLayout {
+    /// Creates a new [`BoardLayout`].
+    ///
+    /// # Panics
+    ///
+    /// - when `cell_bits` does not divide 32.
+    #[must_use]
+    pub fn new(size: (u32, u32), cell_bits: u32) -> Self {
+        assert!(
+            cell_bits != 0 && WORD_BITS % cell_bits == 0,
+            "Cell bits must divide {WORD_BITS}"
+        );
+        Self {
+            width: size.0,
+            height: size.1,
+            cell_bits,
+        }
+    }
+
+    /// Returns dimensions of the board in cells.
+    #[inline]
+    #[must_use]
+    pub fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    /// Returns number of bits taken by a single cell.
+    #[inline]
+    #[must_use]
+    pub fn cell_bits(&self) -> u32 {
+        self.cell_bits
+    }
+
+    /// Returns number of cells stored in a single word.
+    #[inline]
+    #[must_use]
+    pub fn cells_per_word(&self) -> u32 {
+        WORD_BITS / self.cell_bits
+    }
+
+    /// Returns number of words used by a single row.
+    #[inline]
+    #[must_use]
+    pub fn words_per_row(&self) -> u32 {
+        (self.width + self.cells_per_word() - 1) / self.cells_per_word()
+    }
+
+    /// Returns number of words used by the whole board.
+    #[inline]
+    #[must_use]
+    pub fn len(&self) -> u64 {
+        u64::from(self.words_per_row()) * u64::from(self.height)
+    }
+
+    /// Returns number of words storing neighbour counts of cells of a single word.
+    /// Every count takes 4 bits, counts of different words never share a word.
+    #[inline]
+    #[must_use]
+    pub fn count_words_per_word(&self) -> u32 {
+        let counts_per_word = WORD_BITS / COUNT_BITS;
+        (self.cells_per_word() + counts_per_word - 1) / counts_per_word
+    }
+
+    /// Returns number of words storing neighbour counts of the whole board.
+    #[inline]
+    #[must_use]
+    pub fn count_len(&self) -> u64 {
+        self.len() * u64::from(self.count_words_per_word())
+    }
+
+    /// Returns whether the board has no cells.
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns number of work groups needed to process every word of the board
+    /// with work groups of `local_size` × `local_size` invocations.
+    #[must_use]
+    pub fn group_count(&self, local_size: u32) -> [u32; 3] {
+        [
+            (self.words_per_row() + local_size - 1) / local_size,
+            (self.height + local_size - 1) / local_size,
+            1,
+        ]
+    }
+
+    /// Returns value of the cell at given position.
+    ///
+    /// # Panics
+    ///
+    /// - when `words` is shorter than the board.
+    #[must_use]
+    pub fn get(&self, words: &[u32], x: u32, y: u32) -> u32 {
+        let (index, shift) = self.locate(x, y);
+        (words[index] >> shift) & self.cell_mask()
+    }
+
+    /// Sets value of the cell at given position.
+    /// Bits of the value that do not fit into a cell are ignored.
+    ///
+    /// # Panics
+    ///
+    /// - when `words` is shorter than the board.
+    pub fn set(&self, words: &mut [u32], x: u32, y: u32, value: u32) {
+        let (index, shift) = self.locate(x, y);
+        let mask = self.cell_mask() << shift;
+        words[index] = (words[index] & !mask) | ((value << shift) & mask);
+    }
+
+    /// Returns index of the word containing the cell at given position.
+    #[inline]
+    #[must_use]
+    pub fn word_index(&self, x: u32, y: u32) -> u64 {
+        u64::from(y) * u64::from(self.words_per_row()) + u64::from(x / self.cells_per_word())
+    }
+
+    /// Extracts value of the cell in column `x` from the word containing it.
+    #[inline]
+    #[must_use]
+    pub fn cell_from_word(&self, word: u32, x: u32) -> u32 {
+        (word >> ((x % self.cells_per_word()) * self.cell_bits)) & self.cell_mask()
+    }
+
+    /// Returns number of alive cells, cells in state `1`, stored in `words`.
+    /// Unused bits at the end of rows are ignored.
+    ///
+    /// # Panics
+    ///
+    /// - when a row does not fit in memory.
+    #[must_use]
+    pub fn population(&self, words: &[u32]) -> u64 {
+        if self.is_empty() {
+            return 0;
+        }
+        let row_len = usize::try_from(self.words_per_row()).expect("Row does not fit in memory");
+        let used = self.width % self.cells_per_word();
+        let tail_mask = if used == 0 {
+            u32::MAX
+        } else {
+            u32::MAX >> (WORD_BITS - used * self.cell_bits)
+        };
+        words
+            .chunks(row_len)
+            .map(|row| {
+                let (last, rest) = row.split_last().expect("Row is not empty");
+                rest.iter()
+                    .map(|&word| self.word_population(word))
+                    .sum::<u64>()
+                    + self.word_population(last & tail_mask)
+            })
+            .sum()
+    }
+
+    /// Returns number of alive cells stored in a single word.
+    fn word_population(&self, word: u32) -> u64 {
+        if self.cell_bits == 1 {
+            return u64::from(word.count_ones());
+        }
+        (0..self.cells_per_word())
+            .map(|cell| u64::from((word >> (cell * self.cell_bits)) & self.cell_mask() == 1))
+            .sum()
+    }
+
+    /// Returns mask of bits taken by a single cell.
+    #[inline]
+    fn cell_mask(&self) -> u32 {
+        u32::MAX >> (WORD_BITS - self.cell_bits)
+    }
+
+    /// Returns index of the word containing the cell and offset of the cell in the word.
+    #[inline]
+    fn locate(&self, x: u32, y: u32) -> (usize, u32) {
+        let index = self.word_index(x, y);
+        let shift = (x % self.cells_per_word()) * self.cell_bits;
+        (
+            usize::try_from(index).expect("Board does not fit in memory"),
+            shift,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Cell bits of every supported layout, with widths that leave unused bits in the last word.
+    const LAYOUTS: [(u32, u32); 6] = [(1, 37), (2, 19), (4, 11), (8, 7), (16, 3), (32, 5)];
+
+    #[test]
+    fn rows_start_with_new_word() {
+        assert_eq!(BoardLayout::new((37, 3), 1).words_per_row(), 2);
+        assert_eq!(BoardLayout::new((7, 3), 8).words_per_row(), 2);
+        assert_eq!(BoardLayout::new((5, 3), 32).words_per_row(), 5);
+        assert_eq!(BoardLayout::new((32, 3), 1).len(), 3);
+    }
+
+    #[test]
+    fn cells_round_trip() {
+        for (cell_bits, width) in LAYOUTS {
+            let layout = BoardLayout::new((width, 3), cell_bits);
+            let mut words = vec![0; usize::try_from(layout.len()).expect("Board fits in memory")];
+            let states = if cell_bits == 1 { 2 } else { 3 };
+            let value = |x: u32, y: u32| (x + 2 * y) % states;
+            for y in 0..3 {
+                for x in 0..width {
+                    layout.set(&mut words, x, y, value(x, y));
+                }
+            }
+            for y in 0..3 {
+                for x in 0..width {
+                    assert_eq!(layout.get(&words, x, y), value(x, y), "{cell_bits} bits");
+                    let word = words
+                        [usize::try_from(layout.word_index(x, y)).expect("Board fits in memory")];
+                    assert_eq!(layout.cell_from_word(word, x), value(x, y));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn set_ignores_bits_outside_of_cell() {
+        let layout = BoardLayout::new((7, 1), 8);
+        let mut words = vec![0; 2];
+        layout.set(&mut words, 1, 0, 0x1ff);
+        assert_eq!(words, [0xff00, 0]);
+    }
+
+    #[test]
+    fn population_ignores_unused_bits() {
+        for (cell_bits, width) in LAYOUTS {
+            let layout = BoardLayout::new((width, 2), cell_bits);
+            // Every cell of the words is alive, including the unused ones at the end of rows.
+            let alive = u32::MAX / layout.cell_mask();
+            let mut words =
+                vec![alive; usize::try_from(layout.len()).expect("Board fits in memory")];
+            for y in 0..2 {
+                for x in 0..width {
+                    layout.set(&mut words, x, y, 0);
+                }
+            }
+            assert_eq!(layout.population(&words), 0, "{cell_bits} bits");
+            layout.set(&mut words, 0, 0, 1);
+            layout.set(&mut words, width - 1, 1, 1);
+            layout.set(&mut words, width / 2, 1, 2);
+            assert_eq!(layout.population(&words), 2, "{cell_bits} bits");
+        }
+    }
+
+    #[test]
+    fn empty_board_has_no_population() {
+        assert_eq!(BoardLayout::new((0, 0), 1).population(&[]), 0);
+    }
+}
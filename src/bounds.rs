@@ -0,0 +1,177 @@
+use std::sync::Arc;
+
+use vulkano::{
+    buffer::{BufferUsage, CpuAccessibleBuffer},
+    command_buffer::{AutoCommandBufferBuilder, CommandBufferUsage, PrimaryCommandBuffer},
+    descriptor_set::{PersistentDescriptorSet, WriteDescriptorSet},
+    device::Queue,
+    pipeline::{ComputePipeline, Pipeline, PipelineBindPoint},
+    sync::GpuFuture,
+};
+
+use crate::{vulkan, BoardLayout, CommandBuffer, Error, GpuBuffer};
+
+mod shader {
+    vulkano_shaders::shader! {
+        path: "src/shaders/bounds.comp",
+        include: ["src/shaders"],
+        ty: "compute",
+    }
+}
+
+/// Bounds of an empty board, written before every scan.
+const EMPTY_BOUNDS: [u32; 4] = [u32::MAX, u32::MAX, 0, 0];
+
+/// Inclusive bounding box of live cells as `(min_x, min_y, max_x, max_y)`.
+pub type LiveBounds = (u32, u32, u32, u32);
+
+/// This struct represents a pipeline finding the smallest rectangle
+/// containing every live cell of the board.
+pub struct BoundsScanner {
+    compute_queue: Arc<Queue>,
+    bounds: Arc<CpuAccessibleBuffer<[u32]>>,
+    scan_buffer: Arc<CommandBuffer>,
+}
+
+impl BoundsScanner {
+    /// Creates a new [`BoundsScanner`] pipeline for given buffer.
+    ///
+    /// # Panics
+    ///
+    /// - when [`BoundsScanner::try_new`] fails.
+    #[must_use]
+    pub fn new(
+        compute_queue: Arc<Queue>,
+        buffer: Arc<GpuBuffer>,
+        layout: BoardLayout,
+        workgroup: u32,
+    ) -> Self {
+        Self::try_new(compute_queue, buffer, layout, workgroup)
+            .expect("Cannot create bounds scanner")
+    }
+
+    /// Creates a new [`BoundsScanner`] pipeline for given buffer.
+    ///
+    /// # Errors
+    ///
+    /// - when the bounds buffer creation fails.
+    /// - when the shader entry point is not found.
+    /// - when the compute pipeline creation fails.
+    /// - when the descriptor set creation fails.
+    /// - when the command buffer recording fails.
+    pub fn try_new(
+        compute_queue: Arc<Queue>,
+        buffer: Arc<GpuBuffer>,
+        layout: BoardLayout,
+        workgroup: u32,
+    ) -> Result<Self, Error> {
+        let device = compute_queue.device().clone();
+
+        let bounds = CpuAccessibleBuffer::from_iter(
+            device.clone(),
+            BufferUsage {
+                storage_buffer: true,
+                ..BufferUsage::empty()
+            },
+            true,
+            EMPTY_BOUNDS,
+        )?;
+
+        let shader = shader::load(device.clone())?;
+        let pipeline = ComputePipeline::new(
+            device.clone(),
+            shader.entry_point("main").ok_or(Error::MissingEntryPoint)?,
+            &shader::SpecializationConstants {
+                width: layout.size().0,
+                height: layout.size().1,
+                cellBits: layout.cell_bits(),
+                constant_100: workgroup,
+                constant_101: workgroup,
+            },
+            None,
+            |_| {},
+        )?;
+
+        let descriptor = PersistentDescriptorSet::new(
+            pipeline
+                .layout()
+                .set_layouts()
+                .get(0)
+                .ok_or(Error::MissingDescriptorSetLayout)?
+                .clone(),
+            [
+                WriteDescriptorSet::buffer(0, buffer),
+                WriteDescriptorSet::buffer(1, bounds.clone()),
+            ],
+        )?;
+
+        let mut builder = AutoCommandBufferBuilder::primary(
+            device,
+            compute_queue.queue_family_index(),
+            CommandBufferUsage::MultipleSubmit,
+        )?;
+
+        builder
+            .bind_descriptor_sets(
+                PipelineBindPoint::Compute,
+                pipeline.layout().clone(),
+                0,
+                descriptor,
+            )
+            .bind_pipeline_compute(pipeline)
+            .dispatch(layout.group_count(workgroup))?;
+
+        Ok(Self {
+            compute_queue,
+            bounds,
+            scan_buffer: Arc::new(builder.build()?),
+        })
+    }
+
+    /// Scans the board and waits for the result.
+    /// Returns `None` when the board has no live cells.
+    ///
+    /// # Panics
+    ///
+    /// - when [`BoundsScanner::start`] fails.
+    /// - when waiting for the scan fails.
+    /// - when [`BoundsScanner::result`] fails.
+    #[must_use]
+    pub fn scan(&self) -> Option<LiveBounds> {
+        vulkan::try_wait(self.start(), None).expect("Cannot wait for command buffer");
+        self.result()
+    }
+
+    /// Starts scanning the board, the result is read by [`BoundsScanner::result`]
+    /// once the returned [`GpuFuture`] finishes.
+    ///
+    /// # Panics
+    ///
+    /// - when the bounds buffer cannot be written.
+    /// - when the command buffer execution fails.
+    #[must_use]
+    pub fn start(&self) -> Box<dyn GpuFuture> {
+        self.bounds
+            .write()
+            .expect("Cannot write bounds buffer")
+            .copy_from_slice(&EMPTY_BOUNDS);
+
+        self.scan_buffer
+            .clone()
+            .execute(self.compute_queue.clone())
+            .expect("Cannot execute command buffer")
+            .boxed()
+    }
+
+    /// Returns the result of the last finished scan, `None` when the board has no live cells.
+    ///
+    /// # Panics
+    ///
+    /// - when the bounds buffer cannot be read.
+    #[must_use]
+    pub fn result(&self) -> Option<LiveBounds> {
+        let bounds = self.bounds.read().expect("Cannot read bounds buffer");
+        let (min_x, min_y, max_x, max_y) = (bounds[0], bounds[1], bounds[2], bounds[3]);
+        (min_x <= max_x && min_y <= max_y).then_some((min_x, min_y, max_x, max_y))
+    }
+}
@@ -1,21 +1,138 @@
-use glam::{Mat4, Vec3};
+use std::time::{Duration, Instant};
+
+use clap::ValueEnum;
+use glam::{Mat4, Vec3, Vec4};
+use serde::{Deserialize, Serialize};
 use winit::{
     dpi::{PhysicalPosition, PhysicalSize},
-    event::{ElementState, MouseButton, MouseScrollDelta, WindowEvent},
+    event::{
+        ElementState, KeyboardInput, MouseButton, MouseScrollDelta, Touch, TouchPhase,
+        VirtualKeyCode, WindowEvent,
+    },
 };
 
+use crate::{CameraSettings, LiveBounds};
+
 static SCALE_FACTOR: f32 = 0.1;
 
+/// Part of the window filled by cells framed with [`Camera::fit`].
+static FIT_FILL: f32 = 0.9;
+
+/// Drags released longer than this after the cursor last moved do not glide.
+static GLIDE_DELAY: Duration = Duration::from_millis(50);
+
+/// Speed in pixels per second below which the board stops gliding.
+static MIN_GLIDE_SPEED: f64 = 5.0;
+
+/// Weight of the newest cursor movement in the velocity of a drag.
+static VELOCITY_SMOOTHING: f64 = 0.5;
+
+/// Longest time constant of the glide after a drag in seconds that can be selected.
+pub(crate) const MAX_PAN_INERTIA: f32 = 1.0;
+
+/// Mouse buttons used to move the camera and edit cells.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum MouseBindings {
+    /// Left drag moves the camera, right click flips a cell.
+    #[default]
+    Classic,
+    /// Middle drag or space with left drag moves the camera,
+    /// left drag draws cells and right click flips a cell.
+    Draw,
+}
+
+impl MouseBindings {
+    /// Returns whether pressing given button starts moving the camera.
+    #[must_use]
+    pub fn moves(self, button: MouseButton, space: bool) -> bool {
+        match self {
+            Self::Classic => button == MouseButton::Left,
+            Self::Draw => button == MouseButton::Middle || (button == MouseButton::Left && space),
+        }
+    }
+
+    /// Returns whether dragging with given button draws cells.
+    #[must_use]
+    pub fn draws(self, button: MouseButton) -> bool {
+        self == Self::Draw && button == MouseButton::Left
+    }
+
+    /// Returns whether clicking given button flips a cell.
+    #[must_use]
+    pub fn flips(self, button: MouseButton) -> bool {
+        button == MouseButton::Right
+    }
+}
+
+/// Shape of the cells when proportions of the board differ from the window.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CellAspect {
+    /// Cells stay square, the board is letterboxed.
+    #[default]
+    Square,
+    /// The board fills the window, cells are stretched.
+    Stretch,
+}
+
+impl CellAspect {
+    /// All aspects in order they should be displayed.
+    pub const ALL: [Self; 2] = [Self::Square, Self::Stretch];
+
+    /// Returns human readable name of the aspect.
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Square => "Square",
+            Self::Stretch => "Stretch",
+        }
+    }
+}
+
+/// Placement of cells in the window in pixels, used to draw coordinate rulers.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CellMapping {
+    /// Position of the top left corner of the first cell in the first view.
+    pub origin: [f64; 2],
+    /// Width and height of a single cell.
+    pub cell_size: [f64; 2],
+    /// Width of a single view, every view shows cells at the same place.
+    pub view_width: f64,
+    /// Number of views the window is split into horizontally.
+    pub views: u32,
+    /// Dimensions of the board in cells.
+    pub game_size: (u32, u32),
+}
+
 /// Struct that represents a camera.
+#[derive(Clone)]
 pub struct Camera {
     scale: f32,
     ratio: f32,
-    moving: bool,
+    bindings: MouseBindings,
+    aspect: CellAspect,
+    moving: Option<MouseButton>,
+    dragged: bool,
+    space: bool,
     game_ratio: f64,
     translation: Vec3,
     game_size: (u32, u32),
     screen_size: (f64, f64),
+    views: u32,
     cursor_pos: PhysicalPosition<f64>,
+    /// Whether control is held, scrolling with pixel deltas then zooms instead of moving.
+    ctrl: bool,
+    /// Touches on the screen by their identifiers, the first two pan and zoom.
+    touches: Vec<(u64, PhysicalPosition<f64>)>,
+    /// Scale restored when the native mode is left, `None` outside of it.
+    native: Option<f32>,
+    /// Time constant in seconds of the glide after a drag is released, 0 when it stops dead.
+    inertia: f32,
+    /// Velocity of the drag in pixels per second, kept while the board glides after it.
+    velocity: (f64, f64),
+    /// When the cursor last moved the board during the drag.
+    dragged_at: Option<Instant>,
 }
 
 impl Camera {
@@ -24,95 +141,718 @@ impl Camera {
     pub fn new(game_size: (u32, u32), screen_size: PhysicalSize<u32>) -> Self {
         let game_ratio = f64::from(game_size.0) / f64::from(game_size.1);
         let screen_size = (f64::from(screen_size.width), f64::from(screen_size.height));
-        let screen_ratio = screen_size.0 / screen_size.1;
-
-        #[allow(clippy::cast_possible_truncation)]
-        let ratio = (game_ratio / screen_ratio) as f32;
+        let aspect = CellAspect::default();
 
         Self {
             scale: 1.0,
-            ratio,
-            moving: false,
+            ratio: aspect_ratio(aspect, game_ratio, screen_size),
+            bindings: MouseBindings::default(),
+            aspect,
+            moving: None,
+            dragged: false,
+            space: false,
             game_ratio,
             translation: Vec3::ZERO,
             game_size,
             screen_size,
+            views: 1,
             cursor_pos: PhysicalPosition::new(0.0, 0.0),
+            ctrl: false,
+            touches: Vec::new(),
+            native: None,
+            inertia: 0.0,
+            velocity: (0.0, 0.0),
+            dragged_at: None,
         }
     }
 
     /// Updates the camera.
     pub fn update(&mut self, event: &WindowEvent) {
         match event {
-            WindowEvent::Resized(screen_size) => {
-                #[allow(clippy::cast_possible_truncation)]
-                if screen_size.height != 0 && screen_size.width != 0 {
-                    self.screen_size =
-                        (f64::from(screen_size.width), f64::from(screen_size.height));
-                    let screen_ratio = self.screen_size.0 / self.screen_size.1;
-                    self.ratio = (self.game_ratio / screen_ratio) as f32;
-                }
+            WindowEvent::Resized(screen_size) => self.set_screen_size(*screen_size),
+            // Moving the window to a monitor with another scale factor resizes it.
+            WindowEvent::ScaleFactorChanged { new_inner_size, .. } => {
+                self.set_screen_size(**new_inner_size);
             }
             WindowEvent::CursorMoved { position, .. } => {
-                #[allow(clippy::cast_possible_truncation)]
-                if self.moving {
-                    let dx = (position.x - self.cursor_pos.x) * 2.0 / self.screen_size.0;
-                    let dy = (position.y - self.cursor_pos.y) * 2.0 / self.screen_size.1;
-                    self.translation.x += dx as f32 / self.scale;
-                    self.translation.y += dy as f32 / (self.scale / self.ratio);
-                    self.translation.x = self.translation.x.clamp(-1.0, 1.0);
-                    self.translation.y = self.translation.y.clamp(-1.0, 1.0);
+                if self.moving.is_some() {
+                    let (dx, dy) = (
+                        position.x - self.cursor_pos.x,
+                        position.y - self.cursor_pos.y,
+                    );
+                    self.pan(dx, dy);
+                    self.track_velocity(dx, dy, Instant::now());
+                    self.dragged |= *position != self.cursor_pos;
                 }
                 self.cursor_pos = *position;
             }
+            // The scale is fixed in the native mode, scrolling can only move the board.
+            WindowEvent::MouseWheel {
+                delta: MouseScrollDelta::PixelDelta(delta),
+                ..
+            } if self.native.is_some() && !self.ctrl => self.pan(delta.x, delta.y),
+            WindowEvent::MouseWheel { .. } | WindowEvent::TouchpadMagnify { .. }
+                if self.native.is_some() => {}
             WindowEvent::MouseWheel { delta, .. } => {
                 match delta {
                     MouseScrollDelta::LineDelta(_, dy) => {
                         self.scale += dy * SCALE_FACTOR * self.scale;
                     }
+                    // Trackpads scroll by pixels with two fingers, which moves the board
+                    // like dragging it. Pinching is reported as scrolling with control held
+                    // on platforms without magnify events.
                     #[allow(clippy::cast_possible_truncation)]
-                    MouseScrollDelta::PixelDelta(delta) => {
+                    MouseScrollDelta::PixelDelta(delta) if self.ctrl => {
                         self.scale += delta.y.signum() as f32 * SCALE_FACTOR * self.scale;
                     }
+                    MouseScrollDelta::PixelDelta(delta) => self.pan(delta.x, delta.y),
                 }
                 self.scale = self.scale.clamp(0.5, 1000.0); // TODO: scale max scaling with game size
+                self.clamp_translation();
+            }
+            #[allow(clippy::cast_possible_truncation)]
+            WindowEvent::TouchpadMagnify { delta, .. } => {
+                self.zoom_at(1.0 + *delta as f32, self.cursor_pos);
             }
-            WindowEvent::MouseInput { state, button, .. } => {
-                if *button == MouseButton::Left {
-                    self.moving = *state == ElementState::Pressed;
+            WindowEvent::Touch(touch) => self.touch(touch),
+            WindowEvent::ModifiersChanged(modifiers) => self.ctrl = modifiers.ctrl(),
+            WindowEvent::MouseInput { state, button, .. } => match state {
+                ElementState::Pressed => {
+                    self.dragged = false;
+                    if self.bindings.moves(*button, self.space) {
+                        self.moving = Some(*button);
+                        self.velocity = (0.0, 0.0);
+                        self.dragged_at = None;
+                    }
+                }
+                ElementState::Released if self.moving == Some(*button) => {
+                    self.moving = None;
+                    // Holding the cursor still before releasing it stops the board.
+                    let recent = self
+                        .dragged_at
+                        .take()
+                        .map_or(false, |moved| moved.elapsed() <= GLIDE_DELAY);
+                    if !recent || self.inertia <= 0.0 {
+                        self.velocity = (0.0, 0.0);
+                    }
+                }
+                ElementState::Released => (),
+            },
+            WindowEvent::KeyboardInput {
+                input:
+                    KeyboardInput {
+                        state,
+                        virtual_keycode: Some(VirtualKeyCode::Space),
+                        ..
+                    },
+                ..
+            } => self.space = *state == ElementState::Pressed,
+            _ => (),
+        }
+    }
+
+    /// Tracks touches on the screen, moving two fingers pans the board
+    /// by the movement of their centre and pinching zooms around it.
+    #[allow(clippy::cast_possible_truncation)]
+    fn touch(&mut self, touch: &Touch) {
+        let index = self.touches.iter().position(|(id, _)| *id == touch.id);
+        match (touch.phase, index) {
+            (TouchPhase::Started, None) => self.touches.push((touch.id, touch.location)),
+            (TouchPhase::Moved, Some(index)) => {
+                let pinch = self.pinch();
+                self.touches[index].1 = touch.location;
+                if let (Some((centre, distance)), Some((moved_centre, moved_distance))) =
+                    (pinch, self.pinch())
+                {
+                    self.pan(moved_centre.x - centre.x, moved_centre.y - centre.y);
+                    if distance > 0.0 {
+                        self.zoom_at((moved_distance / distance) as f32, moved_centre);
+                    }
                 }
             }
+            (TouchPhase::Ended | TouchPhase::Cancelled, Some(index)) => {
+                self.touches.remove(index);
+            }
             _ => (),
         }
     }
 
+    /// Returns centre of the first two touches and the distance between them,
+    /// `None` when fewer fingers touch the screen.
+    fn pinch(&self) -> Option<(PhysicalPosition<f64>, f64)> {
+        match self.touches.as_slice() {
+            [(_, first), (_, second), ..] => Some((
+                PhysicalPosition::new((first.x + second.x) / 2.0, (first.y + second.y) / 2.0),
+                (first.x - second.x).hypot(first.y - second.y),
+            )),
+            _ => None,
+        }
+    }
+
+    /// Moves the board by given distance in pixels, like dragging it.
+    #[allow(clippy::cast_possible_truncation)]
+    fn pan(&mut self, dx: f64, dy: f64) {
+        let dx = dx * 2.0 / self.view_size().0;
+        let dy = dy * 2.0 / self.screen_size.1;
+        self.translation.x += dx as f32 / self.scale;
+        self.translation.y += dy as f32 / (self.scale / self.ratio);
+        self.clamp_translation();
+    }
+
+    /// Updates the velocity of the drag after the cursor moved by given distance in pixels.
+    fn track_velocity(&mut self, dx: f64, dy: f64, now: Instant) {
+        if let Some(elapsed) = self.dragged_at.map(|moved| now - moved) {
+            let seconds = elapsed.as_secs_f64();
+            if seconds > 0.0 {
+                let (vx, vy) = self.velocity;
+                self.velocity = (
+                    vx + (dx / seconds - vx) * VELOCITY_SMOOTHING,
+                    vy + (dy / seconds - vy) * VELOCITY_SMOOTHING,
+                );
+            }
+        }
+        self.dragged_at = Some(now);
+    }
+
+    /// Moves the board by the velocity of the released drag decaying exponentially
+    /// over `elapsed` time, it stops once it slows down or reaches the limit of the translation.
+    /// Should be called every frame.
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn glide(&mut self, elapsed: Duration) {
+        if self.moving.is_some() || !self.gliding() {
+            return;
+        }
+        let inertia = f64::from(self.inertia);
+        let decay = (-elapsed.as_secs_f64() / inertia).exp();
+        // Distance travelled is the integral of the decaying velocity over the elapsed time.
+        let travelled = inertia * (1.0 - decay);
+        let before = self.translation;
+        self.pan(self.velocity.0 * travelled, self.velocity.1 * travelled);
+        self.velocity = (self.velocity.0 * decay, self.velocity.1 * decay);
+        if self.translation == before || self.velocity.0.hypot(self.velocity.1) < MIN_GLIDE_SPEED {
+            self.velocity = (0.0, 0.0);
+        }
+    }
+
+    /// Returns whether the board is still gliding after a drag was released.
+    #[inline]
+    #[must_use]
+    pub fn gliding(&self) -> bool {
+        self.velocity != (0.0, 0.0)
+    }
+
+    /// Returns time constant of the glide after a drag in seconds.
+    #[inline]
+    #[must_use]
+    pub fn inertia(&self) -> f32 {
+        self.inertia
+    }
+
+    /// Changes time constant of the glide after a drag in seconds,
+    /// 0 stops the board as soon as the drag is released.
+    pub fn set_inertia(&mut self, inertia: f32) {
+        self.inertia = inertia.clamp(0.0, MAX_PAN_INERTIA);
+        if self.inertia <= 0.0 {
+            self.velocity = (0.0, 0.0);
+        }
+    }
+
+    /// Multiplies the zoom by `factor`, keeping the point of the board
+    /// at given position in the window in place.
+    #[allow(clippy::cast_possible_truncation)]
+    fn zoom_at(&mut self, factor: f32, centre: PhysicalPosition<f64>) {
+        if self.native.is_some() {
+            return;
+        }
+        let (width, height) = self.view_size();
+        let x = centre.x % width * 2.0 / width - 1.0;
+        let y = centre.y * 2.0 / height - 1.0;
+        let scale = (self.scale * factor).clamp(0.5, 1000.0);
+        self.translation.x += x as f32 * (1.0 / scale - 1.0 / self.scale);
+        self.translation.y += y as f32 * (self.ratio / scale - self.ratio / self.scale);
+        self.scale = scale;
+        self.clamp_translation();
+    }
+
+    /// Returns mouse bindings used by the camera.
+    #[inline]
+    #[must_use]
+    pub fn bindings(&self) -> MouseBindings {
+        self.bindings
+    }
+
+    /// Changes mouse bindings used by the camera.
+    #[inline]
+    pub fn set_bindings(&mut self, bindings: MouseBindings) {
+        self.bindings = bindings;
+    }
+
+    /// Returns shape of the cells.
+    #[inline]
+    #[must_use]
+    pub fn aspect(&self) -> CellAspect {
+        self.aspect
+    }
+
+    /// Changes shape of the cells.
+    pub fn set_aspect(&mut self, aspect: CellAspect) {
+        self.aspect = aspect;
+        self.update_ratio();
+    }
+
+    /// Splits the window into given number of views side by side.
+    /// Every view shows the same part of its board, the cursor points at the same cell in all.
+    ///
+    /// # Panics
+    ///
+    /// - when `views` is zero.
+    pub fn set_views(&mut self, views: u32) {
+        assert!(views > 0, "Camera needs at least one view");
+        self.views = views;
+        self.update_ratio();
+    }
+
+    /// Returns size of a single view in pixels.
+    fn view_size(&self) -> (f64, f64) {
+        (
+            self.screen_size.0 / f64::from(self.views),
+            self.screen_size.1,
+        )
+    }
+
+    /// Recalculates the ratio after the aspect, the window size or the views changed.
+    /// In the native mode the scale is recalculated as well, so that every cell
+    /// covers a single pixel regardless of the aspect.
+    #[allow(clippy::cast_possible_truncation)]
+    fn update_ratio(&mut self) {
+        if self.native.is_some() {
+            let (width, height) = self.view_size();
+            self.scale = (f64::from(self.game_size.0) / width) as f32;
+            self.ratio = (f64::from(self.scale) * height / f64::from(self.game_size.1)) as f32;
+        } else {
+            self.ratio = aspect_ratio(self.aspect, self.game_ratio, self.view_size());
+        }
+        self.clamp_translation();
+    }
+
+    /// Returns whether every cell is drawn as exactly one pixel.
+    #[inline]
+    #[must_use]
+    pub fn native_mode(&self) -> bool {
+        self.native.is_some()
+    }
+
+    /// Locks the scale so that every cell is drawn as exactly one pixel and aligns
+    /// cells with pixels, the board can then only be moved by whole cells.
+    /// The previous scale is restored when the mode is left.
+    pub fn set_native_mode(&mut self, native: bool) {
+        match (native, self.native) {
+            (true, None) => self.native = Some(self.scale),
+            (false, Some(scale)) => {
+                self.native = None;
+                self.scale = scale;
+            }
+            _ => return,
+        }
+        self.update_ratio();
+    }
+
+    /// Returns the translation used to draw the board. In the native mode it is rounded,
+    /// so that edges of cells land on edges of pixels and the board moves by whole cells.
+    fn drawn_translation(&self) -> Vec3 {
+        if self.native.is_none() {
+            return self.translation;
+        }
+        // An edge of a cell is drawn at `cells * (translation - 1) / 2 + pixels / 2`
+        // pixels from the edge of the view, rounding it aligns all of them.
+        let (width, height) = self.view_size();
+        #[allow(clippy::cast_possible_truncation)]
+        let snap = |translation: f32, cells: u32, pixels: f64| {
+            let cells = f64::from(cells);
+            let edge = ((cells * (f64::from(translation) - 1.0) + pixels) / 2.0).round();
+            (1.0 + (2.0 * edge - pixels) / cells) as f32
+        };
+        Vec3::new(
+            snap(self.translation.x, self.game_size.0, width),
+            snap(self.translation.y, self.game_size.1, height),
+            0.0,
+        )
+    }
+
+    /// Returns zoom and position of the camera.
+    #[must_use]
+    pub fn settings(&self) -> CameraSettings {
+        CameraSettings {
+            scale: self.native.unwrap_or(self.scale),
+            translation: [self.translation.x, self.translation.y],
+        }
+    }
+
+    /// Restores zoom and position of the camera, limited like when moved with the mouse.
+    /// Settings with values that are not finite are ignored.
+    pub fn apply_settings(&mut self, settings: CameraSettings) {
+        let [x, y] = settings.translation;
+        if !(settings.scale.is_finite() && x.is_finite() && y.is_finite()) {
+            return;
+        }
+        let scale = settings.scale.clamp(0.5, 1000.0);
+        match &mut self.native {
+            Some(restored) => *restored = scale,
+            None => self.scale = scale,
+        }
+        self.translation = Vec3::new(x, y, 0.0);
+        self.clamp_translation();
+    }
+
+    /// Zooms and moves the camera so that given cells fill most of the window.
+    /// In the native mode the camera is only moved to centre the cells.
+    #[allow(clippy::cast_precision_loss)]
+    pub fn fit(&mut self, bounds: LiveBounds) {
+        let (min_x, min_y, max_x, max_y) = bounds;
+        let half_x = (max_x - min_x + 1) as f32 / self.game_size.0 as f32;
+        let half_y = (max_y - min_y + 1) as f32 / self.game_size.1 as f32;
+
+        if self.native.is_none() {
+            self.scale = (FIT_FILL / half_x)
+                .min(FIT_FILL * self.ratio / half_y)
+                .clamp(0.5, 1000.0);
+        }
+        self.center_on(bounds);
+    }
+
+    /// Moves the camera so that the centre of given cells is in the middle of the window,
+    /// keeping the current zoom.
+    #[allow(clippy::cast_precision_loss)]
+    pub fn center_on(&mut self, (min_x, min_y, max_x, max_y): LiveBounds) {
+        let (width, height) = (self.game_size.0 as f32, self.game_size.1 as f32);
+        let centre_x = (min_x + max_x + 1) as f32 / width - 1.0;
+        let centre_y = (min_y + max_y + 1) as f32 / height - 1.0;
+        self.translation = Vec3::new(-centre_x, -centre_y, 0.0);
+        self.clamp_translation();
+    }
+
+    /// Changes size of the area the board is drawn into, empty sizes are ignored.
+    pub fn set_screen_size(&mut self, screen_size: PhysicalSize<u32>) {
+        if screen_size.height != 0 && screen_size.width != 0 {
+            self.screen_size = (f64::from(screen_size.width), f64::from(screen_size.height));
+            self.update_ratio();
+        }
+    }
+
+    /// Changes dimensions of the board, which grew evenly around its middle.
+    /// Zoom and position are adjusted, so the same cells stay where they were drawn.
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn set_game_size(&mut self, game_size: (u32, u32)) {
+        let growth_x = (f64::from(game_size.0) / f64::from(self.game_size.0)) as f32;
+        let growth_y = (f64::from(game_size.1) / f64::from(self.game_size.1)) as f32;
+        match &mut self.native {
+            Some(restored) => *restored = (*restored * growth_x).clamp(0.5, 1000.0),
+            None => self.scale = (self.scale * growth_x).clamp(0.5, 1000.0),
+        }
+        self.translation.x /= growth_x;
+        self.translation.y /= growth_y;
+        self.game_size = game_size;
+        self.game_ratio = f64::from(game_size.0) / f64::from(game_size.1);
+        self.update_ratio();
+    }
+
+    /// Returns whether the camera is being moved with the mouse.
+    #[inline]
+    #[must_use]
+    pub fn moving(&self) -> bool {
+        self.moving.is_some()
+    }
+
+    /// Returns whether the camera was moved since a mouse button was last pressed,
+    /// so that releasing the button ends a drag rather than a click.
+    #[inline]
+    #[must_use]
+    pub fn dragged(&self) -> bool {
+        self.dragged
+    }
+
+    /// Limits the translation so the visible region always overlaps the board.
+    ///
+    /// Half of the visible region spans `1 / scale` of the board quad horizontally
+    /// and `ratio / scale` vertically. When it is smaller than the board it has to stay
+    /// inside of the board, when it is bigger the board has to stay inside of it.
+    fn clamp_translation(&mut self) {
+        let max_x = (1.0 / self.scale - 1.0).abs();
+        let max_y = (self.ratio / self.scale - 1.0).abs();
+        self.translation.x = self.translation.x.clamp(-max_x, max_x);
+        self.translation.y = self.translation.y.clamp(-max_y, max_y);
+    }
+
     /// Returns the view matrix.
     #[must_use]
     pub fn matrix(&self) -> Mat4 {
         Mat4::from_scale(Vec3::new(self.scale, self.scale / self.ratio, 1.0))
-            * Mat4::from_translation(self.translation)
+            * Mat4::from_translation(self.drawn_translation())
+    }
+
+    /// Returns size of a single device pixel in cells on both axes.
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn pixel_size(&self) -> [f32; 2] {
+        let (width, height) = self.view_size();
+        [
+            (f64::from(self.game_size.0) / (f64::from(self.scale) * width)) as f32,
+            (f64::from(self.game_size.1) / (f64::from(self.scale / self.ratio) * height)) as f32,
+        ]
+    }
+
+    /// Returns where cells are drawn in the window by [`Camera::matrix`].
+    #[must_use]
+    pub fn cell_mapping(&self) -> CellMapping {
+        let (width, height) = self.view_size();
+        let matrix = self.matrix();
+        let origin = matrix * Vec4::new(-1.0, -1.0, 0.0, 1.0);
+        let corner = matrix * Vec4::new(1.0, 1.0, 0.0, 1.0);
+        let to_screen = |ndc: f32, size: f64| (f64::from(ndc) + 1.0) / 2.0 * size;
+        let origin = [to_screen(origin.x, width), to_screen(origin.y, height)];
+        CellMapping {
+            origin,
+            cell_size: [
+                (to_screen(corner.x, width) - origin[0]) / f64::from(self.game_size.0),
+                (to_screen(corner.y, height) - origin[1]) / f64::from(self.game_size.1),
+            ],
+            view_width: width,
+            views: self.views,
+            game_size: self.game_size,
+        }
     }
 
     /// Calculates the position of the mouse in the game coordinates.
+    /// Returns `None` when the mouse is outside of the board.
     #[must_use]
     #[allow(clippy::cast_sign_loss)]
     #[allow(clippy::cast_possible_truncation)]
-    pub fn cursor_game_position(&self) -> [u32; 2] {
-        let mut pos_x = self.cursor_pos.x - self.screen_size.0 / 2.0;
-        pos_x /= self.screen_size.0 * f64::from(self.scale);
-        let mut pos_y = self.cursor_pos.y - self.screen_size.1 / 2.0;
-        pos_y /= self.screen_size.1 * f64::from(self.scale);
-        pos_y *= f64::from(self.ratio);
+    pub fn cursor_game_position(&self) -> Option<[u32; 2]> {
+        let (x, y) = self.screen_to_game(self.cursor_pos);
+        let (width, height) = (f64::from(self.game_size.0), f64::from(self.game_size.1));
+        ((0.0..width).contains(&x) && (0.0..height).contains(&y)).then(|| [x as u32, y as u32])
+    }
+
+    /// Converts a position in the window into the game coordinates
+    /// by inverting the transformation of [`Camera::matrix`].
+    ///
+    /// The board is drawn as a quad from `-1` to `1` on both axes,
+    /// which maps to cells from `0` to the game size.
+    /// When the window is split, positions in every view map to the same cells.
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn screen_to_game(&self, position: PhysicalPosition<f64>) -> (f64, f64) {
+        let (width, height) = self.view_size();
+        let x = if self.views > 1 {
+            position.x % width
+        } else {
+            position.x
+        };
+        let ndc = Vec4::new(
+            (x * 2.0 / width - 1.0) as f32,
+            (position.y * 2.0 / height - 1.0) as f32,
+            0.0,
+            1.0,
+        );
+        let quad = self.matrix().inverse() * ndc;
+        (
+            (f64::from(quad.x) + 1.0) / 2.0 * f64::from(self.game_size.0),
+            (f64::from(quad.y) + 1.0) / 2.0 * f64::from(self.game_size.1),
+        )
+    }
+}
+
+/// Returns how much higher the board quad is drawn than the window for given cell aspect.
+///
+/// Square cells need the quad to have proportions of the board,
+/// stretched cells fill the window with the quad.
+#[allow(clippy::cast_possible_truncation)]
+fn aspect_ratio(aspect: CellAspect, game_ratio: f64, screen_size: (f64, f64)) -> f32 {
+    match aspect {
+        CellAspect::Square => (game_ratio / (screen_size.0 / screen_size.1)) as f32,
+        CellAspect::Stretch => 1.0,
+    }
+}
 
-        pos_x += f64::from(self.translation.x) / -2.0;
-        pos_y += f64::from(self.translation.y) / -2.0;
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        pos_x *= f64::from(self.game_size.0);
-        pos_y *= f64::from(self.game_size.1);
+    /// Maps a point in the game coordinates to the window with [`Camera::matrix`].
+    #[allow(clippy::cast_possible_truncation)]
+    fn game_to_screen(camera: &Camera, x: f64, y: f64) -> PhysicalPosition<f64> {
+        let quad = Vec4::new(
+            (x / f64::from(camera.game_size.0) * 2.0 - 1.0) as f32,
+            (y / f64::from(camera.game_size.1) * 2.0 - 1.0) as f32,
+            0.0,
+            1.0,
+        );
+        let ndc = camera.matrix() * quad;
+        PhysicalPosition::new(
+            (f64::from(ndc.x) + 1.0) / 2.0 * camera.screen_size.0,
+            (f64::from(ndc.y) + 1.0) / 2.0 * camera.screen_size.1,
+        )
+    }
+
+    /// Checks that centres of cells drawn under the cursor map back to the same cells.
+    fn assert_round_trip(mut camera: Camera) {
+        for cell in [[0, 0], [1, 2], [17, 5], [63, 31], [40, 20]] {
+            let x = f64::from(cell[0]) + 0.5;
+            let y = f64::from(cell[1]) + 0.5;
+            camera.cursor_pos = game_to_screen(&camera, x, y);
+            let (back_x, back_y) = camera.screen_to_game(camera.cursor_pos);
+            assert!((back_x - x).abs() < 1e-3 && (back_y - y).abs() < 1e-3);
+            assert_eq!(camera.cursor_game_position(), Some(cell));
+        }
+    }
+
+    #[test]
+    fn round_trip_matching_aspect() {
+        assert_round_trip(Camera::new((64, 32), PhysicalSize::new(800, 400)));
+    }
+
+    #[test]
+    fn round_trip_wide_window() {
+        assert_round_trip(Camera::new((64, 32), PhysicalSize::new(1600, 400)));
+    }
+
+    #[test]
+    fn round_trip_tall_window() {
+        assert_round_trip(Camera::new((64, 32), PhysicalSize::new(400, 900)));
+    }
+
+    #[test]
+    fn round_trip_stretched() {
+        let mut camera = Camera::new((64, 32), PhysicalSize::new(400, 900));
+        camera.set_aspect(CellAspect::Stretch);
+        assert_round_trip(camera);
+    }
 
-        pos_x += f64::from(self.game_size.0) / 2.0;
-        pos_y += f64::from(self.game_size.1) / 2.0;
+    #[test]
+    fn round_trip_zoomed_and_panned() {
+        let mut camera = Camera::new((64, 32), PhysicalSize::new(1024, 768));
+        camera.scale = 3.5;
+        camera.translation = Vec3::new(0.4, -0.25, 0.0);
+        assert_round_trip(camera);
+    }
+
+    #[test]
+    fn split_views_point_at_same_cell() {
+        let mut camera = Camera::new((64, 32), PhysicalSize::new(1600, 400));
+        camera.set_views(2);
+        for x in [10.0, 350.0, 799.0] {
+            camera.cursor_pos = PhysicalPosition::new(x, 200.0);
+            let left = camera.cursor_game_position();
+            camera.cursor_pos = PhysicalPosition::new(x + 800.0, 200.0);
+            assert!(left.is_some());
+            assert_eq!(camera.cursor_game_position(), left);
+        }
+    }
+
+    #[test]
+    fn zoom_at_keeps_point_under_centre() {
+        let mut camera = Camera::new((64, 32), PhysicalSize::new(1024, 768));
+        let centre = PhysicalPosition::new(700.0, 200.0);
+        let before = camera.screen_to_game(centre);
+        camera.zoom_at(3.0, centre);
+        let after = camera.screen_to_game(centre);
+        assert!((before.0 - after.0).abs() < 1e-3 && (before.1 - after.1).abs() < 1e-3);
+    }
+
+    #[test]
+    fn center_on_keeps_zoom() {
+        let mut camera = Camera::new((64, 32), PhysicalSize::new(800, 400));
+        camera.scale = 4.0;
+        camera.center_on((40, 20, 47, 23));
+        assert!((camera.scale - 4.0).abs() < f32::EPSILON);
+        let centre = camera.screen_to_game(PhysicalPosition::new(400.0, 200.0));
+        assert!((centre.0 - 44.0).abs() < 1e-3 && (centre.1 - 22.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn growing_board_keeps_cells_in_place() {
+        let mut camera = Camera::new((64, 32), PhysicalSize::new(1024, 768));
+        camera.scale = 3.5;
+        camera.translation = Vec3::new(0.4, -0.25, 0.0);
+        let before = game_to_screen(&camera, 20.5, 10.5);
+        camera.set_game_size((128, 64));
+        let after = game_to_screen(&camera, 52.5, 26.5);
+        assert!((before.x - after.x).abs() < 1e-3 && (before.y - after.y).abs() < 1e-3);
+    }
+
+    #[test]
+    fn pixel_size_matches_drawn_cells() {
+        let mut camera = Camera::new((200, 100), PhysicalSize::new(800, 600));
+        camera.scale = 3.0;
+        let [width, height] = camera.pixel_size();
+        let origin = game_to_screen(&camera, 0.0, 0.0);
+        let corner = game_to_screen(&camera, f64::from(width), f64::from(height));
+        assert!((corner.x - origin.x - 1.0).abs() < 1e-3);
+        assert!((corner.y - origin.y - 1.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn cell_mapping_matches_drawn_cells() {
+        let mut camera = Camera::new((64, 32), PhysicalSize::new(1024, 768));
+        camera.scale = 2.5;
+        camera.translation = Vec3::new(-0.3, 0.2, 0.0);
+        let mapping = camera.cell_mapping();
+        let cell = game_to_screen(&camera, 10.0, 20.0);
+        assert!((mapping.origin[0] + 10.0 * mapping.cell_size[0] - cell.x).abs() < 1e-3);
+        assert!((mapping.origin[1] + 20.0 * mapping.cell_size[1] - cell.y).abs() < 1e-3);
+    }
+
+    #[test]
+    fn native_mode_draws_cell_per_pixel() {
+        let mut camera = Camera::new((300, 200), PhysicalSize::new(1024, 767));
+        camera.translation = Vec3::new(0.123, -0.456, 0.0);
+        camera.set_native_mode(true);
+        let mapping = camera.cell_mapping();
+        for ((pixel, origin), size) in camera
+            .pixel_size()
+            .into_iter()
+            .zip(mapping.origin)
+            .zip(mapping.cell_size)
+        {
+            assert!((pixel - 1.0).abs() < 1e-3);
+            assert!((size - 1.0).abs() < 1e-3);
+            assert!((origin - origin.round()).abs() < 1e-2);
+        }
+        assert_round_trip(camera);
+    }
+
+    #[test]
+    fn leaving_native_mode_restores_scale() {
+        let mut camera = Camera::new((64, 32), PhysicalSize::new(1024, 768));
+        camera.scale = 2.5;
+        camera.set_native_mode(true);
+        camera.zoom_at(3.0, PhysicalPosition::new(100.0, 100.0));
+        camera.set_native_mode(false);
+        assert!((camera.scale - 2.5).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn glide_slows_down_and_stops() {
+        let mut camera = Camera::new((64, 32), PhysicalSize::new(1024, 768));
+        camera.scale = 4.0;
+        camera.set_inertia(0.2);
+        camera.velocity = (200.0, 0.0);
+        camera.glide(Duration::from_millis(50));
+        let first = camera.translation.x;
+        camera.glide(Duration::from_millis(50));
+        let second = camera.translation.x - first;
+        assert!(first > 0.0 && second > 0.0 && second < first);
+        for _ in 0..100 {
+            camera.glide(Duration::from_millis(50));
+        }
+        assert!(!camera.gliding());
+    }
 
-        [pos_x as u32, pos_y as u32]
+    #[test]
+    fn outside_of_board() {
+        let mut camera = Camera::new((64, 32), PhysicalSize::new(800, 800));
+        camera.scale = 0.5;
+        camera.cursor_pos = PhysicalPosition::new(1.0, 1.0);
+        assert_eq!(camera.cursor_game_position(), None);
     }
 }
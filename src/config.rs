@@ -1,4 +1,17 @@
-use clap::Parser;
+use std::{
+    iter,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use clap::{Args, Parser};
+use log::LevelFilter;
+use vulkano::image::SampleCount;
+
+use crate::{
+    vulkan::ValidationLevel, MouseBindings, Neighbourhood, Palette, Rule, SavedBoard, Topology,
+    WeightedRule, DEFAULT_BACKGROUND, MAX_UI_SCALE, MIN_UI_SCALE,
+};
 
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
@@ -9,6 +22,201 @@ pub struct Config {
     /// Height of the simulation
     #[arg(long, default_value_t = 1024)]
     height: u32,
+    /// Initial simulation speed in generations per second [default: 60]
+    #[arg(long)]
+    speed: Option<u32>,
+    /// Highest number of frames drawn per second, also with vertical synchronization
+    #[arg(long, value_parser = parse_positive)]
+    max_fps: Option<u32>,
+    /// Show grid on startup
+    #[arg(long)]
+    grid: bool,
+    /// Rule of the simulation in B3/S23 notation
+    #[arg(long, default_value_t = Rule::default())]
+    rule: Rule,
+    /// Rule of a second board drawn next to the main one, starting from the same cells
+    #[arg(long)]
+    compare_rule: Option<Rule>,
+    /// Cells counted as neighbours of a cell
+    #[arg(long, value_enum, default_value_t = Neighbourhood::Moore)]
+    neighbourhood: Neighbourhood,
+    /// Way the edges of the board are connected
+    #[arg(long, value_enum, default_value_t = Topology::Bounded)]
+    topology: Topology,
+    /// Number of bits used to store a single cell (1, 8 or 32),
+    /// narrower cells are packed together into 32-bit words
+    #[arg(long, default_value_t = 32, value_parser = parse_cell_bits)]
+    cell_bits: u32,
+    /// Number of samples used for anti-aliasing (1, 2, 4 or 8)
+    #[arg(long, default_value = "1", value_parser = parse_samples)]
+    samples: SampleCount,
+    /// Width and height of compute shader work groups
+    #[arg(long, default_value_t = 32, value_parser = parse_positive)]
+    workgroup: u32,
+    /// Load words of every work group into shared memory before computing cells
+    #[arg(long)]
+    tiled: bool,
+    /// TOML file with weights of the eight neighbours and ranges of their weighted sums
+    /// a cell is born and survives with, replacing the counts of the rule
+    #[arg(long, value_name = "PATH", value_parser = parse_weights)]
+    weights: Option<WeightedRule>,
+    /// Number of past generations kept for stepping back
+    #[arg(long, default_value_t = 16)]
+    history_depth: usize,
+    /// Largest part of the device memory the board buffers may take (0 to 1)
+    #[arg(long, default_value_t = 0.75, value_parser = parse_fraction)]
+    max_memory_fraction: f64,
+    /// Mouse buttons used to move the camera and edit cells [default: classic]
+    #[arg(long, value_enum)]
+    mouse_bindings: Option<MouseBindings>,
+    /// Pattern file loaded on startup, centred on the board (.cells or .rle)
+    #[arg(long)]
+    pattern: Option<PathBuf>,
+    /// Image loaded on startup as the board (PNG), scaled to cover the board and cropped
+    #[arg(long, conflicts_with = "pattern")]
+    image: Option<PathBuf>,
+    /// Luminance below which pixels of the image become alive cells (0 to 255)
+    #[arg(long, default_value_t = 128)]
+    image_threshold: u8,
+    /// Randomize only a central square of given size on startup, leaving the rest dead,
+    /// its cells are alive with probability --density and the same --seed gives the same soup
+    #[arg(
+        long,
+        value_name = "SIZE",
+        value_parser = parse_positive,
+        conflicts_with_all = ["pattern", "image"]
+    )]
+    soup_size: Option<u32>,
+    /// Probability of a cell of the soup being alive (greater than 0, at most 1)
+    #[arg(long, default_value_t = 0.5, value_parser = parse_fraction, requires = "soup_size")]
+    density: f64,
+    /// Seed of the soup, a random one is used and logged when not given
+    #[arg(long, requires = "soup_size")]
+    seed: Option<u64>,
+    /// Allow fetching patterns by name over the network in the controls
+    #[cfg(feature = "online")]
+    #[arg(long)]
+    online: bool,
+    /// URL patterns are fetched from, `{name}` is replaced with the pattern name
+    #[cfg(feature = "online")]
+    #[arg(long, default_value = crate::DEFAULT_PATTERN_URL)]
+    pattern_url: String,
+    /// Disable vertical synchronization
+    #[arg(long)]
+    no_vsync: bool,
+    /// Background color as a hex sRGB value like #bcbcbc [default: #bcbcbc]
+    #[arg(long, value_parser = parse_color)]
+    background: Option<[u8; 3]>,
+    /// Colors of the board, --background still replaces their background [default: classic]
+    #[arg(long, value_enum)]
+    palette: Option<Palette>,
+    /// Initial zoom of the camera, 1 fits the board into the window (0.5 to 1000)
+    #[arg(long, value_parser = parse_finite)]
+    zoom: Option<f32>,
+    /// Scale of the controls in physical pixels per point, the window scale factor
+    /// when not given (0.5 to 4)
+    #[arg(long, value_parser = parse_ui_scale)]
+    ui_scale: Option<f32>,
+    /// Column of the board initially shown in the centre of the window
+    #[arg(long, value_parser = parse_finite)]
+    pan_x: Option<f32>,
+    /// Row of the board initially shown in the centre of the window
+    #[arg(long, value_parser = parse_finite)]
+    pan_y: Option<f32>,
+    /// Frames per second drawn while the simulation is paused and nothing changes
+    #[arg(long, default_value_t = 10, value_parser = parse_positive)]
+    idle_fps: u32,
+    /// Milliseconds between copies of the board read for the population shown in the controls
+    #[arg(long, default_value_t = 250, value_parser = parse_positive)]
+    readback_interval: u32,
+    /// Seconds generations may take before the device is considered lost and the program exits
+    #[arg(long, default_value_t = 10, value_parser = parse_positive)]
+    gpu_timeout: u32,
+    /// Smallest number of generations between population samples exported as CSV,
+    /// samples are taken when the board is read back for the population
+    #[arg(long, value_name = "GENERATIONS", default_value_t = 1, value_parser = parse_positive)]
+    population_interval: u32,
+    /// Path of the CSV file population samples are exported to
+    #[arg(long, default_value = "population.csv")]
+    population_path: PathBuf,
+    /// Compute a generation every given number of milliseconds instead of the selected speed,
+    /// time the simulation falls behind is caught up later instead of being dropped
+    #[arg(long, value_name = "MS", value_parser = parse_positive)]
+    fixed_step: Option<u32>,
+    /// Keep the simulation running while the window is not focused
+    #[arg(long)]
+    run_unfocused: bool,
+    /// Lowest severity of reported validation layer messages, only in debug builds
+    #[arg(long, value_enum, default_value_t = ValidationLevel::Warning)]
+    validation: ValidationLevel,
+    /// Lowest severity of logged messages (off, error, warn, info, debug or trace),
+    /// RUST_LOG overrides it
+    #[arg(long, default_value_t = LevelFilter::Info)]
+    log_level: LevelFilter,
+    /// Instead of opening the window compute given number of generations of a seeded
+    /// random board copying it before every step and alternating between two buffers,
+    /// print both timings and whether the final boards are identical
+    #[arg(long, value_name = "GENERATIONS", value_parser = parse_positive)]
+    bench: Option<u32>,
+    /// Title of the window [default: game_of_life]
+    #[arg(long)]
+    title: Option<String>,
+    /// Title of the window refreshed while running, {title}, {rule}, {width}, {height}
+    /// and {generation} are replaced with their values, an empty format keeps the title fixed
+    #[arg(
+        long,
+        value_name = "FORMAT",
+        default_value = "{title} - {rule} - {width}x{height} - generation {generation}"
+    )]
+    title_format: String,
+    /// Initial width of the window in pixels, clamped to the primary monitor
+    #[arg(long, default_value_t = 1280, value_parser = parse_positive)]
+    window_width: u32,
+    /// Initial height of the window in pixels, clamped to the primary monitor
+    #[arg(long, default_value_t = 720, value_parser = parse_positive)]
+    window_height: u32,
+    /// Save the board when the window is closed, so that --resume can restore it
+    #[arg(long)]
+    autosave_on_exit: bool,
+    /// Restore the board saved on exit instead of starting with an empty board
+    #[arg(long, conflicts_with_all = ["pattern", "image", "soup_size"])]
+    resume: bool,
+    /// File the board is saved to on exit and restored from
+    /// [default: board.bin next to the settings file]
+    #[arg(long, value_name = "PATH")]
+    autosave_path: Option<PathBuf>,
+    /// Neither restore nor save settings of the controls and the camera
+    #[arg(long)]
+    no_settings: bool,
+    #[command(flatten)]
+    record: RecordConfig,
+    #[command(flatten)]
+    export: ExportConfig,
+    #[command(flatten)]
+    expand: ExpandConfig,
+}
+
+/// Options of the GIF recording.
+#[derive(Args, Clone)]
+pub struct RecordConfig {
+    /// Start recording a GIF on startup
+    #[arg(long = "record")]
+    enabled: bool,
+    /// Path of the recorded GIF
+    #[arg(long = "record-path", default_value = "recording.gif")]
+    path: PathBuf,
+    /// Size of a single cell in pixels of the recording
+    #[arg(long = "record-scale", default_value_t = 2)]
+    scale: u16,
+    /// Number of generations between recorded frames
+    #[arg(long = "record-stride", default_value_t = 1)]
+    stride: u32,
+    /// Maximum number of recorded frames
+    #[arg(long = "record-max-frames", default_value_t = 500)]
+    max_frames: u32,
+    /// Maximum width and height of the recording in pixels
+    #[arg(long = "record-max-size", default_value_t = 512)]
+    max_size: u16,
 }
 
 impl Config {
@@ -18,4 +226,583 @@ impl Config {
     pub fn size(&self) -> (u32, u32) {
         (self.width, self.height)
     }
+
+    /// Returns initial speed of the simulation, `None` when the saved one should be used.
+    #[inline]
+    #[must_use]
+    pub fn speed(&self) -> Option<u32> {
+        self.speed
+    }
+
+    /// Returns highest number of frames drawn per second, `None` when the saved one should be used.
+    #[inline]
+    #[must_use]
+    pub fn max_fps(&self) -> Option<u32> {
+        self.max_fps
+    }
+
+    /// Returns lowest severity of reported validation layer messages.
+    #[inline]
+    #[must_use]
+    pub fn validation(&self) -> ValidationLevel {
+        self.validation
+    }
+
+    /// Returns title of the window, `None` when the application name should be used.
+    #[inline]
+    #[must_use]
+    pub fn title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    /// Returns format of the window title refreshed while running,
+    /// `None` when the title should stay fixed.
+    #[inline]
+    #[must_use]
+    pub fn title_format(&self) -> Option<&str> {
+        Some(self.title_format.as_str()).filter(|format| !format.is_empty())
+    }
+
+    /// Returns initial width and height of the window in pixels.
+    #[inline]
+    #[must_use]
+    pub fn window_size(&self) -> (u32, u32) {
+        (self.window_width, self.window_height)
+    }
+
+    /// Returns whether the grid should be visible on startup.
+    #[inline]
+    #[must_use]
+    pub fn grid(&self) -> bool {
+        self.grid
+    }
+
+    /// Returns rule of the simulation.
+    #[inline]
+    #[must_use]
+    pub fn rule(&self) -> Rule {
+        self.rule
+    }
+
+    /// Returns rule of the board compared side by side with the main one.
+    #[inline]
+    #[must_use]
+    pub fn compare_rule(&self) -> Option<Rule> {
+        self.compare_rule
+    }
+
+    /// Returns neighbourhood of the simulation.
+    #[inline]
+    #[must_use]
+    pub fn neighbourhood(&self) -> Neighbourhood {
+        self.neighbourhood
+    }
+
+    /// Returns topology of the board.
+    #[inline]
+    #[must_use]
+    pub fn topology(&self) -> Topology {
+        self.topology
+    }
+
+    /// Checks whether combinations of options are valid.
+    ///
+    /// # Errors
+    ///
+    /// - when a rule uses more neighbours than the neighbourhood has.
+    /// - when a rule has more states than a cell can store.
+    pub fn validate(&self) -> Result<(), String> {
+        for rule in iter::once(self.rule).chain(self.compare_rule) {
+            if !rule.fits_cell_bits(self.cell_bits) {
+                return Err(format!(
+                    "rule {} has {} states, which do not fit in {} cell bits",
+                    rule,
+                    rule.states(),
+                    self.cell_bits,
+                ));
+            }
+            if !rule.fits(self.neighbourhood) {
+                return Err(format!(
+                    "rule {} uses more than {} neighbours of the {} neighbourhood",
+                    rule,
+                    self.neighbourhood.max_neighbours(),
+                    self.neighbourhood.name(),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns number of bits used to store a single cell.
+    #[inline]
+    #[must_use]
+    pub fn cell_bits(&self) -> u32 {
+        self.cell_bits
+    }
+
+    /// Returns number of samples used for anti-aliasing.
+    #[inline]
+    #[must_use]
+    pub fn samples(&self) -> SampleCount {
+        self.samples
+    }
+
+    /// Returns width and height of compute shader work groups.
+    #[inline]
+    #[must_use]
+    pub fn workgroup(&self) -> u32 {
+        self.workgroup
+    }
+
+    /// Returns whether the simulation shader loads words of every work group
+    /// into shared memory first.
+    #[inline]
+    #[must_use]
+    pub fn tiled(&self) -> bool {
+        self.tiled
+    }
+
+    /// Returns the weighted rule replacing birth and survival counts of the rule, if any.
+    #[inline]
+    #[must_use]
+    pub fn weights(&self) -> Option<WeightedRule> {
+        self.weights
+    }
+
+    /// Returns number of past generations kept for stepping back.
+    #[inline]
+    #[must_use]
+    pub fn history_depth(&self) -> usize {
+        self.history_depth
+    }
+
+    /// Returns largest part of the device memory the board buffers may take.
+    #[inline]
+    #[must_use]
+    pub fn max_memory_fraction(&self) -> f64 {
+        self.max_memory_fraction
+    }
+
+    /// Returns mouse buttons used to move the camera and edit cells,
+    /// `None` when the saved ones should be used.
+    #[inline]
+    #[must_use]
+    pub fn mouse_bindings(&self) -> Option<MouseBindings> {
+        self.mouse_bindings
+    }
+
+    /// Returns path of the pattern file loaded on startup.
+    #[inline]
+    #[must_use]
+    pub fn pattern(&self) -> Option<&Path> {
+        self.pattern.as_deref()
+    }
+
+    /// Returns path of the image loaded as the board on startup.
+    #[inline]
+    #[must_use]
+    pub fn image(&self) -> Option<&Path> {
+        self.image.as_deref()
+    }
+
+    /// Returns luminance below which pixels of the image become alive cells.
+    #[inline]
+    #[must_use]
+    pub fn image_threshold(&self) -> u8 {
+        self.image_threshold
+    }
+
+    /// Returns size of the random square seeded in the centre of the board on startup.
+    #[inline]
+    #[must_use]
+    pub fn soup_size(&self) -> Option<u32> {
+        self.soup_size
+    }
+
+    /// Returns probability of a cell of the soup being alive.
+    #[inline]
+    #[must_use]
+    pub fn density(&self) -> f64 {
+        self.density
+    }
+
+    /// Returns seed of the soup, `None` when a random one should be used.
+    #[inline]
+    #[must_use]
+    pub fn seed(&self) -> Option<u64> {
+        self.seed
+    }
+
+    /// Returns URL template patterns are fetched from, `None` when fetching is not allowed.
+    #[cfg(feature = "online")]
+    #[inline]
+    #[must_use]
+    pub fn pattern_url(&self) -> Option<&str> {
+        self.online.then_some(self.pattern_url.as_str())
+    }
+
+    /// Returns whether vertical synchronization should be enabled.
+    #[inline]
+    #[must_use]
+    pub fn vsync(&self) -> bool {
+        !self.no_vsync
+    }
+
+    /// Returns frames per second drawn while the simulation is paused and nothing changes.
+    #[inline]
+    #[must_use]
+    pub fn idle_fps(&self) -> u32 {
+        self.idle_fps
+    }
+
+    /// Returns time between copies of the board read for statistics.
+    #[inline]
+    #[must_use]
+    pub fn readback_interval(&self) -> Duration {
+        Duration::from_millis(self.readback_interval.into())
+    }
+
+    /// Returns time generations may take before the device is considered lost.
+    #[inline]
+    #[must_use]
+    pub fn gpu_timeout(&self) -> Duration {
+        Duration::from_secs(self.gpu_timeout.into())
+    }
+
+    /// Returns lowest severity of logged messages.
+    #[inline]
+    #[must_use]
+    pub fn log_level(&self) -> LevelFilter {
+        self.log_level
+    }
+
+    /// Returns smallest number of generations between population samples.
+    #[inline]
+    #[must_use]
+    pub fn population_interval(&self) -> u64 {
+        self.population_interval.into()
+    }
+
+    /// Returns path of the CSV file population samples are exported to.
+    #[inline]
+    #[must_use]
+    pub fn population_path(&self) -> &Path {
+        &self.population_path
+    }
+
+    /// Returns fixed time between generations, `None` when the selected speed is used.
+    #[inline]
+    #[must_use]
+    pub fn fixed_step(&self) -> Option<Duration> {
+        self.fixed_step
+            .map(|interval| Duration::from_millis(interval.into()))
+    }
+
+    /// Returns number of generations of the step benchmark, `None` when the window should be opened.
+    #[inline]
+    #[must_use]
+    pub fn bench(&self) -> Option<u32> {
+        self.bench
+    }
+
+    /// Returns whether the simulation should pause while the window is not focused.
+    #[inline]
+    #[must_use]
+    pub fn pause_unfocused(&self) -> bool {
+        !self.run_unfocused
+    }
+
+    /// Returns background color in sRGB, `None` when the saved one should be used.
+    #[inline]
+    #[must_use]
+    pub fn background(&self) -> Option<[u8; 3]> {
+        self.background
+    }
+
+    /// Returns colors of the board, `None` when the saved ones should be used.
+    #[inline]
+    #[must_use]
+    pub fn palette(&self) -> Option<Palette> {
+        self.palette
+    }
+
+    /// Returns initial zoom of the camera, `None` when the saved one should be used.
+    #[inline]
+    #[must_use]
+    pub fn zoom(&self) -> Option<f32> {
+        self.zoom
+    }
+
+    /// Returns scale of the controls in physical pixels per point,
+    /// `None` when the saved one should be used.
+    #[inline]
+    #[must_use]
+    pub fn ui_scale(&self) -> Option<f32> {
+        self.ui_scale
+    }
+
+    /// Returns column and row of the board initially shown in the centre of the window,
+    /// each `None` when the saved one should be used.
+    #[inline]
+    #[must_use]
+    pub fn pan(&self) -> [Option<f32>; 2] {
+        [self.pan_x, self.pan_y]
+    }
+
+    /// Returns whether the board should be saved when the window is closed.
+    #[inline]
+    #[must_use]
+    pub fn autosave_on_exit(&self) -> bool {
+        self.autosave_on_exit
+    }
+
+    /// Returns whether the board saved on exit should be restored on startup.
+    #[inline]
+    #[must_use]
+    pub fn resume(&self) -> bool {
+        self.resume
+    }
+
+    /// Returns file the board is saved to on exit and restored from,
+    /// `None` when the platform config directory cannot be determined.
+    #[must_use]
+    pub fn autosave_path(&self) -> Option<PathBuf> {
+        self.autosave_path.clone().or_else(SavedBoard::default_path)
+    }
+
+    /// Returns whether settings should be restored on startup and saved on exit.
+    #[inline]
+    #[must_use]
+    pub fn settings(&self) -> bool {
+        !self.no_settings
+    }
+
+    /// Returns options of the GIF recording.
+    #[inline]
+    #[must_use]
+    pub fn record(&self) -> &RecordConfig {
+        &self.record
+    }
+
+    /// Returns options of the PNG frames export.
+    #[inline]
+    #[must_use]
+    pub fn export(&self) -> &ExportConfig {
+        &self.export
+    }
+
+    /// Returns options of the automatic board expansion.
+    #[inline]
+    #[must_use]
+    pub fn expand(&self) -> &ExpandConfig {
+        &self.expand
+    }
+}
+
+/// Options of the PNG frames export.
+#[derive(Args, Clone)]
+pub struct ExportConfig {
+    /// Directory the PNG frames are exported to, export is disabled when not set
+    #[arg(long = "export-dir")]
+    directory: Option<PathBuf>,
+    /// First exported generation
+    #[arg(long = "export-first", default_value_t = 1)]
+    first: u64,
+    /// Last exported generation
+    #[arg(long = "export-last", default_value_t = 1000)]
+    last: u64,
+    /// Number of generations between exported frames
+    #[arg(long = "export-stride", default_value_t = 1)]
+    stride: u64,
+    /// Size of a single cell in pixels of the exported frames
+    #[arg(long = "export-scale", default_value_t = 1)]
+    scale: u32,
+}
+
+/// Options of the automatic board expansion.
+#[derive(Args, Clone)]
+pub struct ExpandConfig {
+    /// Double the board size when live cells come close to the edge
+    #[arg(long = "auto-expand")]
+    enabled: bool,
+    /// Maximum width and height the board is expanded to
+    #[arg(long = "expand-max-size", default_value_t = 4096)]
+    max_size: u32,
+    /// Distance from the edge in cells that triggers the expansion
+    #[arg(long = "expand-margin", default_value_t = 8)]
+    margin: u32,
+}
+
+impl RecordConfig {
+    /// Returns whether recording should start on startup.
+    #[inline]
+    #[must_use]
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Returns path of the recorded GIF.
+    #[inline]
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns size of a single cell in pixels of the recording.
+    #[inline]
+    #[must_use]
+    pub fn scale(&self) -> u16 {
+        self.scale
+    }
+
+    /// Returns number of generations between recorded frames.
+    #[inline]
+    #[must_use]
+    pub fn stride(&self) -> u32 {
+        self.stride
+    }
+
+    /// Returns maximum number of recorded frames.
+    #[inline]
+    #[must_use]
+    pub fn max_frames(&self) -> u32 {
+        self.max_frames
+    }
+
+    /// Returns maximum width and height of the recording in pixels.
+    #[inline]
+    #[must_use]
+    pub fn max_size(&self) -> u16 {
+        self.max_size
+    }
+}
+
+impl ExportConfig {
+    /// Returns directory the PNG frames are exported to.
+    #[inline]
+    #[must_use]
+    pub fn directory(&self) -> Option<&Path> {
+        self.directory.as_deref()
+    }
+
+    /// Returns first exported generation.
+    #[inline]
+    #[must_use]
+    pub fn first(&self) -> u64 {
+        self.first
+    }
+
+    /// Returns last exported generation.
+    #[inline]
+    #[must_use]
+    pub fn last(&self) -> u64 {
+        self.last
+    }
+
+    /// Returns number of generations between exported frames.
+    #[inline]
+    #[must_use]
+    pub fn stride(&self) -> u64 {
+        self.stride
+    }
+
+    /// Returns size of a single cell in pixels of the exported frames.
+    #[inline]
+    #[must_use]
+    pub fn scale(&self) -> u32 {
+        self.scale
+    }
+}
+
+impl ExpandConfig {
+    /// Returns whether the board should be expanded automatically.
+    #[inline]
+    #[must_use]
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Returns maximum width and height the board is expanded to.
+    #[inline]
+    #[must_use]
+    pub fn max_size(&self) -> u32 {
+        self.max_size
+    }
+
+    /// Returns distance from the edge in cells that triggers the expansion.
+    #[inline]
+    #[must_use]
+    pub fn margin(&self) -> u32 {
+        self.margin
+    }
+}
+
+/// Parses number of samples used for anti-aliasing.
+fn parse_samples(value: &str) -> Result<SampleCount, String> {
+    match value.parse::<u32>() {
+        Ok(samples @ (1 | 2 | 4 | 8)) => SampleCount::try_from(samples)
+            .map_err(|_| format!("{samples} samples are not supported")),
+        _ => Err("expected 1, 2, 4 or 8".to_owned()),
+    }
+}
+
+/// Parses number of bits used to store a single cell.
+fn parse_cell_bits(value: &str) -> Result<u32, String> {
+    match value.parse::<u32>() {
+        Ok(bits @ (1 | 8 | 32)) => Ok(bits),
+        _ => Err("expected 1, 8 or 32".to_owned()),
+    }
+}
+
+/// Parses a positive number.
+fn parse_positive(value: &str) -> Result<u32, String> {
+    match value.parse::<u32>() {
+        Ok(size) if size > 0 => Ok(size),
+        _ => Err("expected a positive number".to_owned()),
+    }
+}
+
+/// Parses a hex color like `#bcbcbc`, the leading `#` is optional.
+fn parse_color(value: &str) -> Result<[u8; 3], String> {
+    let hex = value.strip_prefix('#').unwrap_or(value);
+    let error = || {
+        let [r, g, b] = DEFAULT_BACKGROUND;
+        format!("expected a hex color like #{r:02x}{g:02x}{b:02x}")
+    };
+    if hex.len() != 6 || !hex.is_ascii() {
+        return Err(error());
+    }
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).map_err(|_| error());
+    Ok([channel(0)?, channel(2)?, channel(4)?])
+}
+
+/// Parses a finite number.
+fn parse_finite(value: &str) -> Result<f32, String> {
+    match value.parse::<f32>() {
+        Ok(number) if number.is_finite() => Ok(number),
+        _ => Err("expected a finite number".to_owned()),
+    }
+}
+
+/// Loads a weighted rule from the file at given path.
+fn parse_weights(value: &str) -> Result<WeightedRule, String> {
+    WeightedRule::load(Path::new(value)).map_err(|error| error.to_string())
+}
+
+/// Parses a scale of the controls, from [`MIN_UI_SCALE`] to [`MAX_UI_SCALE`].
+fn parse_ui_scale(value: &str) -> Result<f32, String> {
+    match value.parse::<f32>() {
+        Ok(scale) if (MIN_UI_SCALE..=MAX_UI_SCALE).contains(&scale) => Ok(scale),
+        _ => Err(format!(
+            "expected a number from {MIN_UI_SCALE} to {MAX_UI_SCALE}"
+        )),
+    }
+}
+
+/// Parses a fraction greater than 0 and at most 1.
+fn parse_fraction(value: &str) -> Result<f64, String> {
+    match value.parse::<f64>() {
+        Ok(fraction) if fraction > 0.0 && fraction <= 1.0 => Ok(fraction),
+        _ => Err("expected a number greater than 0 and at most 1".to_owned()),
+    }
 }
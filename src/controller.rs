@@ -1,31 +1,340 @@
-use std::{collections::VecDeque, sync::Arc, time::Instant};
+use std::{
+    collections::VecDeque,
+    iter,
+    ops::Range,
+    path::PathBuf,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
+use clap::ValueEnum;
 use egui_winit_vulkano::{egui, Gui};
+use serde::{Deserialize, Serialize};
 use vulkano::{image::ImageViewAbstract, sync::GpuFuture};
 use vulkano_util::renderer::VulkanoWindowRenderer;
 use winit::{
-    event::WindowEvent,
+    event::{ElementState, KeyboardInput, ModifiersState, VirtualKeyCode, WindowEvent},
     event_loop::{EventLoop, EventLoopProxy},
 };
 
-use crate::Message;
+use crate::{
+    CellAspect, CellMapping, CellShading, Config, Distribution, FillMode, LiveBounds, Message,
+    Mirror, Neighbourhood, Rotation, Rule, Settings, Symmetry, Topology, BUILTIN_PATTERNS,
+    DEFAULT_BACKGROUND, DEFAULT_NOISE_FREQUENCY, MAX_BORDER_WIDTH, MAX_CELL_PADDING,
+    MAX_NOISE_FREQUENCY, MAX_PAN_INERTIA, MAX_SPEED, MIN_NOISE_FREQUENCY, PRESETS,
+};
+
+/// Smallest distance between labelled ticks of the ruler in points.
+const RULER_SPACING: f64 = 60.0;
+
+/// Length of ticks of the ruler in points.
+const TICK_LENGTH: f32 = 6.0;
+
+/// Smallest size of a cell in points outlined by the brush outline itself,
+/// smaller cells are surrounded by a circle of this radius instead.
+const BRUSH_OUTLINE_MIN_SIZE: f32 = 6.0;
+
+/// Width of the progress bar of a jump to a generation in points.
+const JUMP_PROGRESS_WIDTH: f32 = 200.0;
+
+/// Smallest scale of the controls in physical pixels per point that can be selected.
+pub(crate) const MIN_UI_SCALE: f32 = 0.5;
+
+/// Largest scale of the controls in physical pixels per point that can be selected.
+pub(crate) const MAX_UI_SCALE: f32 = 4.0;
+
+/// Largest interval of major grid lines in cells that can be selected.
+const MAX_GRID_INTERVAL: u32 = 1000;
+
+/// Highest frame rate limit that can be selected.
+const MAX_FPS: u32 = 1000;
+
+/// Number of per-second samples kept in the frames per second history.
+const FPS_HISTORY_LENGTH: usize = 60;
+
+/// Keyboard shortcuts and mouse controls listed in the help window.
+/// Every new binding should be described here.
+const SHORTCUTS: [(&str, &str); 20] = [
+    ("Tab / H", "Show or hide controls"),
+    ("?", "Show or hide this help"),
+    ("Left arrow", "Step back to the previous generation"),
+    ("Ctrl + arrows", "Shift the board by one cell"),
+    ("P", "Stamp the selected pattern under the cursor"),
+    ("I", "Invert colors"),
+    ("R", "Randomize the board"),
+    ("C", "Clear the board"),
+    ("N", "Clear and randomize the board"),
+    ("L", "Center the camera on live cells"),
+    ("Left drag", "Move the camera (classic bindings)"),
+    (
+        "Middle drag / Space + left drag",
+        "Move the camera (draw bindings)",
+    ),
+    ("Left drag", "Draw cells (draw bindings)"),
+    ("Scroll", "Zoom in and out"),
+    ("Two-finger drag", "Move the camera (touch and trackpad)"),
+    (
+        "Pinch / Ctrl + scroll",
+        "Zoom around the fingers (touch and trackpad)",
+    ),
+    ("Right click", "Flip cell under the cursor"),
+    ("Left click", "Start or end a line (line tool)"),
+    ("Escape", "Forget the start of the line"),
+    ("Left drag", "Fill a rectangle (rectangle tool)"),
+];
+
+/// What happens when the board stops changing.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum StabilityAction {
+    /// The board is not checked.
+    Ignore,
+    /// A label is shown in the controls.
+    #[default]
+    Notify,
+    /// A label is shown and the simulation is paused.
+    Pause,
+}
+
+impl StabilityAction {
+    /// All actions in order they should be displayed.
+    pub const ALL: [Self; 3] = [Self::Ignore, Self::Notify, Self::Pause];
+
+    /// Returns human readable name of the action.
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Ignore => "Ignore",
+            Self::Notify => "Notify",
+            Self::Pause => "Notify and pause",
+        }
+    }
+}
+
+/// What clicking and dragging over the board with the left button does.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DrawTool {
+    /// Cells are drawn one by one, depending on the mouse bindings.
+    #[default]
+    Cell,
+    /// The first click sets the start of a line, the next one draws it.
+    Line,
+    /// Dragging out a rectangle fills it when the button is released.
+    Rectangle,
+}
+
+impl DrawTool {
+    /// All tools in order they should be displayed.
+    pub const ALL: [Self; 3] = [Self::Cell, Self::Line, Self::Rectangle];
+
+    /// Returns human readable name of the tool.
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Cell => "Cells",
+            Self::Line => "Line",
+            Self::Rectangle => "Rectangle",
+        }
+    }
+}
+
+/// Named set of colors the board is drawn with.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Palette {
+    /// Black cells on white.
+    #[default]
+    Classic,
+    /// Bright green cells on black, like an old monochrome monitor.
+    GreenPhosphor,
+    /// Amber cells on black.
+    Amber,
+    /// Blue cells with the Solarized base colors.
+    Solarized,
+}
+
+/// Colors of a [`Palette`] in sRGB.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PaletteColors {
+    /// Color of alive cells, dying cells fade from it to the dead color.
+    pub alive: [u8; 3],
+    /// Color of dead cells.
+    pub dead: [u8; 3],
+    /// Color of the grid lines.
+    pub grid: [u8; 3],
+    /// Color the window is cleared with around the board.
+    pub background: [u8; 3],
+}
+
+impl Palette {
+    /// All palettes in order they should be displayed.
+    pub const ALL: [Self; 4] = [
+        Self::Classic,
+        Self::GreenPhosphor,
+        Self::Amber,
+        Self::Solarized,
+    ];
+
+    /// Returns human readable name of the palette.
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Classic => "Classic",
+            Self::GreenPhosphor => "Green phosphor",
+            Self::Amber => "Amber",
+            Self::Solarized => "Solarized",
+        }
+    }
+
+    /// Returns colors of the palette.
+    #[must_use]
+    pub fn colors(self) -> PaletteColors {
+        let [alive, dead, grid, background] = match self {
+            Self::Classic => [
+                [0, 0, 0],
+                [255, 255, 255],
+                [243, 243, 243],
+                DEFAULT_BACKGROUND,
+            ],
+            Self::GreenPhosphor => [[51, 255, 102], [6, 18, 9], [20, 58, 28], [0, 0, 0]],
+            Self::Amber => [[255, 176, 0], [20, 12, 0], [66, 42, 0], [0, 0, 0]],
+            Self::Solarized => [
+                [38, 139, 210],
+                [253, 246, 227],
+                [238, 232, 213],
+                [0, 43, 54],
+            ],
+        };
+        PaletteColors {
+            alive,
+            dead,
+            grid,
+            background,
+        }
+    }
+}
+
+impl PaletteColors {
+    /// Returns colors with alive and dead cells swapped,
+    /// the grid and the background are replaced with their negatives.
+    #[must_use]
+    pub fn inverted(self) -> Self {
+        let negative = |color: [u8; 3]| color.map(|channel| u8::MAX - channel);
+        Self {
+            alive: self.dead,
+            dead: self.alive,
+            grid: negative(self.grid),
+            background: negative(self.background),
+        }
+    }
+}
 
 /// This struct represents controls menu.
 pub struct Controller {
     gui: Gui,
     grid: bool,
+    grid_interval: u32,
+    ruler: bool,
+    neighbour_colors: bool,
     speed: u32,
+    max_speed: bool,
+    fixed_step: bool,
+    limit_fps: bool,
+    max_fps: u32,
+    jump_target: u64,
+    #[cfg(feature = "online")]
+    fetch_enabled: bool,
+    #[cfg(feature = "online")]
+    fetch_name: String,
+    #[cfg(feature = "online")]
+    fetch_status: Option<String>,
+    jump_progress: Option<(u64, f32)>,
     pause: bool,
-    max_speed: u32,
+    vsync: bool,
+    /// Physical pixels per point of the controls, `None` when the window scale factor is used.
+    ui_scale: Option<f32>,
+    /// Scale selected by the slider while it is dragged, it is applied once released.
+    dragged_ui_scale: Option<f32>,
+    /// Scale factor of the window reported by the platform.
+    native_ui_scale: f32,
+    ui_visible: bool,
+    help_visible: bool,
+    modifiers: ModifiersState,
+    wrap_shift: bool,
+    background: [u8; 3],
+    palette: Palette,
+    cell_aspect: CellAspect,
+    native_view: bool,
+    pan_inertia: f32,
+    cell_padding: f32,
+    inverted: bool,
+    state_threshold: u32,
+    cell_shading: CellShading,
+    /// Whether a border is drawn along the edges of the board.
+    border: bool,
+    border_width: u32,
+    border_color: [u8; 3],
+    rule: Rule,
+    comparison: bool,
+    comparison_rule: Rule,
+    neighbourhood: Neighbourhood,
+    topology: Topology,
+    symmetry: Symmetry,
+    /// Whether randomized cells are clustered by noise, see [`Distribution::Noise`].
+    noise: bool,
+    noise_frequency: f32,
+    noise_density: f32,
+    keep_cells: bool,
+    /// Index of the selected pattern in [`BUILTIN_PATTERNS`].
+    pattern: usize,
+    tool: DrawTool,
+    line_anchor: Option<[u32; 2]>,
+    fill_mode: FillMode,
+    fill_density: f32,
+    fill_preview: Option<[[u32; 2]; 2]>,
+    /// Whether the cell painted by the cells and line tools is outlined under the cursor.
+    brush_outline: bool,
+    hovered_cell: Option<[u32; 2]>,
+    /// Paths of the two saved states typed into the compare controls.
+    compare_paths: [String; 2],
+    /// Number of cells which differ between the compared states.
+    state_difference: Option<usize>,
+    /// Why the states could not be compared.
+    compare_error: Option<String>,
+    recording: bool,
+    stability_action: StabilityAction,
+    stabilized: Option<u32>,
+    live_bounds: Option<LiveBounds>,
+    population: Option<u64>,
+    population_samples: usize,
+    population_status: Option<String>,
+    rotation_rejected: bool,
+    /// Why the board could not be expanded, it is not expanded anymore afterwards.
+    expand_error: Option<String>,
+    cursor_cell: Option<([u32; 2], u32)>,
+    cell_mapping: Option<CellMapping>,
+    step_latency: Option<Duration>,
+    step_duration: Option<Duration>,
+    cell_bits: u32,
     pub fps_counter: VecDeque<Instant>,
+    fps_history: VecDeque<u32>,
+    fps_sampled: Instant,
+    /// Batches of generations submitted within the last second, with their number of steps.
+    step_counter: VecDeque<(Instant, u32)>,
     event_loop: EventLoopProxy<Message>,
 }
 
 impl Controller {
     /// Create [`Controller`] instance.
+    /// Controls kept between runs are restored from `settings`.
     #[inline]
     #[must_use]
-    pub fn new(renderer: &VulkanoWindowRenderer, event_loop: &EventLoop<Message>) -> Self {
+    pub fn new(
+        renderer: &VulkanoWindowRenderer,
+        event_loop: &EventLoop<Message>,
+        config: &Config,
+        settings: &Settings,
+    ) -> Self {
         let gui = Gui::new(
             event_loop,
             renderer.surface(),
@@ -33,29 +342,279 @@ impl Controller {
             renderer.graphics_queue(),
             true,
         );
-        let max_speed = renderer
-            .window()
-            .available_monitors()
-            .next()
-            .and_then(|monitor| monitor.refresh_rate_millihertz())
-            .unwrap_or(60000)
-            / 1000;
+        #[allow(clippy::cast_possible_truncation)]
+        let native_ui_scale = renderer.window().scale_factor() as f32;
+        let ui_scale = settings
+            .ui_scale
+            .map(|scale| scale.clamp(MIN_UI_SCALE, MAX_UI_SCALE));
+        gui.context()
+            .set_pixels_per_point(ui_scale.unwrap_or(native_ui_scale));
 
         Self {
             gui,
-            grid: false,
-            speed: 60,
+            grid: settings.grid,
+            grid_interval: settings.grid_interval.clamp(1, MAX_GRID_INTERVAL),
+            ruler: settings.ruler,
+            neighbour_colors: settings.neighbour_colors,
+            speed: settings.speed.clamp(1, MAX_SPEED),
+            max_speed: settings.max_speed,
+            fixed_step: config.fixed_step().is_some(),
+            limit_fps: settings.limit_fps,
+            max_fps: settings.max_fps.clamp(1, MAX_FPS),
+            jump_target: 1000,
+            #[cfg(feature = "online")]
+            fetch_enabled: config.pattern_url().is_some(),
+            #[cfg(feature = "online")]
+            fetch_name: String::new(),
+            #[cfg(feature = "online")]
+            fetch_status: None,
+            jump_progress: None,
             pause: true,
-            max_speed,
+            vsync: settings.vsync,
+            ui_scale,
+            dragged_ui_scale: None,
+            native_ui_scale,
+            ui_visible: settings.controls_visible,
+            help_visible: false,
+            modifiers: ModifiersState::empty(),
+            wrap_shift: settings.wrap_shift,
+            background: settings.background,
+            palette: settings.palette,
+            cell_aspect: settings.cell_aspect,
+            native_view: settings.native_view,
+            pan_inertia: settings.pan_inertia.clamp(0.0, MAX_PAN_INERTIA),
+            cell_padding: settings.cell_padding.clamp(0.0, MAX_CELL_PADDING),
+            inverted: settings.inverted,
+            state_threshold: settings.state_threshold.max(1),
+            cell_shading: settings.cell_shading,
+            border: settings.border,
+            border_width: settings.border_width.clamp(1, MAX_BORDER_WIDTH),
+            border_color: settings.border_color,
+            rule: config.rule(),
+            comparison: config.compare_rule().is_some(),
+            comparison_rule: config.compare_rule().unwrap_or_else(|| config.rule()),
+            neighbourhood: config.neighbourhood(),
+            topology: config.topology(),
+            symmetry: Symmetry::default(),
+            noise: false,
+            noise_frequency: DEFAULT_NOISE_FREQUENCY,
+            noise_density: 0.5,
+            keep_cells: false,
+            pattern: 0,
+            tool: DrawTool::default(),
+            line_anchor: None,
+            fill_mode: FillMode::default(),
+            fill_density: 0.5,
+            fill_preview: None,
+            brush_outline: settings.brush_outline,
+            hovered_cell: None,
+            compare_paths: Default::default(),
+            state_difference: None,
+            compare_error: None,
+            recording: false,
+            stability_action: settings.stability_action,
+            stabilized: None,
+            live_bounds: None,
+            population: None,
+            population_samples: 0,
+            population_status: None,
+            rotation_rejected: false,
+            expand_error: None,
+            cursor_cell: None,
+            cell_mapping: None,
+            step_latency: None,
+            step_duration: None,
+            cell_bits: config.cell_bits(),
             fps_counter: VecDeque::new(),
+            fps_history: VecDeque::with_capacity(FPS_HISTORY_LENGTH),
+            fps_sampled: Instant::now(),
+            step_counter: VecDeque::new(),
             event_loop: event_loop.create_proxy(),
         }
     }
 
     /// Update equivalent of [`Gui`] update method.
-    #[inline]
+    ///
+    /// Handles keys toggling visibility of the controls and the shortcuts help.
+    /// While nothing is visible events are never reported as consumed.
     pub fn update(&mut self, event: &WindowEvent) -> bool {
-        self.gui.update(event)
+        if let WindowEvent::ModifiersChanged(modifiers) = event {
+            self.modifiers = *modifiers;
+        }
+        if let WindowEvent::ScaleFactorChanged { scale_factor, .. } = event {
+            #[allow(clippy::cast_possible_truncation)]
+            let scale_factor = *scale_factor as f32;
+            self.native_ui_scale = scale_factor;
+        }
+        if !self.gui.context().wants_keyboard_input() {
+            match event {
+                WindowEvent::KeyboardInput {
+                    input:
+                        KeyboardInput {
+                            state: ElementState::Pressed,
+                            virtual_keycode:
+                                Some(
+                                    key @ (VirtualKeyCode::Left
+                                    | VirtualKeyCode::Right
+                                    | VirtualKeyCode::Up
+                                    | VirtualKeyCode::Down),
+                                ),
+                            ..
+                        },
+                    ..
+                } if self.modifiers.ctrl() => {
+                    let (dx, dy) = match key {
+                        VirtualKeyCode::Left => (-1, 0),
+                        VirtualKeyCode::Right => (1, 0),
+                        VirtualKeyCode::Up => (0, -1),
+                        _ => (0, 1),
+                    };
+                    self.event_loop
+                        .send_event(Message::Shift(dx, dy, self.wrap_shift))
+                        .expect("Cannot send event");
+                    return true;
+                }
+                WindowEvent::KeyboardInput {
+                    input:
+                        KeyboardInput {
+                            state: ElementState::Pressed,
+                            virtual_keycode: Some(VirtualKeyCode::Tab | VirtualKeyCode::H),
+                            ..
+                        },
+                    ..
+                } => {
+                    self.ui_visible = !self.ui_visible;
+                    return true;
+                }
+                WindowEvent::KeyboardInput {
+                    input:
+                        KeyboardInput {
+                            state: ElementState::Pressed,
+                            virtual_keycode: Some(VirtualKeyCode::Left),
+                            ..
+                        },
+                    ..
+                } => {
+                    self.event_loop
+                        .send_event(Message::StepBack)
+                        .expect("Cannot send event");
+                    return true;
+                }
+                WindowEvent::KeyboardInput {
+                    input:
+                        KeyboardInput {
+                            state: ElementState::Pressed,
+                            virtual_keycode: Some(VirtualKeyCode::P),
+                            ..
+                        },
+                    ..
+                } => {
+                    if let Some((position, _)) = self.cursor_cell {
+                        self.event_loop
+                            .send_event(Message::Stamp(self.pattern, Some(position)))
+                            .expect("Cannot send event");
+                    }
+                    return true;
+                }
+                WindowEvent::KeyboardInput {
+                    input:
+                        KeyboardInput {
+                            state: ElementState::Pressed,
+                            virtual_keycode: Some(VirtualKeyCode::I),
+                            ..
+                        },
+                    ..
+                } => {
+                    self.inverted = !self.inverted;
+                    self.event_loop
+                        .send_event(Message::SetInverted(self.inverted))
+                        .expect("Cannot send event");
+                    return true;
+                }
+                WindowEvent::KeyboardInput {
+                    input:
+                        KeyboardInput {
+                            state: ElementState::Pressed,
+                            virtual_keycode: Some(VirtualKeyCode::R),
+                            ..
+                        },
+                    ..
+                } => {
+                    self.event_loop
+                        .send_event(Message::Randomize(
+                            self.symmetry,
+                            self.distribution(),
+                            self.keep_cells,
+                        ))
+                        .expect("Cannot send event");
+                    return true;
+                }
+                WindowEvent::KeyboardInput {
+                    input:
+                        KeyboardInput {
+                            state: ElementState::Pressed,
+                            virtual_keycode: Some(VirtualKeyCode::C),
+                            ..
+                        },
+                    ..
+                } => {
+                    self.event_loop
+                        .send_event(Message::Clear)
+                        .expect("Cannot send event");
+                    return true;
+                }
+                WindowEvent::KeyboardInput {
+                    input:
+                        KeyboardInput {
+                            state: ElementState::Pressed,
+                            virtual_keycode: Some(VirtualKeyCode::N),
+                            ..
+                        },
+                    ..
+                } => {
+                    self.event_loop
+                        .send_event(Message::Randomize(
+                            self.symmetry,
+                            self.distribution(),
+                            false,
+                        ))
+                        .expect("Cannot send event");
+                    return true;
+                }
+                WindowEvent::KeyboardInput {
+                    input:
+                        KeyboardInput {
+                            state: ElementState::Pressed,
+                            virtual_keycode: Some(VirtualKeyCode::L),
+                            ..
+                        },
+                    ..
+                } => {
+                    self.event_loop
+                        .send_event(Message::CenterOnLiveCells)
+                        .expect("Cannot send event");
+                    return true;
+                }
+                WindowEvent::KeyboardInput {
+                    input:
+                        KeyboardInput {
+                            state: ElementState::Pressed,
+                            virtual_keycode: Some(VirtualKeyCode::Escape),
+                            ..
+                        },
+                    ..
+                } if self.line_anchor.is_some() => {
+                    self.line_anchor = None;
+                    return true;
+                }
+                WindowEvent::ReceivedCharacter('?') => {
+                    self.help_visible = !self.help_visible;
+                    return true;
+                }
+                _ => (),
+            }
+        }
+        self.gui.update(event) && (self.ui_visible || self.help_visible)
     }
 
     /// Draw gui on screen
@@ -64,22 +623,408 @@ impl Controller {
         future: Box<dyn GpuFuture>,
         image: Arc<dyn ImageViewAbstract>,
     ) -> Box<dyn GpuFuture> {
+        self.sample_fps();
+        let generations_per_second = self.generations_per_second();
+
+        // The integration resets the scale when the window scale factor changes.
+        let ui_scale = self.ui_scale.unwrap_or(self.native_ui_scale);
+        let ctx = self.gui.context();
+        if (ctx.pixels_per_point() - ui_scale).abs() > f32::EPSILON {
+            ctx.set_pixels_per_point(ui_scale);
+        }
+
         self.gui.immediate_ui(|ui| {
             let ctx = ui.context();
 
+            egui::containers::Window::new("Shortcuts")
+                .open(&mut self.help_visible)
+                .show(&ctx, |ui| {
+                    egui::Grid::new("shortcuts").striped(true).show(ui, |ui| {
+                        for (input, action) in SHORTCUTS {
+                            ui.label(input);
+                            ui.label(action);
+                            ui.end_row();
+                        }
+                    });
+                });
+
+            if let (Some(mapping), Some(corners)) = (self.cell_mapping, self.fill_preview) {
+                draw_fill_preview(&ctx, &mapping, corners);
+            }
+            let outlined = self.hovered_cell.filter(|_| {
+                self.brush_outline && matches!(self.tool, DrawTool::Cell | DrawTool::Line)
+            });
+            if let (Some(mapping), Some(cell)) = (self.cell_mapping, outlined) {
+                draw_brush_outline(&ctx, &mapping, cell);
+            }
+
+            if !self.ui_visible {
+                return;
+            }
+
+            if let Some(mapping) = self.cell_mapping.filter(|_| self.ruler) {
+                draw_ruler(&ctx, &mapping);
+            }
+
             egui::containers::Window::new("Controls").show(&ctx, |ui| {
                 ui.label(format!("Frames per second: {}", self.fps_counter.len()));
-                ui.add(
-                    egui::Slider::new(&mut self.speed, 1..=self.max_speed).text("Simulation speed"),
+                if let Some(latency) = self.step_latency {
+                    ui.label(format!(
+                        "Step latency: {:.2} ms",
+                        latency.as_secs_f64() * 1000.0
+                    ));
+                }
+                if let Some(duration) = self.step_duration {
+                    ui.label(format!("Step GPU time: {} µs", duration.as_micros()));
+                }
+                ui.label(cursor_text(self.cursor_cell));
+                if let Some(population) = self.population {
+                    ui.label(format!("Population: {population}"));
+                }
+                ui.horizontal(|ui| {
+                    ui.label(format!("Samples: {}", self.population_samples));
+                    if ui
+                        .add_enabled(self.population_samples > 0, egui::Button::new("Export CSV"))
+                        .clicked()
+                    {
+                        self.event_loop
+                            .send_event(Message::ExportPopulation)
+                            .expect("Cannot send event");
+                    }
+                });
+                if let Some(status) = &self.population_status {
+                    ui.label(status);
+                }
+                ui.horizontal(|ui| {
+                    ui.label(bounds_text(self.live_bounds));
+                    if ui
+                        .add_enabled(self.live_bounds.is_some(), egui::Button::new("Frame"))
+                        .clicked()
+                    {
+                        self.event_loop
+                            .send_event(Message::FrameLiveCells)
+                            .expect("Cannot send event");
+                    }
+                    if ui
+                        .add_enabled(self.live_bounds.is_some(), egui::Button::new("Center"))
+                        .clicked()
+                    {
+                        self.event_loop
+                            .send_event(Message::CenterOnLiveCells)
+                            .expect("Cannot send event");
+                    }
+                });
+                sparkline(ui, &self.fps_history);
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut self.limit_fps, "Limit frames per second to");
+                    ui.add_enabled(
+                        self.limit_fps,
+                        egui::DragValue::new(&mut self.max_fps).clamp_range(1..=MAX_FPS),
+                    );
+                });
+                ui.horizontal(|ui| {
+                    ui.add_enabled(
+                        !self.max_speed && !self.fixed_step,
+                        egui::Slider::new(&mut self.speed, 1..=MAX_SPEED)
+                            .logarithmic(true)
+                            .text("Simulation speed"),
+                    );
+                    ui.checkbox(&mut self.max_speed, "Max speed");
+                    ui.label(format!("({generations_per_second} gen/s)"));
+                });
+                ui.horizontal(|ui| {
+                    ui.add(egui::DragValue::new(&mut self.jump_target).prefix("Generation "));
+                    if ui
+                        .add_enabled(self.jump_progress.is_none(), egui::Button::new("Go"))
+                        .clicked()
+                    {
+                        self.event_loop
+                            .send_event(Message::JumpTo(Some(self.jump_target)))
+                            .expect("Cannot send event");
+                    }
+                });
+                if let Some((target, progress)) = self.jump_progress {
+                    ui.horizontal(|ui| {
+                        ui.add(
+                            egui::ProgressBar::new(progress)
+                                .desired_width(JUMP_PROGRESS_WIDTH)
+                                .text(format!("Jumping to generation {target}")),
+                        );
+                        if ui.button("Cancel").clicked() {
+                            self.event_loop
+                                .send_event(Message::JumpTo(None))
+                                .expect("Cannot send event");
+                        }
+                    });
+                }
+                let label = if self.comparison { "Left rule" } else { "Rule" };
+                if rule_combo(ui, label, &mut self.rule, self.cell_bits) {
+                    self.event_loop
+                        .send_event(Message::SetRule(self.rule))
+                        .expect("Cannot send event");
+                }
+                let mut comparison_changed = ui
+                    .checkbox(&mut self.comparison, "Compare with another rule")
+                    .changed();
+                if self.comparison {
+                    comparison_changed |=
+                        rule_combo(ui, "Right rule", &mut self.comparison_rule, self.cell_bits);
+                }
+                if comparison_changed {
+                    self.event_loop
+                        .send_event(Message::SetComparison(
+                            self.comparison.then_some(self.comparison_rule),
+                        ))
+                        .expect("Cannot send event");
+                }
+                let neighbourhood = self.neighbourhood;
+                egui::ComboBox::from_label("Neighbourhood")
+                    .selected_text(neighbourhood.name())
+                    .show_ui(ui, |ui| {
+                        for value in Neighbourhood::ALL {
+                            ui.selectable_value(&mut self.neighbourhood, value, value.name());
+                        }
+                    });
+                if self.neighbourhood != neighbourhood {
+                    self.event_loop
+                        .send_event(Message::SetNeighbourhood(self.neighbourhood))
+                        .expect("Cannot send event");
+                }
+                if !self.rule.fits(self.neighbourhood)
+                    || (self.comparison && !self.comparison_rule.fits(self.neighbourhood))
+                {
+                    ui.colored_label(
+                        egui::Color32::YELLOW,
+                        "Rule uses more neighbours than the neighbourhood has",
+                    );
+                }
+                let topology = self.topology;
+                egui::ComboBox::from_label("Topology")
+                    .selected_text(topology.name())
+                    .show_ui(ui, |ui| {
+                        for value in Topology::ALL {
+                            ui.selectable_value(&mut self.topology, value, value.name());
+                        }
+                    });
+                if self.topology != topology {
+                    self.event_loop
+                        .send_event(Message::SetTopology(self.topology))
+                        .expect("Cannot send event");
+                }
+                egui::ComboBox::from_label("Symmetry")
+                    .selected_text(self.symmetry.name())
+                    .show_ui(ui, |ui| {
+                        for symmetry in Symmetry::ALL {
+                            ui.selectable_value(&mut self.symmetry, symmetry, symmetry.name());
+                        }
+                    });
+                ui.checkbox(&mut self.noise, "Cluster random cells with noise");
+                ui.add_enabled(
+                    self.noise,
+                    egui::Slider::new(
+                        &mut self.noise_frequency,
+                        MIN_NOISE_FREQUENCY..=MAX_NOISE_FREQUENCY,
+                    )
+                    .logarithmic(true)
+                    .text("Noise frequency"),
                 );
-                ui.checkbox(&mut self.grid, "Show grid");
+                ui.add_enabled(
+                    self.noise,
+                    egui::Slider::new(&mut self.noise_density, 0.0..=1.0).text("Noise density"),
+                );
+                ui.checkbox(&mut self.keep_cells, "Randomize keeps existing cells");
+                egui::ComboBox::from_label("When stable")
+                    .selected_text(self.stability_action.name())
+                    .show_ui(ui, |ui| {
+                        for action in StabilityAction::ALL {
+                            ui.selectable_value(&mut self.stability_action, action, action.name());
+                        }
+                    });
+                if let Some(period) = self.stabilized {
+                    ui.label(stabilized_text(period));
+                }
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut self.grid, "Show grid, major lines every");
+                    ui.add_enabled(
+                        self.grid,
+                        egui::DragValue::new(&mut self.grid_interval)
+                            .clamp_range(1..=MAX_GRID_INTERVAL)
+                            .suffix(" cells"),
+                    );
+                });
+                ui.checkbox(&mut self.ruler, "Show coordinates");
+                if ui
+                    .checkbox(&mut self.neighbour_colors, "Color cells by neighbours")
+                    .changed()
+                {
+                    self.event_loop
+                        .send_event(Message::SetNeighbourColors(self.neighbour_colors))
+                        .expect("Cannot send event");
+                }
+                if ui.checkbox(&mut self.vsync, "VSync").changed() {
+                    self.event_loop
+                        .send_event(Message::SetVsync(self.vsync))
+                        .expect("Cannot send event");
+                }
+                ui.horizontal(|ui| {
+                    let mut scale = self
+                        .dragged_ui_scale
+                        .or(self.ui_scale)
+                        .unwrap_or(self.native_ui_scale);
+                    let slider = ui.add(
+                        egui::Slider::new(&mut scale, MIN_UI_SCALE..=MAX_UI_SCALE).text("UI scale"),
+                    );
+                    // The layout changes with the scale, it is applied once the slider is released.
+                    if slider.dragged() {
+                        self.dragged_ui_scale = Some(scale);
+                    } else if slider.changed() || self.dragged_ui_scale.is_some() {
+                        self.ui_scale = Some(scale);
+                        self.dragged_ui_scale = None;
+                    }
+                    if ui
+                        .add_enabled(self.ui_scale.is_some(), egui::Button::new("Reset"))
+                        .clicked()
+                    {
+                        self.ui_scale = None;
+                    }
+                });
+                let palette = self.palette;
+                egui::ComboBox::from_label("Palette")
+                    .selected_text(self.palette.name())
+                    .show_ui(ui, |ui| {
+                        for palette in Palette::ALL {
+                            ui.selectable_value(&mut self.palette, palette, palette.name());
+                        }
+                    });
+                if self.palette != palette {
+                    self.background = self.palette.colors().background;
+                    self.event_loop
+                        .send_event(Message::SetPalette(self.palette))
+                        .expect("Cannot send event");
+                }
+                if ui.checkbox(&mut self.inverted, "Invert colors").changed() {
+                    self.event_loop
+                        .send_event(Message::SetInverted(self.inverted))
+                        .expect("Cannot send event");
+                }
+                ui.horizontal_top(|ui| {
+                    if ui.color_edit_button_srgb(&mut self.background).changed() {
+                        self.event_loop
+                            .send_event(Message::SetBackground(self.background))
+                            .expect("Cannot send event");
+                    }
+                    ui.label("Background");
+                });
+                ui.horizontal_top(|ui| {
+                    let mut changed = ui.checkbox(&mut self.border, "Border").changed();
+                    changed |= ui.color_edit_button_srgb(&mut self.border_color).changed();
+                    changed |= ui
+                        .add_enabled(
+                            self.border,
+                            egui::Slider::new(&mut self.border_width, 1..=MAX_BORDER_WIDTH)
+                                .suffix(" px"),
+                        )
+                        .changed();
+                    if changed {
+                        let width = if self.border { self.border_width } else { 0 };
+                        self.event_loop
+                            .send_event(Message::SetBorder(width, self.border_color))
+                            .expect("Cannot send event");
+                    }
+                });
+                let cell_aspect = self.cell_aspect;
+                egui::ComboBox::from_label("Cell shape")
+                    .selected_text(self.cell_aspect.name())
+                    .show_ui(ui, |ui| {
+                        for aspect in CellAspect::ALL {
+                            ui.selectable_value(&mut self.cell_aspect, aspect, aspect.name());
+                        }
+                    });
+                if self.cell_aspect != cell_aspect {
+                    self.event_loop
+                        .send_event(Message::SetCellAspect(self.cell_aspect))
+                        .expect("Cannot send event");
+                }
+                if ui
+                    .checkbox(&mut self.native_view, "One cell per pixel")
+                    .changed()
+                {
+                    self.event_loop
+                        .send_event(Message::SetNativeView(self.native_view))
+                        .expect("Cannot send event");
+                }
+                if ui
+                    .add(
+                        egui::Slider::new(&mut self.pan_inertia, 0.0..=MAX_PAN_INERTIA)
+                            .suffix(" s")
+                            .text("Pan inertia"),
+                    )
+                    .changed()
+                {
+                    self.event_loop
+                        .send_event(Message::SetPanInertia(self.pan_inertia))
+                        .expect("Cannot send event");
+                }
+                if ui
+                    .add(
+                        egui::Slider::new(&mut self.cell_padding, 0.0..=MAX_CELL_PADDING)
+                            .text("Cell padding"),
+                    )
+                    .changed()
+                {
+                    self.event_loop
+                        .send_event(Message::SetCellPadding(self.cell_padding))
+                        .expect("Cannot send event");
+                }
+                if self.rule.states() > 2 {
+                    // The threshold is kept when the rule changes, only its display is clamped.
+                    let mut threshold = self.state_threshold.min(self.rule.states() - 1);
+                    if ui
+                        .add(
+                            egui::Slider::new(&mut threshold, 1..=self.rule.states() - 1)
+                                .text("Highest state shown"),
+                        )
+                        .changed()
+                    {
+                        self.state_threshold = threshold;
+                        self.event_loop
+                            .send_event(Message::SetStateThreshold(threshold))
+                            .expect("Cannot send event");
+                    }
+                    let cell_shading = self.cell_shading;
+                    egui::ComboBox::from_label("Dying cells shading")
+                        .selected_text(self.cell_shading.name())
+                        .show_ui(ui, |ui| {
+                            for shading in CellShading::ALL {
+                                ui.selectable_value(
+                                    &mut self.cell_shading,
+                                    shading,
+                                    shading.name(),
+                                );
+                            }
+                        });
+                    if self.cell_shading != cell_shading {
+                        self.event_loop
+                            .send_event(Message::SetCellShading(self.cell_shading))
+                            .expect("Cannot send event");
+                    }
+                }
                 ui.horizontal_top(|ui| {
                     if ui.button(play_text(self.pause)).clicked() {
                         self.pause = !self.pause;
                     }
+                    if ui.button("Back").clicked() {
+                        self.event_loop
+                            .send_event(Message::StepBack)
+                            .expect("Cannot send event");
+                    }
                     if ui.button("Randomize").clicked() {
                         self.event_loop
-                            .send_event(Message::Randomize)
+                            .send_event(Message::Randomize(
+                                self.symmetry,
+                                self.distribution(),
+                                self.keep_cells,
+                            ))
                             .expect("Cannot send event");
                     }
                     if ui.button("Clear").clicked() {
@@ -87,26 +1032,608 @@ impl Controller {
                             .send_event(Message::Clear)
                             .expect("Cannot send event");
                     }
+                    // The randomizer overwrites every word when cells are not kept,
+                    // so the board is cleared and randomized by a single dispatch.
+                    if ui.button("Reseed").clicked() {
+                        self.event_loop
+                            .send_event(Message::Randomize(
+                                self.symmetry,
+                                self.distribution(),
+                                false,
+                            ))
+                            .expect("Cannot send event");
+                    }
+                });
+                ui.horizontal_top(|ui| {
+                    if ui.button("Flip H").clicked() {
+                        self.event_loop
+                            .send_event(Message::Mirror(Mirror::Horizontal))
+                            .expect("Cannot send event");
+                    }
+                    if ui.button("Flip V").clicked() {
+                        self.event_loop
+                            .send_event(Message::Mirror(Mirror::Vertical))
+                            .expect("Cannot send event");
+                    }
+                    if ui.button("Rotate CW").clicked() {
+                        self.event_loop
+                            .send_event(Message::Rotate(Rotation::Clockwise))
+                            .expect("Cannot send event");
+                    }
+                    if ui.button("Rotate CCW").clicked() {
+                        self.event_loop
+                            .send_event(Message::Rotate(Rotation::CounterClockwise))
+                            .expect("Cannot send event");
+                    }
+                });
+                ui.checkbox(&mut self.wrap_shift, "Wrap cells shifted over the edge");
+                ui.horizontal_top(|ui| {
+                    egui::ComboBox::from_label("Pattern")
+                        .selected_text(BUILTIN_PATTERNS[self.pattern].0)
+                        .show_ui(ui, |ui| {
+                            for (index, (name, _)) in BUILTIN_PATTERNS.iter().enumerate() {
+                                ui.selectable_value(&mut self.pattern, index, *name);
+                            }
+                        });
+                    if ui.button("Place").clicked() {
+                        self.event_loop
+                            .send_event(Message::Stamp(self.pattern, None))
+                            .expect("Cannot send event");
+                    }
+                });
+                #[cfg(feature = "online")]
+                if self.fetch_enabled {
+                    ui.horizontal_top(|ui| {
+                        ui.add(
+                            egui::TextEdit::singleline(&mut self.fetch_name)
+                                .hint_text("LifeWiki pattern name"),
+                        );
+                        if ui.button("Fetch").clicked() {
+                            self.event_loop
+                                .send_event(Message::FetchPattern(self.fetch_name.clone()))
+                                .expect("Cannot send event");
+                        }
+                    });
+                    if let Some(status) = &self.fetch_status {
+                        ui.label(status);
+                    }
+                }
+                ui.horizontal_top(|ui| {
+                    egui::ComboBox::from_label("Tool")
+                        .selected_text(self.tool.name())
+                        .show_ui(ui, |ui| {
+                            for tool in DrawTool::ALL {
+                                ui.selectable_value(&mut self.tool, tool, tool.name());
+                            }
+                        });
+                    if self.tool != DrawTool::Line {
+                        self.line_anchor = None;
+                    }
+                    match (self.tool, self.line_anchor) {
+                        (DrawTool::Cell, _) => {
+                            ui.checkbox(&mut self.brush_outline, "Outline");
+                        }
+                        (DrawTool::Rectangle, _) => {
+                            egui::ComboBox::from_label("Fill")
+                                .selected_text(self.fill_mode.name())
+                                .show_ui(ui, |ui| {
+                                    for mode in FillMode::ALL {
+                                        ui.selectable_value(&mut self.fill_mode, mode, mode.name());
+                                    }
+                                });
+                            if self.fill_mode == FillMode::Random {
+                                ui.add(
+                                    egui::Slider::new(&mut self.fill_density, 0.0..=1.0)
+                                        .text("Density"),
+                                );
+                            }
+                        }
+                        (DrawTool::Line, None) => {
+                            ui.label("Click the start of the line");
+                        }
+                        (DrawTool::Line, Some([x, y])) => {
+                            ui.label(format!("Line from {x}, {y}, Escape to cancel"));
+                        }
+                    }
+                });
+                if self.rotation_rejected {
+                    ui.colored_label(egui::Color32::YELLOW, "Only square boards can be rotated");
+                }
+                if let Some(error) = &self.expand_error {
+                    ui.colored_label(egui::Color32::YELLOW, error);
+                }
+                if ui.button(record_text(self.recording)).clicked() {
+                    self.event_loop
+                        .send_event(Message::ToggleRecording)
+                        .expect("Cannot send event");
+                }
+                ui.collapsing("Compare states", |ui| {
+                    for (path, label) in self.compare_paths.iter_mut().zip(["First", "Second"]) {
+                        ui.horizontal_top(|ui| {
+                            ui.text_edit_singleline(path);
+                            ui.label(label);
+                        });
+                    }
+                    ui.horizontal_top(|ui| {
+                        if ui.button("Compare").clicked() {
+                            let paths = self.compare_paths.clone().map(PathBuf::from);
+                            self.event_loop
+                                .send_event(Message::CompareStates(Some(paths)))
+                                .expect("Cannot send event");
+                        }
+                        if ui
+                            .add_enabled(self.state_difference.is_some(), egui::Button::new("Stop"))
+                            .clicked()
+                        {
+                            self.event_loop
+                                .send_event(Message::CompareStates(None))
+                                .expect("Cannot send event");
+                        }
+                    });
+                    if let Some(cells) = self.state_difference {
+                        ui.label(format!(
+                            "{cells} cells differ, highlighted on the first state"
+                        ));
+                    }
+                    if let Some(error) = &self.compare_error {
+                        ui.colored_label(egui::Color32::YELLOW, error);
+                    }
                 });
             });
         });
         self.gui.draw_on_image(future, image)
     }
 
-    /// Returns the speed of the simulation.
-    pub fn speed(&self) -> u128 {
-        self.speed.into()
+    /// Records current frames per second in the history once every second.
+    fn sample_fps(&mut self) {
+        let now = Instant::now();
+        if now - self.fps_sampled < Duration::from_secs(1) {
+            return;
+        }
+        self.fps_sampled = now;
+
+        if self.fps_history.len() == FPS_HISTORY_LENGTH {
+            self.fps_history.pop_front();
+        }
+        #[allow(clippy::cast_possible_truncation)]
+        self.fps_history.push_back(self.fps_counter.len() as u32);
+    }
+
+    /// Records a batch of `steps` simulation steps submitted at given time.
+    pub fn record_steps(&mut self, now: Instant, steps: u32) {
+        if steps > 0 {
+            self.step_counter.push_back((now, steps));
+        }
+    }
+
+    /// Returns number of simulation steps completed in the last second.
+    fn generations_per_second(&mut self) -> u64 {
+        let now = Instant::now();
+        while let Some((x, _)) = self.step_counter.front() {
+            if now - *x < Duration::from_secs(1) {
+                break;
+            }
+            self.step_counter.pop_front();
+        }
+        self.step_counter
+            .iter()
+            .map(|&(_, steps)| u64::from(steps))
+            .sum()
+    }
+
+    /// Returns the speed of the simulation in generations per second.
+    pub fn speed(&self) -> u32 {
+        self.speed
+    }
+
+    /// Returns highest number of frames that should be drawn per second,
+    /// `None` when frames are not limited.
+    pub fn max_fps(&self) -> Option<u32> {
+        self.limit_fps.then_some(self.max_fps)
+    }
+
+    /// Returns whether generations should be computed as fast as the GPU allows,
+    /// ignoring [`Controller::speed`].
+    pub fn max_speed(&self) -> bool {
+        self.max_speed
+    }
+
+    /// Returns interval of major grid lines in cells, `None` when the grid is hidden.
+    pub fn grid(&self) -> Option<u32> {
+        self.grid.then_some(self.grid_interval)
     }
 
-    /// Returns whether the grid should be drawn.
-    pub fn grid(&self) -> bool {
-        self.grid
+    /// Stores controls kept between runs in `settings`.
+    pub fn store_settings(&self, settings: &mut Settings) {
+        settings.speed = self.speed;
+        settings.max_speed = self.max_speed;
+        settings.limit_fps = self.limit_fps;
+        settings.max_fps = self.max_fps;
+        settings.grid = self.grid;
+        settings.grid_interval = self.grid_interval;
+        settings.ruler = self.ruler;
+        settings.neighbour_colors = self.neighbour_colors;
+        settings.vsync = self.vsync;
+        settings.ui_scale = self.ui_scale;
+        settings.controls_visible = self.ui_visible;
+        settings.wrap_shift = self.wrap_shift;
+        settings.background = self.background;
+        settings.palette = self.palette;
+        settings.cell_aspect = self.cell_aspect;
+        settings.brush_outline = self.brush_outline;
+        settings.native_view = self.native_view;
+        settings.pan_inertia = self.pan_inertia;
+        settings.cell_padding = self.cell_padding;
+        settings.inverted = self.inverted;
+        settings.state_threshold = self.state_threshold;
+        settings.cell_shading = self.cell_shading;
+        settings.border = self.border;
+        settings.border_width = self.border_width;
+        settings.border_color = self.border_color;
+        settings.stability_action = self.stability_action;
     }
 
     /// Returns whether the simulation is paused.
     pub fn pause(&self) -> bool {
         self.pause
     }
+
+    /// Sets whether the simulation is being recorded.
+    pub fn set_recording(&mut self, recording: bool) {
+        self.recording = recording;
+    }
+
+    /// Returns what happens when the board stops changing.
+    pub fn stability_action(&self) -> StabilityAction {
+        self.stability_action
+    }
+
+    /// Reports that the board repeats with given period.
+    /// Depending on the [`StabilityAction`] a label is shown and the simulation paused.
+    pub fn stabilized(&mut self, period: u32) {
+        match self.stability_action {
+            StabilityAction::Ignore => (),
+            StabilityAction::Notify => self.stabilized = Some(period),
+            StabilityAction::Pause => {
+                self.stabilized = Some(period);
+                self.pause = true;
+            }
+        }
+    }
+
+    /// Hides the label shown after the board stabilized.
+    pub fn clear_stabilized(&mut self) {
+        self.stabilized = None;
+    }
+
+    /// Returns the selected [`DrawTool`].
+    pub fn tool(&self) -> DrawTool {
+        self.tool
+    }
+
+    /// Returns the selected [`Distribution`] of randomized cells.
+    pub fn distribution(&self) -> Distribution {
+        if self.noise {
+            Distribution::Noise {
+                frequency: self.noise_frequency,
+                density: self.noise_density,
+            }
+        } else {
+            Distribution::Uniform
+        }
+    }
+
+    /// Returns what cells filled with the rectangle tool become
+    /// and the density of [`FillMode::Random`].
+    pub fn fill(&self) -> (FillMode, f32) {
+        (self.fill_mode, self.fill_density)
+    }
+
+    /// Sets number of cells which differ between the compared states,
+    /// `None` when no states are compared.
+    pub fn set_state_difference(&mut self, cells: Option<usize>) {
+        self.state_difference = cells;
+    }
+
+    /// Sets the message describing why the states could not be compared,
+    /// `None` when the last comparison succeeded.
+    pub fn set_compare_error(&mut self, error: Option<String>) {
+        self.compare_error = error;
+    }
+
+    /// Pauses or resumes the simulation.
+    pub fn set_pause(&mut self, pause: bool) {
+        self.pause = pause;
+    }
+
+    /// Sets corners of the rectangle being dragged out, `None` when there is none.
+    pub fn set_fill_preview(&mut self, corners: Option<[[u32; 2]; 2]>) {
+        self.fill_preview = corners;
+    }
+
+    /// Sets the cell under the cursor the brush outline is drawn around,
+    /// `None` when the cursor is outside of the board.
+    pub fn set_hovered_cell(&mut self, cell: Option<[u32; 2]>) {
+        self.hovered_cell = cell;
+    }
+
+    /// Handles a click of the line tool at given cell.
+    /// The first click sets the start of the line and returns `None`,
+    /// the next one returns the start, which is forgotten.
+    pub fn line_click(&mut self, position: [u32; 2]) -> Option<[u32; 2]> {
+        let anchor = self.line_anchor.take();
+        if anchor.is_none() {
+            self.line_anchor = Some(position);
+        }
+        anchor
+    }
+
+    /// Sets position and value of the cell under the cursor,
+    /// `None` when the cursor is outside of the board.
+    pub fn set_cursor_cell(&mut self, cell: Option<([u32; 2], u32)>) {
+        self.cursor_cell = cell;
+    }
+
+    /// Sets time a single generation took on the GPU in the last batch.
+    pub fn set_step_latency(&mut self, latency: Duration) {
+        self.step_latency = Some(latency);
+    }
+
+    /// Sets time the dispatch of the last generation took, measured with GPU timestamps.
+    pub fn set_step_duration(&mut self, duration: Option<Duration>) {
+        self.step_duration = duration;
+    }
+
+    /// Sets where cells are drawn in the window, used by the coordinate ruler.
+    pub fn set_cell_mapping(&mut self, mapping: CellMapping) {
+        self.cell_mapping = Some(mapping);
+    }
+
+    /// Sets whether the last rotation was rejected because the board is not square.
+    pub fn set_rotation_rejected(&mut self, rejected: bool) {
+        self.rotation_rejected = rejected;
+    }
+
+    /// Sets the message describing why the board could not be expanded.
+    pub fn set_expand_error(&mut self, error: String) {
+        self.expand_error = Some(error);
+    }
+
+    /// Sets generation a jump goes to and the fraction of it already computed,
+    /// `None` when no jump is running.
+    pub fn set_jump_progress(&mut self, progress: Option<(u64, f32)>) {
+        self.jump_progress = progress;
+    }
+
+    /// Sets the message describing the last fetched pattern.
+    #[cfg(feature = "online")]
+    pub fn set_fetch_status(&mut self, status: String) {
+        self.fetch_status = Some(status);
+    }
+
+    /// Sets the bounding box of live cells, `None` when the board is empty.
+    pub fn set_live_bounds(&mut self, bounds: Option<LiveBounds>) {
+        self.live_bounds = bounds;
+    }
+
+    /// Returns number of alive cells of the last board read back.
+    #[inline]
+    #[must_use]
+    pub fn population(&self) -> Option<u64> {
+        self.population
+    }
+
+    /// Sets number of alive cells, it may lag a few frames behind the drawn board.
+    pub fn set_population(&mut self, population: u64) {
+        self.population = Some(population);
+    }
+
+    /// Sets number of population samples that can be exported.
+    pub fn set_population_samples(&mut self, samples: usize) {
+        self.population_samples = samples;
+    }
+
+    /// Sets the message describing the last population export.
+    pub fn set_population_status(&mut self, status: String) {
+        self.population_status = Some(status);
+    }
+}
+
+/// Shows a combo box selecting one of the [`PRESETS`] that fit into `cell_bits`.
+/// Returns whether the rule was changed.
+fn rule_combo(ui: &mut egui::Ui, label: &str, rule: &mut Rule, cell_bits: u32) -> bool {
+    let previous = *rule;
+    egui::ComboBox::from_label(label)
+        .selected_text(
+            previous
+                .preset_name()
+                .map_or_else(|| previous.to_string(), str::to_owned),
+        )
+        .show_ui(ui, |ui| {
+            for (name, preset) in &PRESETS {
+                if preset.fits_cell_bits(cell_bits) {
+                    ui.selectable_value(rule, *preset, *name);
+                }
+            }
+        });
+    *rule != previous
+}
+
+/// Outlines the rectangle spanned by given corner cells in every view.
+#[allow(clippy::cast_possible_truncation)]
+fn draw_fill_preview(ctx: &egui::Context, mapping: &CellMapping, corners: [[u32; 2]; 2]) {
+    let points = f64::from(ctx.pixels_per_point());
+    let painter = ctx.layer_painter(egui::LayerId::new(
+        egui::Order::Background,
+        egui::Id::new("fill preview"),
+    ));
+    let [[first_x, first_y], [second_x, second_y]] = corners;
+    let [min_x, min_y] = [first_x.min(second_x), first_y.min(second_y)].map(f64::from);
+    let [max_x, max_y] =
+        [first_x.max(second_x), first_y.max(second_y)].map(|corner| f64::from(corner) + 1.0);
+
+    let [origin_x, origin_y] = mapping.origin.map(|position| position / points);
+    let [width, height] = mapping.cell_size.map(|size| size / points);
+    let view_width = mapping.view_width / points;
+    for view in 0..mapping.views {
+        let origin_x = origin_x + view_width * f64::from(view);
+        let rect = egui::Rect::from_min_max(
+            egui::pos2(
+                (origin_x + min_x * width) as f32,
+                (origin_y + min_y * height) as f32,
+            ),
+            egui::pos2(
+                (origin_x + max_x * width) as f32,
+                (origin_y + max_y * height) as f32,
+            ),
+        );
+        painter.rect(
+            rect,
+            0.0,
+            egui::Color32::from_rgba_unmultiplied(66, 135, 245, 48),
+            egui::Stroke::new(1.5, egui::Color32::from_rgb(66, 135, 245)),
+        );
+    }
+}
+
+/// Outlines the cell painted under the cursor in every view, snapped to its edges.
+#[allow(clippy::cast_possible_truncation)]
+fn draw_brush_outline(ctx: &egui::Context, mapping: &CellMapping, [x, y]: [u32; 2]) {
+    let points = f64::from(ctx.pixels_per_point());
+    let painter = ctx.layer_painter(egui::LayerId::new(
+        egui::Order::Background,
+        egui::Id::new("brush outline"),
+    ));
+    let [origin_x, origin_y] = mapping.origin.map(|position| position / points);
+    let [width, height] = mapping.cell_size.map(|size| size / points);
+    let view_width = mapping.view_width / points;
+    for view in 0..mapping.views {
+        let left = origin_x + view_width * f64::from(view) + f64::from(x) * width;
+        let top = origin_y + f64::from(y) * height;
+        let rect = egui::Rect::from_min_size(
+            egui::pos2(left as f32, top as f32),
+            egui::vec2(width as f32, height as f32),
+        );
+        // Small cells are outlined by a circle around them, so the brush stays visible.
+        if rect.width().min(rect.height()) < BRUSH_OUTLINE_MIN_SIZE {
+            painter.circle_stroke(
+                rect.center(),
+                BRUSH_OUTLINE_MIN_SIZE,
+                egui::Stroke::new(1.5, egui::Color32::from_rgb(66, 135, 245)),
+            );
+        } else {
+            painter.rect_stroke(
+                rect,
+                0.0,
+                egui::Stroke::new(1.5, egui::Color32::from_rgb(66, 135, 245)),
+            );
+        }
+    }
+}
+
+/// Draws cell coordinates along the top and the left edge of every view.
+#[allow(clippy::cast_possible_truncation)]
+fn draw_ruler(ctx: &egui::Context, mapping: &CellMapping) {
+    let points = f64::from(ctx.pixels_per_point());
+    let screen = ctx.input().screen_rect();
+    let painter = ctx.layer_painter(egui::LayerId::new(
+        egui::Order::Background,
+        egui::Id::new("ruler"),
+    ));
+    let stroke = egui::Stroke::new(1.0, egui::Color32::BLACK);
+    let font = egui::FontId::monospace(10.0);
+
+    let [origin_x, origin_y] = mapping.origin.map(|position| position / points);
+    let [width, height] = mapping.cell_size.map(|size| size / points);
+    let view_width = mapping.view_width / points;
+    for view in 0..mapping.views {
+        let left = view_width * f64::from(view);
+        let origin_x = origin_x + left;
+        for x in ticks(
+            origin_x,
+            width,
+            left..left + view_width,
+            mapping.game_size.0,
+        ) {
+            let position = (origin_x + f64::from(x) * width) as f32;
+            let top = egui::pos2(position, screen.top());
+            painter.line_segment([top, top + egui::vec2(0.0, TICK_LENGTH)], stroke);
+            painter.text(
+                top + egui::vec2(2.0, TICK_LENGTH),
+                egui::Align2::LEFT_TOP,
+                x,
+                font.clone(),
+                egui::Color32::BLACK,
+            );
+        }
+        let top = f64::from(screen.top());
+        for y in ticks(
+            origin_y,
+            height,
+            top..f64::from(screen.bottom()),
+            mapping.game_size.1,
+        ) {
+            let edge = egui::pos2(left as f32, (origin_y + f64::from(y) * height) as f32);
+            painter.line_segment([edge, edge + egui::vec2(TICK_LENGTH, 0.0)], stroke);
+            painter.text(
+                edge + egui::vec2(TICK_LENGTH + 2.0, 0.0),
+                egui::Align2::LEFT_CENTER,
+                y,
+                font.clone(),
+                egui::Color32::BLACK,
+            );
+        }
+    }
+}
+
+/// Returns coordinates of ticks of cells `size` points big starting at `origin`
+/// which are visible in given `range` of points and lie on the board of `cells` cells.
+/// Ticks are placed every 1, 2 or 5 times a power of ten cells,
+/// the smallest step that keeps them [`RULER_SPACING`] apart.
+#[allow(clippy::cast_possible_truncation)]
+#[allow(clippy::cast_sign_loss)]
+fn ticks(origin: f64, size: f64, range: Range<f64>, cells: u32) -> impl Iterator<Item = u32> {
+    let step = iter::successors(Some(1_u32), |magnitude| magnitude.checked_mul(10))
+        .flat_map(|magnitude| [1, 2, 5].map(|factor| magnitude.saturating_mul(factor)))
+        .find(|step| f64::from(*step) * size >= RULER_SPACING)
+        .unwrap_or(u32::MAX);
+    let first = ((range.start - origin) / size).max(0.0).ceil() as u32;
+    let last = ((range.end - origin) / size).min(f64::from(cells)) as u32;
+    let first = first.saturating_add((step - first % step) % step);
+    (first..=last)
+        .step_by(step as usize)
+        .filter(move |cell| range.contains(&(origin + f64::from(*cell) * size)))
+}
+
+/// Returns text describing the bounding box of live cells.
+fn bounds_text(bounds: Option<LiveBounds>) -> String {
+    match bounds {
+        Some((min_x, min_y, max_x, max_y)) => format!(
+            "Live cells: ({min_x}, {min_y}) to ({max_x}, {max_y}), {}x{}",
+            max_x - min_x + 1,
+            max_y - min_y + 1
+        ),
+        None => "Live cells: none".to_owned(),
+    }
+}
+
+/// Returns text describing the cell under the cursor.
+fn cursor_text(cell: Option<([u32; 2], u32)>) -> String {
+    match cell {
+        Some(([x, y], 0)) => format!("Cursor: ({x}, {y}) dead"),
+        Some(([x, y], 1)) => format!("Cursor: ({x}, {y}) alive"),
+        Some(([x, y], state)) => format!("Cursor: ({x}, {y}) dying ({state})"),
+        None => "Cursor: outside of the board".to_owned(),
+    }
+}
+
+/// Returns text describing the period the board repeats with.
+fn stabilized_text(period: u32) -> String {
+    if period == 1 {
+        "Board stabilized".to_owned()
+    } else {
+        format!("Board oscillates with period {period}")
+    }
 }
 
 /// Return text that should be displayed on the pause button.
@@ -116,4 +1643,40 @@ fn play_text(pause: bool) -> &'static str {
     } else {
         "Pause"
     }
-}
\ No newline at end of file
+}
+
+/// Return text that should be displayed on the record button.
+fn record_text(recording: bool) -> &'static str {
+    if recording {
+        "Stop recording"
+    } else {
+        "Record GIF"
+    }
+}
+
+/// Draws a small line chart of given values, newest value on the right.
+#[allow(clippy::cast_precision_loss)]
+fn sparkline(ui: &mut egui::Ui, values: &VecDeque<u32>) {
+    let (rect, _) = ui.allocate_exact_size(egui::vec2(200.0, 40.0), egui::Sense::hover());
+    let painter = ui.painter_at(rect);
+    painter.rect_stroke(rect, 0.0, ui.visuals().widgets.noninteractive.bg_stroke);
+
+    let max = values.iter().copied().max().unwrap_or(0).max(1) as f32;
+    let step = rect.width() / (FPS_HISTORY_LENGTH - 1) as f32;
+    let points = values
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, value)| {
+            egui::pos2(
+                rect.right() - i as f32 * step,
+                rect.bottom() - *value as f32 / max * rect.height(),
+            )
+        })
+        .collect();
+
+    painter.add(egui::Shape::line(
+        points,
+        ui.visuals().widgets.inactive.fg_stroke,
+    ));
+}
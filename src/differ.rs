@@ -0,0 +1,144 @@
+use std::sync::Arc;
+
+use vulkano::{
+    command_buffer::{AutoCommandBufferBuilder, CommandBufferUsage, PrimaryCommandBuffer},
+    descriptor_set::{PersistentDescriptorSet, WriteDescriptorSet},
+    device::Queue,
+    pipeline::{ComputePipeline, Pipeline, PipelineBindPoint},
+    sync::GpuFuture,
+};
+
+use crate::{BoardLayout, Error, GpuBuffer};
+
+/// This module contains compiled compute shader and shader data structures.
+mod shader {
+    vulkano_shaders::shader! {
+        ty: "compute",
+        path: "src/shaders/difference.comp",
+        include: ["src/shaders"],
+    }
+}
+
+/// This struct represents a pipeline that can be used to
+/// find cells which differ between two boards of the same layout.
+pub struct Differ {
+    group_size: [u32; 3],
+    compute_queue: Arc<Queue>,
+    pipeline: Arc<ComputePipeline>,
+    descriptor: Arc<PersistentDescriptorSet>,
+}
+
+impl Differ {
+    /// Creates a new [`Differ`] pipeline writing the difference of `first`
+    /// and `second` into `output`.
+    ///
+    /// It creates new [`ComputePipeline`] and [`PersistentDescriptorSet`].
+    ///
+    /// # Panics
+    ///
+    /// - when [`Differ::try_new`] fails.
+    #[must_use]
+    pub fn new(
+        compute_queue: Arc<Queue>,
+        [first, second, output]: [Arc<GpuBuffer>; 3],
+        layout: BoardLayout,
+        workgroup: u32,
+    ) -> Self {
+        Self::try_new(compute_queue, [first, second, output], layout, workgroup)
+            .expect("Cannot create differ")
+    }
+
+    /// Creates a new [`Differ`] pipeline writing the difference of `first`
+    /// and `second` into `output`.
+    ///
+    /// It creates new [`ComputePipeline`] and [`PersistentDescriptorSet`].
+    ///
+    /// # Errors
+    ///
+    /// - when the underlying Vulkano struct creations fail.
+    /// - when the shader entry point is not found.
+    /// - when the descriptor set creation fails.
+    pub fn try_new(
+        compute_queue: Arc<Queue>,
+        [first, second, output]: [Arc<GpuBuffer>; 3],
+        layout: BoardLayout,
+        workgroup: u32,
+    ) -> Result<Self, Error> {
+        let device = compute_queue.device().clone();
+
+        let shader = shader::load(device.clone())?;
+        let pipeline = ComputePipeline::new(
+            device,
+            shader.entry_point("main").ok_or(Error::MissingEntryPoint)?,
+            &shader::SpecializationConstants {
+                width: layout.size().0,
+                height: layout.size().1,
+                cellBits: layout.cell_bits(),
+                constant_100: workgroup,
+                constant_101: workgroup,
+            },
+            None,
+            |_| {},
+        )?;
+
+        let set_layout = pipeline
+            .layout()
+            .set_layouts()
+            .get(0)
+            .ok_or(Error::MissingDescriptorSetLayout)?;
+
+        let descriptor = PersistentDescriptorSet::new(
+            set_layout.clone(),
+            [
+                WriteDescriptorSet::buffer(0, first),
+                WriteDescriptorSet::buffer(1, second),
+                WriteDescriptorSet::buffer(2, output),
+            ],
+        )?;
+
+        Ok(Self {
+            group_size: layout.group_count(workgroup),
+            compute_queue,
+            pipeline,
+            descriptor,
+        })
+    }
+
+    /// Returns a gpu future that can be used to execute the pipeline.
+    /// As a result every word of the output is the XOR of the words of both boards,
+    /// so cells which differ are non-zero.
+    ///
+    /// # Panics
+    ///
+    /// - when the command buffer creation fails.
+    /// - when the command buffer building fails.
+    /// - when the command buffer recording fails.
+    /// - when the command buffer execution fails.
+    #[must_use]
+    pub fn run(&self) -> Box<dyn GpuFuture> {
+        let mut builder = AutoCommandBufferBuilder::primary(
+            self.compute_queue.device().clone(),
+            self.compute_queue.queue_family_index(),
+            CommandBufferUsage::OneTimeSubmit,
+        )
+        .expect("Cannot create command buffer builder");
+
+        builder
+            .bind_descriptor_sets(
+                PipelineBindPoint::Compute,
+                self.pipeline.layout().clone(),
+                0,
+                self.descriptor.clone(),
+            )
+            .bind_pipeline_compute(self.pipeline.clone())
+            .dispatch(self.group_size)
+            .expect("Cannot record command buffer");
+
+        builder
+            .build()
+            .expect("Cannot build command buffer")
+            .execute(self.compute_queue.clone())
+            .expect("Cannot execute command buffer")
+            .boxed()
+    }
+}
@@ -0,0 +1,215 @@
+use std::fmt;
+
+use vulkano::{
+    command_buffer::{
+        BuildError, CommandBufferBeginError, CopyError, PipelineExecutionError, QueryError,
+    },
+    descriptor_set::DescriptorSetCreationError,
+    memory::DeviceMemoryError,
+    pipeline::{compute::ComputePipelineCreationError, graphics::GraphicsPipelineCreationError},
+    query::QueryPoolCreationError,
+    render_pass::RenderPassCreationError,
+    shader::ShaderCreationError,
+    sync::FlushError,
+};
+use winit::error::OsError;
+
+/// Number of bytes in a mebibyte.
+const MIB: u64 = 1024 * 1024;
+
+/// Error returned when the simulation or one of its pipelines cannot be created.
+#[derive(Debug)]
+pub enum Error {
+    Window(OsError),
+    Memory(DeviceMemoryError),
+    Shader(ShaderCreationError),
+    MissingEntryPoint,
+    MissingDescriptorSetLayout,
+    MissingSubpass,
+    ComputePipeline(ComputePipelineCreationError),
+    GraphicsPipeline(GraphicsPipelineCreationError),
+    RenderPass(RenderPassCreationError),
+    DescriptorSet(DescriptorSetCreationError),
+    CommandBufferBegin(CommandBufferBeginError),
+    CommandBufferBuild(BuildError),
+    Copy(CopyError),
+    Dispatch(PipelineExecutionError),
+    QueryPool(QueryPoolCreationError),
+    Query(QueryError),
+    Flush(FlushError),
+    UnsupportedWorkgroup {
+        size: u32,
+        max_size: [u32; 2],
+        max_invocations: u32,
+    },
+    UnsupportedSharedMemory {
+        size: u32,
+        requested: u64,
+        available: u32,
+    },
+    UnsupportedSamples {
+        samples: u32,
+        supported: Vec<u32>,
+    },
+    OutOfMemory {
+        requested: u64,
+        available: u64,
+    },
+    BufferTooLarge {
+        size: (u32, u32),
+        requested: u64,
+        max_range: u32,
+    },
+    UnsupportedDevice {
+        name: String,
+        missing: Vec<String>,
+    },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Window(error) => write!(f, "cannot create window: {error}"),
+            Self::Memory(error) => write!(f, "cannot allocate buffer: {error}"),
+            Self::Shader(error) => write!(f, "cannot load shader: {error}"),
+            Self::MissingEntryPoint => f.write_str("shader has no entry point"),
+            Self::MissingDescriptorSetLayout => f.write_str("pipeline has no descriptor set"),
+            Self::MissingSubpass => f.write_str("render pass has no subpass"),
+            Self::ComputePipeline(error) => write!(f, "cannot create compute pipeline: {error}"),
+            Self::GraphicsPipeline(error) => {
+                write!(f, "cannot create graphics pipeline: {error}")
+            }
+            Self::RenderPass(error) => write!(f, "cannot create render pass: {error}"),
+            Self::DescriptorSet(error) => write!(f, "cannot create descriptor set: {error}"),
+            Self::CommandBufferBegin(error) => write!(f, "cannot begin command buffer: {error}"),
+            Self::CommandBufferBuild(error) => write!(f, "cannot build command buffer: {error}"),
+            Self::Copy(error) => write!(f, "cannot record copy: {error}"),
+            Self::Dispatch(error) => write!(f, "cannot record dispatch: {error}"),
+            Self::QueryPool(error) => write!(f, "cannot create query pool: {error}"),
+            Self::Query(error) => write!(f, "cannot record query: {error}"),
+            Self::Flush(error) => write!(f, "cannot wait for the GPU: {error}"),
+            Self::UnsupportedWorkgroup {
+                size,
+                max_size: [max_x, max_y],
+                max_invocations,
+            } => write!(
+                f,
+                "work group of {size}x{size} is not supported, the device allows at most \
+                 {max_x}x{max_y} and {max_invocations} invocations"
+            ),
+            Self::UnsupportedSharedMemory {
+                size,
+                requested,
+                available,
+            } => write!(
+                f,
+                "work group of {size}x{size} needs {requested} bytes of shared memory, \
+                 the device has only {available} bytes"
+            ),
+            Self::UnsupportedSamples { samples, supported } => {
+                write!(f, "{samples} samples are not supported, the device allows")?;
+                supported
+                    .iter()
+                    .try_for_each(|samples| write!(f, " {samples}"))
+            }
+            Self::OutOfMemory {
+                requested,
+                available,
+            } => write!(
+                f,
+                "board needs {} MiB of device memory, but only {} MiB may be used, \
+                 try a smaller board, fewer history generations or --cell-bits 1",
+                requested / MIB,
+                available / MIB
+            ),
+            Self::BufferTooLarge {
+                size: (width, height),
+                requested,
+                max_range,
+            } => write!(
+                f,
+                "board of {width}x{height} needs a buffer of {requested} bytes, but storage \
+                 buffers of the device may have at most {max_range} bytes, \
+                 try a smaller board or --cell-bits 1"
+            ),
+            Self::UnsupportedDevice { name, missing } => {
+                write!(f, "device {name} is not supported, it lacks:")?;
+                missing
+                    .iter()
+                    .try_for_each(|requirement| write!(f, "\n  - {requirement}"))
+            }
+        }
+    }
+}
+
+impl Error {
+    /// Returns whether the GPU did not finish the work in time or the device was lost,
+    /// so nothing should be submitted to it or waited for anymore.
+    #[must_use]
+    pub fn is_stall(&self) -> bool {
+        matches!(
+            self,
+            Self::Flush(FlushError::Timeout | FlushError::DeviceLost)
+        )
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Window(error) => Some(error),
+            Self::Memory(error) => Some(error),
+            Self::Shader(error) => Some(error),
+            Self::ComputePipeline(error) => Some(error),
+            Self::GraphicsPipeline(error) => Some(error),
+            Self::RenderPass(error) => Some(error),
+            Self::DescriptorSet(error) => Some(error),
+            Self::CommandBufferBegin(error) => Some(error),
+            Self::CommandBufferBuild(error) => Some(error),
+            Self::Copy(error) => Some(error),
+            Self::Dispatch(error) => Some(error),
+            Self::QueryPool(error) => Some(error),
+            Self::Query(error) => Some(error),
+            Self::Flush(error) => Some(error),
+            Self::MissingEntryPoint
+            | Self::MissingDescriptorSetLayout
+            | Self::MissingSubpass
+            | Self::UnsupportedWorkgroup { .. }
+            | Self::UnsupportedSharedMemory { .. }
+            | Self::UnsupportedSamples { .. }
+            | Self::OutOfMemory { .. }
+            | Self::BufferTooLarge { .. }
+            | Self::UnsupportedDevice { .. } => None,
+        }
+    }
+}
+
+/// Implements conversion from an underlying error into given [`Error`] variant.
+macro_rules! impl_from {
+    ($($variant:ident($error:ty)),* $(,)?) => {
+        $(
+            impl From<$error> for Error {
+                fn from(error: $error) -> Self {
+                    Self::$variant(error)
+                }
+            }
+        )*
+    };
+}
+
+impl_from!(
+    Window(OsError),
+    Memory(DeviceMemoryError),
+    Shader(ShaderCreationError),
+    ComputePipeline(ComputePipelineCreationError),
+    GraphicsPipeline(GraphicsPipelineCreationError),
+    RenderPass(RenderPassCreationError),
+    DescriptorSet(DescriptorSetCreationError),
+    CommandBufferBegin(CommandBufferBeginError),
+    CommandBufferBuild(BuildError),
+    Copy(CopyError),
+    Dispatch(PipelineExecutionError),
+    QueryPool(QueryPoolCreationError),
+    Query(QueryError),
+    Flush(FlushError),
+);
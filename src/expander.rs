@@ -0,0 +1,215 @@
+use std::sync::Arc;
+
+use vulkano::{
+    buffer::{BufferUsage, CpuAccessibleBuffer},
+    command_buffer::{
+        AutoCommandBufferBuilder, CommandBufferUsage, FillBufferInfo, PrimaryCommandBuffer,
+    },
+    descriptor_set::{PersistentDescriptorSet, WriteDescriptorSet},
+    device::Queue,
+    pipeline::{ComputePipeline, Pipeline, PipelineBindPoint},
+    sync::GpuFuture,
+};
+
+use crate::{vulkan, BoardLayout, CommandBuffer, Error, ExpandConfig, GpuBuffer};
+
+mod shader {
+    vulkano_shaders::shader! {
+        path: "src/shaders/border.comp",
+        include: ["src/shaders"],
+        ty: "compute",
+    }
+}
+
+/// This struct represents a pipeline checking whether
+/// live cells came close to the edge of the board.
+///
+/// When they do, the board should be expanded to a bigger [`BoardLayout`]
+/// returned by [`Expander::expanded_layout`].
+pub struct Expander {
+    layout: BoardLayout,
+    max_size: u32,
+    /// Number of words of the biggest board a single storage buffer binding can hold.
+    max_words: u64,
+    compute_queue: Arc<Queue>,
+    flag: Arc<CpuAccessibleBuffer<[u32]>>,
+    check_buffer: Arc<CommandBuffer>,
+}
+
+impl Expander {
+    /// Creates a new [`Expander`] pipeline for given buffer.
+    ///
+    /// # Panics
+    ///
+    /// - when [`Expander::try_new`] fails.
+    #[must_use]
+    pub fn new(
+        compute_queue: Arc<Queue>,
+        buffer: Arc<GpuBuffer>,
+        layout: BoardLayout,
+        workgroup: u32,
+        config: &ExpandConfig,
+    ) -> Self {
+        Self::try_new(compute_queue, buffer, layout, workgroup, config)
+            .expect("Cannot create expander")
+    }
+
+    /// Creates a new [`Expander`] pipeline for given buffer.
+    ///
+    /// # Errors
+    ///
+    /// - when the flag buffer creation fails.
+    /// - when the shader entry point is not found.
+    /// - when the compute pipeline creation fails.
+    /// - when the descriptor set creation fails.
+    /// - when the command buffer recording fails.
+    pub fn try_new(
+        compute_queue: Arc<Queue>,
+        buffer: Arc<GpuBuffer>,
+        layout: BoardLayout,
+        workgroup: u32,
+        config: &ExpandConfig,
+    ) -> Result<Self, Error> {
+        let device = compute_queue.device().clone();
+
+        let flag = CpuAccessibleBuffer::from_iter(
+            device.clone(),
+            BufferUsage {
+                storage_buffer: true,
+                transfer_dst: true,
+                ..BufferUsage::empty()
+            },
+            true,
+            [0],
+        )?;
+
+        let shader = shader::load(device.clone())?;
+        let pipeline = ComputePipeline::new(
+            device.clone(),
+            shader.entry_point("main").ok_or(Error::MissingEntryPoint)?,
+            &shader::SpecializationConstants {
+                width: layout.size().0,
+                height: layout.size().1,
+                cellBits: layout.cell_bits(),
+                margin: config.margin(),
+                constant_100: workgroup,
+                constant_101: workgroup,
+            },
+            None,
+            |_| {},
+        )?;
+
+        let descriptor = PersistentDescriptorSet::new(
+            pipeline
+                .layout()
+                .set_layouts()
+                .get(0)
+                .ok_or(Error::MissingDescriptorSetLayout)?
+                .clone(),
+            [
+                WriteDescriptorSet::buffer(0, buffer),
+                WriteDescriptorSet::buffer(1, flag.clone()),
+            ],
+        )?;
+
+        let mut builder = AutoCommandBufferBuilder::primary(
+            device,
+            compute_queue.queue_family_index(),
+            CommandBufferUsage::MultipleSubmit,
+        )?;
+
+        builder
+            .fill_buffer(FillBufferInfo::dst_buffer(flag.clone()))?
+            .bind_descriptor_sets(
+                PipelineBindPoint::Compute,
+                pipeline.layout().clone(),
+                0,
+                descriptor,
+            )
+            .bind_pipeline_compute(pipeline)
+            .dispatch(layout.group_count(workgroup))?;
+
+        let max_words = u64::from(
+            compute_queue
+                .device()
+                .physical_device()
+                .properties()
+                .max_storage_buffer_range,
+        ) / 4;
+        let expander = Self {
+            layout,
+            max_size: config.max_size(),
+            max_words,
+            compute_queue,
+            flag,
+            check_buffer: Arc::new(builder.build()?),
+        };
+        let next = expander.next_layout();
+        if next.size() != layout.size() && next.len() > max_words {
+            let (width, height) = next.size();
+            log::warn!(
+                "Board will not be expanded to {width}x{height}, its buffer would exceed \
+                 the largest storage buffer range of {} bytes",
+                max_words * 4
+            );
+        }
+        Ok(expander)
+    }
+
+    /// Checks the board and returns layout it should be expanded to.
+    /// Returns `None` when no live cell is near the edge, when the board already
+    /// has the maximum size or when the expanded board would not fit into a storage buffer.
+    ///
+    /// Both dimensions are doubled, but never beyond the maximum size.
+    ///
+    /// # Panics
+    ///
+    /// - when [`Expander::check`] fails.
+    /// - when waiting for the check fails.
+    /// - when [`Expander::checked_layout`] fails.
+    #[must_use]
+    pub fn expanded_layout(&self) -> Option<BoardLayout> {
+        vulkan::try_wait(self.check()?, None).expect("Cannot wait for command buffer");
+        self.checked_layout()
+    }
+
+    /// Starts checking the board, the layout is returned by [`Expander::checked_layout`]
+    /// once the returned [`GpuFuture`] finishes. Returns `None` when the board already
+    /// has the maximum size or when the expanded board would not fit into a storage buffer.
+    ///
+    /// # Panics
+    ///
+    /// - when the command buffer execution fails.
+    #[must_use]
+    pub fn check(&self) -> Option<Box<dyn GpuFuture>> {
+        let layout = self.next_layout();
+        if layout.size() == self.layout.size() || layout.len() > self.max_words {
+            return None;
+        }
+        let future = self
+            .check_buffer
+            .clone()
+            .execute(self.compute_queue.clone())
+            .expect("Cannot execute command buffer");
+        Some(future.boxed())
+    }
+
+    /// Returns layout the board should be expanded to according to the last finished
+    /// [`Expander::check`], `None` when no live cell is near the edge.
+    ///
+    /// # Panics
+    ///
+    /// - when the flag buffer cannot be read.
+    #[must_use]
+    pub fn checked_layout(&self) -> Option<BoardLayout> {
+        let reached_border = self.flag.read().expect("Cannot read flag buffer")[0] != 0;
+        reached_border.then(|| self.next_layout())
+    }
+
+    /// Returns layout with both dimensions doubled, but never beyond the maximum size.
+    fn next_layout(&self) -> BoardLayout {
+        let (width, height) = self.layout.size();
+        let expand = |size: u32| size.saturating_mul(2).min(self.max_size).max(size);
+        BoardLayout::new((expand(width), expand(height)), self.layout.cell_bits())
+    }
+}
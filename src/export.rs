@@ -0,0 +1,108 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use image::{GrayImage, ImageResult, Luma};
+use vulkano::sync::GpuFuture;
+
+use crate::{BoardLayout, ExportConfig, Readback};
+
+/// Converts words of the board into a grayscale image.
+/// Dead cells are white, alive cells are black and every cell takes `scale` × `scale` pixels.
+#[must_use]
+pub fn board_image(layout: BoardLayout, words: &[u32], scale: u32) -> GrayImage {
+    let (width, height) = layout.size();
+    let scale = scale.max(1);
+    GrayImage::from_fn(width * scale, height * scale, |x, y| {
+        let alive = layout.get(words, x / scale, y / scale) != 0;
+        Luma([if alive { 0x00 } else { 0xFF }])
+    })
+}
+
+/// This struct represents an export of generations into numbered PNG files.
+///
+/// Files are named `frame_00001.png`, `frame_00002.png` and so on,
+/// so they can be assembled into a video by external tools.
+pub struct Exporter {
+    readback: Readback,
+    directory: PathBuf,
+    first: u64,
+    last: u64,
+    stride: u64,
+    scale: u32,
+    frames: u32,
+}
+
+impl Exporter {
+    /// Creates a new [`Exporter`] writing to the directory from the [`ExportConfig`].
+    ///
+    /// # Errors
+    ///
+    /// - when the directory cannot be created.
+    pub fn new(readback: Readback, directory: &Path, config: &ExportConfig) -> ImageResult<Self> {
+        fs::create_dir_all(directory)?;
+        Ok(Self {
+            readback,
+            directory: directory.to_owned(),
+            first: config.first(),
+            last: config.last(),
+            stride: config.stride().max(1),
+            scale: config.scale(),
+            frames: 0,
+        })
+    }
+
+    /// Replaces the readback of the board, used when the board is replaced by a bigger one.
+    #[inline]
+    pub fn set_readback(&mut self, readback: Readback) {
+        self.readback = readback;
+    }
+
+    /// Starts copying given generation into host memory when it is in the configured range
+    /// and falls on the stride, the image is written by [`Exporter::capture`]
+    /// once the returned [`GpuFuture`] finishes.
+    ///
+    /// # Panics
+    ///
+    /// - when [`Readback::copy`] fails.
+    #[must_use]
+    pub fn copy(&self, generation: u64) -> Option<Box<dyn GpuFuture>> {
+        self.exports(generation).then(|| self.readback.copy())
+    }
+
+    /// Exports given generation copied by [`Exporter::copy`] when it is in the configured range
+    /// and falls on the stride. Returns `false` once the last generation of the range has been passed.
+    ///
+    /// # Errors
+    ///
+    /// - when the image cannot be written.
+    pub fn capture(&mut self, generation: u64) -> ImageResult<bool> {
+        if self.exports(generation) {
+            self.frames += 1;
+            let image = board_image(self.readback.layout(), &self.readback.copied(), self.scale);
+            image.save(self.directory.join(format!("frame_{:05}.png", self.frames)))?;
+        }
+        Ok(generation < self.last)
+    }
+
+    /// Returns whether given generation is in the configured range and falls on the stride.
+    fn exports(&self, generation: u64) -> bool {
+        (self.first..=self.last).contains(&generation)
+            && (generation - self.first) % self.stride == 0
+    }
+
+    /// Returns the next generation after given one that is exported,
+    /// `None` when the range has been passed.
+    #[must_use]
+    pub fn next_generation(&self, generation: u64) -> Option<u64> {
+        let next = if generation < self.first {
+            self.first
+        } else {
+            let strides = (generation - self.first) / self.stride + 1;
+            self.first
+                .saturating_add(strides.saturating_mul(self.stride))
+        };
+        (next <= self.last).then_some(next)
+    }
+}
@@ -0,0 +1,200 @@
+use std::sync::Arc;
+
+use vulkano::{
+    command_buffer::{AutoCommandBufferBuilder, CommandBufferUsage, PrimaryCommandBuffer},
+    descriptor_set::{PersistentDescriptorSet, WriteDescriptorSet},
+    device::Queue,
+    pipeline::{ComputePipeline, Pipeline, PipelineBindPoint},
+    sync::GpuFuture,
+};
+
+use crate::{BoardLayout, Error, GpuBuffer};
+
+/// This module contains compiled compute shader and shader data structures.
+mod shader {
+    vulkano_shaders::shader! {
+        ty: "compute",
+        path: "src/shaders/fill.comp",
+        include: ["src/shaders"],
+        types_meta: {
+            use bytemuck::{Pod, Zeroable};
+            #[derive(Clone, Copy, Pod, Zeroable)]
+        }
+    }
+}
+
+/// What cells of a filled rectangle become.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FillMode {
+    #[default]
+    Alive,
+    Dead,
+    /// Every cell becomes alive with the selected density.
+    Random,
+}
+
+impl FillMode {
+    /// All fill modes in order they should be displayed.
+    pub const ALL: [Self; 3] = [Self::Alive, Self::Dead, Self::Random];
+
+    /// Returns human readable name of the fill mode.
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Alive => "Alive",
+            Self::Dead => "Dead",
+            Self::Random => "Random",
+        }
+    }
+
+    /// Returns identifier of the fill mode understood by the shader.
+    fn id(self) -> u32 {
+        match self {
+            Self::Dead => 0,
+            Self::Alive => 1,
+            Self::Random => 2,
+        }
+    }
+}
+
+/// This struct represents a pipeline that can be used to
+/// fill a rectangle of the board of the game of life.
+pub struct Filler {
+    workgroup: u32,
+    layout: BoardLayout,
+    compute_queue: Arc<Queue>,
+    pipeline: Arc<ComputePipeline>,
+    descriptor: Arc<PersistentDescriptorSet>,
+}
+
+impl Filler {
+    /// Creates a new [`Filler`] pipeline.
+    ///
+    /// It creates new [`ComputePipeline`] and [`PersistentDescriptorSet`].
+    ///
+    /// # Panics
+    ///
+    /// - when [`Filler::try_new`] fails.
+    #[must_use]
+    pub fn new(
+        compute_queue: Arc<Queue>,
+        buffer: Arc<GpuBuffer>,
+        layout: BoardLayout,
+        workgroup: u32,
+    ) -> Self {
+        Self::try_new(compute_queue, buffer, layout, workgroup).expect("Cannot create filler")
+    }
+
+    /// Creates a new [`Filler`] pipeline.
+    ///
+    /// It creates new [`ComputePipeline`] and [`PersistentDescriptorSet`].
+    ///
+    /// # Errors
+    ///
+    /// - when the underlying Vulkano struct creations fail.
+    /// - when the shader entry point is not found.
+    /// - when the descriptor set creation fails.
+    pub fn try_new(
+        compute_queue: Arc<Queue>,
+        buffer: Arc<GpuBuffer>,
+        layout: BoardLayout,
+        workgroup: u32,
+    ) -> Result<Self, Error> {
+        let device = compute_queue.device().clone();
+
+        let shader = shader::load(device.clone())?;
+        let pipeline = ComputePipeline::new(
+            device,
+            shader.entry_point("main").ok_or(Error::MissingEntryPoint)?,
+            &shader::SpecializationConstants {
+                width: layout.size().0,
+                height: layout.size().1,
+                cellBits: layout.cell_bits(),
+                constant_100: workgroup,
+                constant_101: workgroup,
+            },
+            None,
+            |_| {},
+        )?;
+
+        let set_layout = pipeline
+            .layout()
+            .set_layouts()
+            .get(0)
+            .ok_or(Error::MissingDescriptorSetLayout)?;
+
+        let descriptor = PersistentDescriptorSet::new(
+            set_layout.clone(),
+            [WriteDescriptorSet::buffer(0, buffer)],
+        )?;
+
+        Ok(Self {
+            workgroup,
+            layout,
+            compute_queue,
+            pipeline,
+            descriptor,
+        })
+    }
+
+    /// Returns a gpu future that can be used to execute the pipeline.
+    /// Cells of the rectangle spanned by given corners, both included, are set
+    /// according to the [`FillMode`], `density` is the share of alive cells
+    /// of [`FillMode::Random`]. Only words overlapping the rectangle are dispatched.
+    ///
+    /// # Panics
+    ///
+    /// - when the command buffer creation fails.
+    /// - when the command buffer building fails.
+    /// - when the command buffer recording fails.
+    /// - when the command buffer execution fails.
+    #[must_use]
+    pub fn run(&self, corners: [[u32; 2]; 2], mode: FillMode, density: f32) -> Box<dyn GpuFuture> {
+        let [[first_x, first_y], [second_x, second_y]] = corners;
+        let min_corner = [first_x.min(second_x), first_y.min(second_y)];
+        let max_corner = [first_x.max(second_x), first_y.max(second_y)];
+        let cells_per_word = self.layout.cells_per_word();
+        let words = max_corner[0] / cells_per_word - min_corner[0] / cells_per_word + 1;
+        let rows = max_corner[1] - min_corner[1] + 1;
+
+        let mut builder = AutoCommandBufferBuilder::primary(
+            self.compute_queue.device().clone(),
+            self.compute_queue.queue_family_index(),
+            CommandBufferUsage::OneTimeSubmit,
+        )
+        .expect("Cannot create command buffer builder");
+
+        builder
+            .bind_descriptor_sets(
+                PipelineBindPoint::Compute,
+                self.pipeline.layout().clone(),
+                0,
+                self.descriptor.clone(),
+            )
+            .push_constants(
+                self.pipeline.layout().clone(),
+                0,
+                shader::ty::PushConstants {
+                    minCorner: min_corner,
+                    maxCorner: max_corner,
+                    mode: mode.id(),
+                    density,
+                    seed: rand::random(),
+                },
+            )
+            .bind_pipeline_compute(self.pipeline.clone())
+            .dispatch([
+                (words + self.workgroup - 1) / self.workgroup,
+                (rows + self.workgroup - 1) / self.workgroup,
+                1,
+            ])
+            .expect("Cannot record command buffer");
+
+        builder
+            .build()
+            .expect("Cannot build command buffer")
+            .execute(self.compute_queue.clone())
+            .expect("Cannot execute command buffer")
+            .boxed()
+    }
+}
@@ -8,11 +8,12 @@ use vulkano::{
     sync::GpuFuture,
 };
 
-use crate::GpuBuffer;
+use crate::{BoardLayout, Error, GpuBuffer};
 
 mod shader {
     vulkano_shaders::shader! {
         path: "src/shaders/flip.comp",
+        include: ["src/shaders"],
         ty: "compute",
         types_meta: {
             use bytemuck::{Pod, Zeroable};
@@ -32,61 +33,105 @@ pub struct Flipper {
 impl Flipper {
     /// Creates a new [`Flipper`] pipeline.
     /// It creates new [`ComputePipeline`] and [`PersistentDescriptorSet`].
-    /// 
+    ///
     /// # Panics
-    /// 
+    ///
+    /// - when [`Flipper::try_new`] fails.
+    #[must_use]
+    pub fn new(compute_queue: Arc<Queue>, buffer: Arc<GpuBuffer>, layout: BoardLayout) -> Self {
+        Self::try_new(compute_queue, buffer, layout).expect("Cannot create flipper")
+    }
+
+    /// Creates a new [`Flipper`] pipeline.
+    /// It creates new [`ComputePipeline`] and [`PersistentDescriptorSet`].
+    ///
+    /// # Errors
+    ///
     /// - when the underlying Vulkano struct creations fail.
     /// - when the shader entry point is not found.
     /// - when the descriptor set creation fails.
     /// - when the compute pipeline creation fails.
-    #[must_use]
-    pub fn new(compute_queue: Arc<Queue>, buffer: Arc<GpuBuffer>, size: (u32, u32)) -> Self {
+    pub fn try_new(
+        compute_queue: Arc<Queue>,
+        buffer: Arc<GpuBuffer>,
+        layout: BoardLayout,
+    ) -> Result<Self, Error> {
         let device = compute_queue.device().clone();
 
-        let shader = shader::load(device.clone()).expect("failed to create shader module");
+        let shader = shader::load(device.clone())?;
         let pipeline = ComputePipeline::new(
             device,
-            shader
-                .entry_point("main")
-                .expect("failed to find entry point"),
+            shader.entry_point("main").ok_or(Error::MissingEntryPoint)?,
             &shader::SpecializationConstants {
-                width: size.0,
-                height: size.1,
+                width: layout.size().0,
+                height: layout.size().1,
+                cellBits: layout.cell_bits(),
             },
             None,
             |_| {},
-        )
-        .expect("failed to create compute pipeline");
+        )?;
 
         let descriptor = PersistentDescriptorSet::new(
             pipeline
                 .layout()
                 .set_layouts()
                 .get(0)
-                .expect("Cannot get descriptor set layout")
+                .ok_or(Error::MissingDescriptorSetLayout)?
                 .clone(),
             [WriteDescriptorSet::buffer(0, buffer)],
-        )
-        .expect("Cannot create descriptor set");
+        )?;
 
-        Self {
+        Ok(Self {
             compute_queue,
             pipeline,
             descriptor,
-        }
+        })
     }
 
     /// Runs the pipeline and returns gpu future.
     /// As a result cells are flipped.
-    /// 
+    ///
     /// # Panics
-    /// 
+    ///
     /// - when the command buffer creation fails.
     /// - when the command buffer building fails.
     /// - when the command buffer recording fails.
     /// - when the command buffer submission fails.
     #[must_use]
     pub fn flip(&self, position: [u32; 2]) -> Box<dyn GpuFuture> {
+        self.run(&[position], false)
+    }
+
+    /// Runs the pipeline and returns gpu future.
+    /// As a result the cell becomes alive, regardless of its state.
+    ///
+    /// # Panics
+    ///
+    /// - when the command buffer creation fails.
+    /// - when the command buffer building fails.
+    /// - when the command buffer recording fails.
+    /// - when the command buffer submission fails.
+    #[must_use]
+    pub fn paint(&self, position: [u32; 2]) -> Box<dyn GpuFuture> {
+        self.run(&[position], true)
+    }
+
+    /// Runs the pipeline and returns gpu future.
+    /// As a result every given cell becomes alive, all in a single submission.
+    ///
+    /// # Panics
+    ///
+    /// - when the command buffer creation fails.
+    /// - when the command buffer building fails.
+    /// - when the command buffer recording fails.
+    /// - when the command buffer submission fails.
+    #[must_use]
+    pub fn paint_cells(&self, positions: &[[u32; 2]]) -> Box<dyn GpuFuture> {
+        self.run(positions, true)
+    }
+
+    /// Records and submits the pipeline once for every cell.
+    fn run(&self, positions: &[[u32; 2]], paint: bool) -> Box<dyn GpuFuture> {
         let mut builder = AutoCommandBufferBuilder::primary(
             self.compute_queue.device().clone(),
             self.compute_queue.queue_family_index(),
@@ -101,14 +146,21 @@ impl Flipper {
                 0,
                 self.descriptor.clone(),
             )
-            .push_constants(
-                self.pipeline.layout().clone(),
-                0,
-                shader::ty::PushConstants { position },
-            )
-            .bind_pipeline_compute(self.pipeline.clone())
-            .dispatch([1, 1, 1])
-            .expect("Cannot record command buffer");
+            .bind_pipeline_compute(self.pipeline.clone());
+        for &position in positions {
+            builder
+                .push_constants(
+                    self.pipeline.layout().clone(),
+                    0,
+                    shader::ty::PushConstants {
+                        paint: paint.into(),
+                        _dummy0: [0; 4],
+                        position,
+                    },
+                )
+                .dispatch([1, 1, 1])
+                .expect("Cannot record command buffer");
+        }
 
         builder
             .build()
@@ -120,3 +172,60 @@ impl Flipper {
             .boxed()
     }
 }
+
+/// Returns cells of the straight line from `from` to `to`, both ends included,
+/// using Bresenham's algorithm. Consecutive cells touch at least by a corner.
+#[must_use]
+pub fn line_cells(from: [u32; 2], to: [u32; 2]) -> Vec<[u32; 2]> {
+    let [mut x, mut y] = from.map(i64::from);
+    let [end_x, end_y] = to.map(i64::from);
+    let dx = (end_x - x).abs();
+    let dy = -(end_y - y).abs();
+    let step_x = if x < end_x { 1 } else { -1 };
+    let step_y = if y < end_y { 1 } else { -1 };
+    let mut error = dx + dy;
+
+    let mut cells = Vec::new();
+    loop {
+        cells.push(
+            [x, y].map(|coordinate| {
+                u32::try_from(coordinate).expect("Line cells lie between its ends")
+            }),
+        );
+        if x == end_x && y == end_y {
+            return cells;
+        }
+        if 2 * error >= dy {
+            error += dy;
+            x += step_x;
+        }
+        if 2 * error <= dx {
+            error += dx;
+            y += step_y;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn line_cells_include_both_ends() {
+        assert_eq!(line_cells([3, 4], [3, 4]), vec![[3, 4]]);
+        assert_eq!(line_cells([2, 1], [0, 1]), vec![[2, 1], [1, 1], [0, 1]]);
+        assert_eq!(
+            line_cells([0, 3], [3, 0]),
+            vec![[0, 3], [1, 2], [2, 1], [3, 0]]
+        );
+    }
+
+    #[test]
+    fn line_cells_step_along_the_longer_axis() {
+        assert_eq!(
+            line_cells([0, 0], [4, 2]),
+            vec![[0, 0], [1, 0], [2, 1], [3, 1], [4, 2]]
+        );
+        assert_eq!(line_cells([1, 5], [0, 0]).len(), 6);
+    }
+}
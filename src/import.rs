@@ -0,0 +1,68 @@
+use std::path::Path;
+
+use image::{imageops, GrayImage, ImageResult};
+
+/// Converts a grayscale image into values of cells of a board with given dimensions,
+/// row after row. The image is scaled to cover the whole board and cropped
+/// around its centre, pixels darker than `threshold` become alive cells.
+#[must_use]
+#[allow(clippy::cast_possible_truncation)]
+#[allow(clippy::cast_sign_loss)]
+pub fn board_from_image(image: &GrayImage, (width, height): (u32, u32), threshold: u8) -> Vec<u32> {
+    let (image_width, image_height) = image.dimensions();
+    if image_width == 0 || image_height == 0 {
+        return vec![0; width as usize * height as usize];
+    }
+    let scale = (f64::from(width) / f64::from(image_width))
+        .max(f64::from(height) / f64::from(image_height));
+    let scaled_width = ((f64::from(image_width) * scale).ceil() as u32).max(width);
+    let scaled_height = ((f64::from(image_height) * scale).ceil() as u32).max(height);
+    let scaled = imageops::resize(
+        image,
+        scaled_width,
+        scaled_height,
+        imageops::FilterType::Triangle,
+    );
+    let left = (scaled_width - width) / 2;
+    let top = (scaled_height - height) / 2;
+    (0..height)
+        .flat_map(|y| (0..width).map(move |x| (x, y)))
+        .map(|(x, y)| u32::from(scaled.get_pixel(left + x, top + y).0[0] < threshold))
+        .collect()
+}
+
+/// Loads an image file, PNG in particular, and converts it into values of cells
+/// of a board with given dimensions, see [`board_from_image`].
+///
+/// # Errors
+///
+/// - when the file cannot be read or decoded.
+pub fn load_board_image(path: &Path, size: (u32, u32), threshold: u8) -> ImageResult<Vec<u32>> {
+    let image = image::open(path)?.into_luma8();
+    Ok(board_from_image(&image, size, threshold))
+}
+
+#[cfg(test)]
+mod tests {
+    use image::Luma;
+
+    use super::*;
+
+    #[test]
+    fn dark_pixels_become_alive_cells() {
+        let image = GrayImage::from_fn(4, 2, |x, _| Luma([if x < 2 { 0 } else { 255 }]));
+        assert_eq!(
+            board_from_image(&image, (4, 2), 128),
+            [1, 1, 0, 0, 1, 1, 0, 0]
+        );
+    }
+
+    #[test]
+    fn wide_image_is_cropped_around_centre() {
+        // Only the middle two of six columns are dark, a square board keeps them.
+        let image = GrayImage::from_fn(6, 2, |x, _| {
+            Luma([if (2..4).contains(&x) { 0 } else { 255 }])
+        });
+        assert_eq!(board_from_image(&image, (2, 2), 128), [1, 1, 1, 1]);
+    }
+}
@@ -2,55 +2,254 @@
 #![warn(clippy::pedantic)]
 #![warn(clippy::unwrap_used)]
 #![warn(clippy::undocumented_unsafe_blocks)]
+mod autosave;
+mod bench;
+mod board;
+mod bounds;
 mod camera;
 mod config;
 mod controller;
+mod differ;
+mod error;
+mod expander;
+mod export;
+mod filler;
 mod flipper;
+mod import;
+#[cfg(feature = "online")]
+mod online;
+mod pattern;
+mod patterns;
+mod population;
 mod presenter;
 mod randomizer;
+mod readback;
+mod recorder;
+mod rule;
+mod settings;
 mod simulation;
+mod soup;
+mod topology;
+mod weights;
 pub mod vulkan;
 
+pub use autosave::*;
+pub use bench::*;
+pub use board::*;
+pub use bounds::*;
 pub use camera::*;
 pub use config::*;
 pub use controller::*;
+pub use differ::*;
+pub use error::*;
+pub use expander::*;
+pub use export::*;
+pub use filler::*;
 pub use flipper::*;
+pub use import::*;
+#[cfg(feature = "online")]
+pub use online::*;
+pub use pattern::*;
+pub use patterns::*;
+pub use population::*;
 pub use presenter::*;
 pub use randomizer::*;
+pub use readback::*;
+pub use recorder::*;
+pub use rule::*;
+pub use settings::*;
 pub use simulation::*;
+pub use soup::*;
+pub use topology::*;
+pub use weights::*;
 
-use std::time::Instant;
+use std::{
+    io, iter, mem,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use vulkano::{
     buffer::DeviceLocalBuffer,
     command_buffer::{pool::standard::StandardCommandPoolAlloc, PrimaryAutoCommandBuffer},
     memory::pool::{PotentialDedicatedAllocation, StandardMemoryPoolAlloc},
-    sync::GpuFuture,
+    swapchain::AcquireError,
+    sync::{self, FenceSignalFuture, FlushError, GpuFuture},
 };
 use vulkano_util::renderer::VulkanoWindowRenderer;
 use winit::{
     event::{ElementState, Event, MouseButton, WindowEvent},
-    event_loop::{EventLoop, EventLoopBuilder},
+    event_loop::{EventLoop, EventLoopBuilder, EventLoopProxy},
 };
 
 type GpuBuffer = DeviceLocalBuffer<[u32], PotentialDedicatedAllocation<StandardMemoryPoolAlloc>>;
 type CommandBuffer = PrimaryAutoCommandBuffer<StandardCommandPoolAlloc>;
 
+/// Minimum time between refreshes of the window title.
+const TITLE_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Minimum time between reads of the cell under the cursor while the board changes.
+const PROBE_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Time frames are drawn at full rate after the last input, before slowing down
+/// to the idle frame rate, so that the controls can finish their animations.
+const ACTIVE_DURATION: Duration = Duration::from_secs(1);
+
+/// Maximum number of generations computed in a single frame.
+/// When the simulation falls further behind, the remaining time is dropped
+/// instead of piling up and making every following frame slower.
+/// It is high enough to reach [`MAX_SPEED`] at 60 frames per second.
+const MAX_STEPS_PER_FRAME: u32 = 256;
+
+/// Maximum number of generations computed in a single frame at max speed,
+/// where only [`BATCH_LATENCY`] should limit them.
+const UNLIMITED_STEPS_PER_FRAME: u32 = 4096;
+
+/// Longest time generations submitted at once should take on the GPU.
+/// Fewer generations are submitted when a single one takes longer,
+/// so that new generations keep appearing and edits do not wait long for them.
+const BATCH_LATENCY: Duration = Duration::from_millis(50);
+
+/// Longest time the simulation may fall behind with `--fixed-step`,
+/// time beyond it is dropped, so a slow GPU does not make it fall further and further behind.
+const FIXED_STEP_BACKLOG: Duration = Duration::from_secs(1);
+
+/// Number of times a swapchain image is acquired in a single frame,
+/// the swapchain is recreated before every attempt after the first one.
+const ACQUIRE_ATTEMPTS: u32 = 3;
+
+/// Highest speed of the simulation in generations per second that can be selected.
+pub(crate) const MAX_SPEED: u32 = 10_000;
+
 #[derive(Debug)]
 pub enum Message {
-    Randomize,
+    Randomize(Symmetry, Distribution, bool),
     Clear,
+    Mirror(Mirror),
+    Rotate(Rotation),
+    Shift(i32, i32, bool),
+    StepBack,
+    SetVsync(bool),
+    SetBackground([u8; 3]),
+    SetPalette(Palette),
+    SetCellAspect(CellAspect),
+    SetNativeView(bool),
+    SetPanInertia(f32),
+    SetCellPadding(f32),
+    SetInverted(bool),
+    SetStateThreshold(u32),
+    SetCellShading(CellShading),
+    SetBorder(u32, [u8; 3]),
+    SetRule(Rule),
+    SetComparison(Option<Rule>),
+    SetNeighbourColors(bool),
+    SetNeighbourhood(Neighbourhood),
+    SetTopology(Topology),
+    ToggleRecording,
+    Stabilized(u32),
+    FrameLiveCells,
+    CenterOnLiveCells,
+    Stamp(usize, Option<[u32; 2]>),
+    CompareStates(Option<[PathBuf; 2]>),
+    JumpTo(Option<u64>),
+    ExportPopulation,
+    #[cfg(feature = "online")]
+    FetchPattern(String),
+    #[cfg(feature = "online")]
+    PatternFetched(String, Result<Pattern, String>),
+}
+
+/// State of the simulation passed to the hook registered with [`GameOfLife::on_frame`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FrameInfo {
+    pub generation: u64,
+    /// Number of alive cells of the last board read back, `None` before the first readback.
+    /// It may lag a few frames behind the generation.
+    pub population: Option<u64>,
+    /// Number of frames drawn within the last second.
+    pub fps: u32,
+}
+
+/// Generations submitted to the GPU at once, the host does not wait for them.
+struct Batch {
+    fence: FenceSignalFuture<Box<dyn GpuFuture>>,
+    submitted: Instant,
+    steps: u32,
 }
 
 /// This struct represents the game of life.
 /// It contains the event loop, renderer, simulation, controller and the presenter.
 pub struct GameOfLife {
-    event_loop: EventLoop<Message>,
+    event_loop: Option<EventLoop<Message>>,
+    proxy: EventLoopProxy<Message>,
     renderer: VulkanoWindowRenderer,
     simulation: Simulation,
+    /// Board computed with another rule, drawn next to the main one.
+    comparison: Option<Simulation>,
     presenter: Presenter,
     controller: Controller,
     flipper: Flipper,
+    filler: Filler,
+    probe: CellProbe,
+    readback: AsyncReadback,
+    readback_interval: Duration,
+    /// Time a batch may take before the device is considered lost, given by `--gpu-timeout`.
+    gpu_timeout: Duration,
+    /// Whether a batch did not finish in time, nothing is submitted or waited for anymore.
+    stalled: bool,
+    /// Fraction of device memory boards may take, given by `--max-memory-fraction`.
+    memory_fraction: f64,
+    /// Time between generations given by `--fixed-step`, it replaces the selected speed.
+    fixed_step: Option<Duration>,
+    readback_due: bool,
+    read_at: Instant,
+    population: PopulationHistory,
+    population_path: PathBuf,
+    /// Generation a jump started at and the generation it goes to.
+    jump: Option<(u64, u64)>,
+    #[cfg(feature = "online")]
+    pattern_url: Option<String>,
+    recorder: Option<Recorder>,
+    record_config: RecordConfig,
+    exporter: Option<Exporter>,
+    expander: Option<Expander>,
+    expand_config: ExpandConfig,
+    settings_path: Option<PathBuf>,
+    timer: Instant,
+    /// When the last frame was drawn, frames are limited by [`Controller::max_fps`].
+    drawn: Instant,
+    accumulator: Duration,
+    minimized: bool,
+    focused: bool,
+    pause_unfocused: bool,
+    last_input: Instant,
+    idle_interval: Duration,
+    probed: (Option<[u32; 2]>, Instant),
+    board_changed: bool,
+    /// Generations being computed, the board must not be accessed until they finish.
+    batch: Option<Batch>,
+    /// Whether a batch finished and its generations were not captured and checked yet.
+    stepped: bool,
+    /// Whether the boards changed since the presenter took their snapshot.
+    snapshot_due: bool,
+    /// Time a single generation took in the last batch.
+    step_latency: Option<Duration>,
+    drawing: bool,
+    painted: Option<[u32; 2]>,
+    /// Corners of the rectangle being dragged out with the rectangle tool.
+    fill_drag: Option<[[u32; 2]; 2]>,
+    stable: bool,
+    /// File the board is saved to on exit, `None` when it is not saved.
+    autosave_path: Option<PathBuf>,
+    /// Format of the window title refreshed while running, `None` when the title is fixed.
+    title_format: Option<String>,
+    /// Title given on the command line or the application name.
+    base_title: String,
+    /// Last title set on the window and when it was refreshed.
+    titled: (String, Instant),
+    /// Called after every drawn frame, see [`GameOfLife::on_frame`].
+    frame_hook: Option<Box<dyn FnMut(FrameInfo)>>,
 }
 
 impl GameOfLife {
@@ -62,128 +261,1653 @@ impl GameOfLife {
     ///
     /// # Panics
     ///
-    /// - when the renderer fails to initialize.
-    /// - when the simulation fails to initialize.
-    /// - when the presenter fails to initialize.
-    /// - when vulkan fails to create any of structures.
+    /// - when [`GameOfLife::try_new`] fails.
     #[must_use]
     pub fn new(config: &Config) -> Self {
-        let context = vulkan::vulkano_context();
+        Self::try_new(config).expect("Cannot create game of life")
+    }
+
+    /// Creates a new [`GameOfLife`] instance.
+    ///
+    /// # Arguments
+    ///
+    /// - `config` - The configuration for the simulation.
+    ///
+    /// # Errors
+    ///
+    /// - when the window creation fails.
+    /// - when the device does not support the work group size.
+    /// - when vulkan fails to create any of structures.
+    ///
+    /// # Panics
+    ///
+    /// - when the vulkan context or the renderer fails to initialize,
+    ///   [`vulkano_util`] does not report these failures as errors.
+    pub fn try_new(config: &Config) -> Result<Self, Error> {
         let event_loop = EventLoopBuilder::<Message>::with_user_event().build();
-        let renderer = vulkan::vulkano_renderer(&context, &event_loop);
-        let controller = Controller::new(&renderer, &event_loop);
-        let buffer = vulkan::create_gpu_buffer(context.device(), config.size(), true);
-        let simulation = Simulation::new(renderer.compute_queue(), buffer.clone(), config.size());
-        let presenter = Presenter::new(&renderer, buffer.clone(), config.size());
-        let flipper = Flipper::new(renderer.compute_queue(), buffer, config.size());
-
-        Self {
+        let mut game = Self::try_with_event_loop(&event_loop, config)?;
+        game.event_loop = Some(event_loop);
+        Ok(game)
+    }
+
+    /// Creates a new [`GameOfLife`] instance with a window of given event loop.
+    /// The event loop stays owned by the caller, which has to pass its events to
+    /// [`GameOfLife::window_event`] and [`GameOfLife::message`]
+    /// and call [`GameOfLife::tick`] once per frame.
+    /// [`GameOfLife::next_frame`] tells when the next frame is due while nothing changes.
+    ///
+    /// # Arguments
+    ///
+    /// - `event_loop` - The event loop the window is created with.
+    /// - `config` - The configuration for the simulation.
+    ///
+    /// # Panics
+    ///
+    /// - when [`GameOfLife::try_with_event_loop`] fails.
+    #[must_use]
+    pub fn with_event_loop(event_loop: &EventLoop<Message>, config: &Config) -> Self {
+        Self::try_with_event_loop(event_loop, config).expect("Cannot create game of life")
+    }
+
+    /// Creates a new [`GameOfLife`] instance with a window of given event loop,
+    /// see [`GameOfLife::with_event_loop`].
+    ///
+    /// # Errors
+    ///
+    /// - when the window creation fails.
+    /// - when the device does not support the work group size.
+    /// - when the device does not support the number of samples.
+    /// - when vulkan fails to create any of structures.
+    ///
+    /// # Panics
+    ///
+    /// - when the vulkan context or the renderer fails to initialize,
+    ///   [`vulkano_util`] does not report these failures as errors.
+    pub fn try_with_event_loop(
+        event_loop: &EventLoop<Message>,
+        config: &Config,
+    ) -> Result<Self, Error> {
+        let context = vulkan::vulkano_context(config.validation());
+        let workgroup = config.workgroup();
+        vulkan::try_validate_workgroup(context.device(), workgroup)?;
+        vulkan::try_validate_samples(context.device(), config.samples())?;
+        let layout = BoardLayout::new(config.size(), config.cell_bits());
+        vulkan::try_validate_device(
+            context.compute_queue(),
+            layout,
+            presenter::PUSH_CONSTANTS_SIZE,
+        )?;
+        let settings_path = config.settings().then(Settings::path).flatten();
+        let settings = settings_path
+            .as_deref()
+            .map_or_else(Settings::default, Settings::load)
+            .with_overrides(config);
+        let boards = if config.compare_rule().is_some() {
+            2
+        } else {
+            1
+        };
+        vulkan::try_validate_memory(
+            context.device(),
+            board_memory(layout, config.history_depth(), settings.neighbour_colors)
+                .saturating_mul(boards),
+            config.max_memory_fraction(),
+        )?;
+        let mut renderer = vulkan::try_vulkano_renderer(
+            &context,
             event_loop,
+            config.title(),
+            config.window_size(),
+        )?;
+        renderer.set_present_mode(vulkan::present_mode(&renderer, settings.vsync));
+        let mut controller = Controller::new(&renderer, event_loop, config, &settings);
+        let buffer = vulkan::try_create_gpu_buffer(context.device(), layout, true)?;
+        let mut simulation = Simulation::try_new(
+            renderer.compute_queue(),
+            buffer.clone(),
+            layout,
+            config.rule(),
+            config.neighbourhood(),
+            config.topology(),
+            workgroup,
+        )?;
+        simulation.set_history_depth(config.history_depth());
+        simulation.set_neighbour_counts(settings.neighbour_colors);
+        simulation.set_tiled(config.tiled());
+        simulation.set_weights(config.weights());
+        if let Some(path) = config.pattern() {
+            match Pattern::load(path) {
+                Ok(pattern) => simulation.load_pattern(&pattern),
+                Err(error) => log::error!("Cannot load pattern {}: {error}", path.display()),
+            }
+        }
+        if let Some(path) = config.image() {
+            match load_board_image(path, layout.size(), config.image_threshold()) {
+                Ok(cells) => simulation.write_cells(&cells),
+                Err(error) => log::error!("Cannot load image {}: {error}", path.display()),
+            }
+        }
+        if config.resume() {
+            resume(&mut simulation, config.autosave_path().as_deref());
+        }
+        if let Some(soup_size) = config.soup_size() {
+            let seed = config.seed().unwrap_or_else(rand::random);
+            log::info!("Seeding a {soup_size}x{soup_size} soup with seed {seed}");
+            simulation.write_cells(&soup_cells(
+                layout.size(),
+                soup_size,
+                config.density(),
+                seed,
+            ));
+        }
+        let mut presenter =
+            Presenter::try_new(&renderer, buffer.clone(), layout, config.samples())?;
+        let comparison = match config.compare_rule() {
+            Some(rule) => {
+                let (comparison, buffer) = simulation.try_fork(rule, Some(config.gpu_timeout()))?;
+                presenter.set_comparison(Some(buffer), comparison.neighbour_counts());
+                Some(comparison)
+            }
+            None => None,
+        };
+        presenter.set_neighbour_counts(simulation.neighbour_counts());
+        presenter.camera_mut().set_bindings(settings.mouse_bindings);
+        presenter.camera_mut().set_aspect(settings.cell_aspect);
+        presenter.set_cell_padding(settings.cell_padding);
+        presenter.set_inverted(settings.inverted);
+        presenter.set_state_threshold(settings.state_threshold);
+        presenter.set_shading(settings.cell_shading);
+        if settings.border {
+            presenter.set_border(settings.border_width, settings.border_color);
+        }
+        presenter.camera_mut().apply_settings(settings.camera);
+        presenter.camera_mut().set_native_mode(settings.native_view);
+        presenter.camera_mut().set_inertia(settings.pan_inertia);
+        presenter.set_palette(settings.palette.colors());
+        presenter.set_background(settings.background);
+        let flipper = Flipper::try_new(renderer.compute_queue(), buffer.clone(), layout)?;
+        let filler = Filler::try_new(renderer.compute_queue(), buffer.clone(), layout, workgroup)?;
+        let probe = CellProbe::try_new(renderer.compute_queue(), buffer.clone(), layout)?;
+        let readback =
+            AsyncReadback::try_new(vulkan::transfer_queue(&renderer), buffer.clone(), layout)?;
+        let expand_config = config.expand().clone();
+        let expander = if expand_config.enabled() {
+            Some(Expander::try_new(
+                renderer.compute_queue(),
+                buffer,
+                layout,
+                workgroup,
+                &expand_config,
+            )?)
+        } else {
+            None
+        };
+        let record_config = config.record().clone();
+        let recorder = if record_config.enabled() {
+            start_recording(&simulation, &record_config)
+        } else {
+            None
+        };
+        controller.set_recording(recorder.is_some());
+        let exporter = config.export().directory().and_then(|directory| {
+            Exporter::new(simulation.readback(), directory, config.export())
+                .map_err(|error| log::error!("Cannot start export: {error}"))
+                .ok()
+        });
+
+        Ok(Self {
+            event_loop: None,
+            proxy: event_loop.create_proxy(),
             renderer,
             simulation,
+            comparison,
             presenter,
             controller,
             flipper,
-        }
+            filler,
+            probe,
+            readback,
+            readback_interval: config.readback_interval(),
+            gpu_timeout: config.gpu_timeout(),
+            stalled: false,
+            memory_fraction: config.max_memory_fraction(),
+            fixed_step: config.fixed_step(),
+            readback_due: true,
+            read_at: Instant::now(),
+            population: PopulationHistory::new(config.population_interval()),
+            population_path: config.population_path().to_owned(),
+            jump: None,
+            #[cfg(feature = "online")]
+            pattern_url: config.pattern_url().map(str::to_owned),
+            recorder,
+            record_config,
+            exporter,
+            expander,
+            expand_config,
+            settings_path,
+            timer: Instant::now(),
+            drawn: Instant::now(),
+            accumulator: Duration::ZERO,
+            minimized: false,
+            focused: true,
+            pause_unfocused: config.pause_unfocused(),
+            last_input: Instant::now(),
+            idle_interval: Duration::from_secs(1) / config.idle_fps(),
+            probed: (None, Instant::now()),
+            board_changed: true,
+            batch: None,
+            stepped: false,
+            snapshot_due: true,
+            step_latency: None,
+            drawing: false,
+            painted: None,
+            fill_drag: None,
+            stable: false,
+            autosave_path: config
+                .autosave_on_exit()
+                .then(|| config.autosave_path())
+                .flatten(),
+            title_format: config.title_format().map(str::to_owned),
+            base_title: config.title().unwrap_or(env!("CARGO_PKG_NAME")).to_owned(),
+            titled: (String::new(), Instant::now()),
+            frame_hook: None,
+        })
+    }
+
+    /// Registers a closure called after every drawn frame with the current generation,
+    /// population and frame rate, replacing the previous one.
+    #[must_use]
+    pub fn on_frame(mut self, hook: impl FnMut(FrameInfo) + 'static) -> Self {
+        self.frame_hook = Some(Box::new(hook));
+        self
     }
 
     /// Runs the Conway's Game of Life simulation.
+    /// Takes over the event loop and never returns, see [`GameOfLife::with_event_loop`]
+    /// for running the simulation from an event loop owned by the caller.
+    /// Settings are saved when the window is closed.
+    /// The program exits with code 1 once the device [`GameOfLife::stalled`].
     ///
     /// # Panics
     ///
+    /// - when the instance was not created with [`GameOfLife::new`].
     /// - when vulkan fails to create any of structures.
     /// - when vulkan fails to execute any of commands.
     /// - when vulkan fails to wait for any of commands.
     /// - when vulkan fails to present any of frames.
     /// - when vulkan fails to acquire any of frames.
     pub fn run(mut self) -> ! {
-        let mut timer = Instant::now();
-        let mut minimized = false;
+        let event_loop = self
+            .event_loop
+            .take()
+            .expect("Cannot run without own event loop");
 
-        self.event_loop.run(move |event, _, flow| match event {
-            Event::WindowEvent {
-                event: WindowEvent::CloseRequested,
-                ..
-            } => flow.set_exit(),
-            Event::WindowEvent { event, .. } => {
-                if self.controller.update(&event) {
-                    return;
+        event_loop.run(move |event, _, flow| {
+            match event {
+                Event::WindowEvent {
+                    event: WindowEvent::CloseRequested,
+                    ..
+                } => {
+                    self.save_board();
+                    self.save_settings();
+                    flow.set_exit();
                 }
-                self.presenter.update(&event);
-                if let WindowEvent::Resized(size) = event {
-                    if size.height == 0 || size.width == 0 {
+                Event::WindowEvent { event, .. } => self.window_event(&event),
+                Event::UserEvent(message) => self.message(message),
+                Event::MainEventsCleared => {
+                    self.tick();
+                    if self.idle() {
                         flow.set_wait();
-                        minimized = true;
+                    } else if let Some(next_frame) = self.next_frame() {
+                        flow.set_wait_until(next_frame);
                     } else {
                         flow.set_poll();
-                        minimized = false;
                     }
                 }
-                if let WindowEvent::MouseInput { state, button, .. } = event {
-                    if MouseButton::Right == button && state == ElementState::Pressed {
-                        self.flipper
-                            .flip(self.presenter.camera().cursor_game_position())
-                            .then_signal_fence_and_flush()
-                            .expect("Failed to flip")
-                            .wait(None)
-                            .expect("Failed to wait for flip");
+                _ => (),
+            }
+            if self.stalled {
+                self.save_settings();
+                flow.set_exit_with_code(1);
+            }
+        });
+    }
+
+    /// Returns current settings of the controls and the camera.
+    #[must_use]
+    pub fn settings(&self) -> Settings {
+        let camera = self.presenter.camera();
+        let mut settings = Settings {
+            mouse_bindings: camera.bindings(),
+            camera: camera.settings(),
+            ..Settings::default()
+        };
+        self.controller.store_settings(&mut settings);
+        settings
+    }
+
+    /// Saves current settings so they are restored on the next run,
+    /// nothing is saved when started with `--no-settings`.
+    /// An event loop owned by the caller should call it before exiting.
+    pub fn save_settings(&self) {
+        if let Some(path) = &self.settings_path {
+            if let Err(error) = self.settings().save(path) {
+                log::error!("Cannot save settings {}: {error}", path.display());
+            }
+        }
+    }
+
+    /// Saves the board to be restored by `--resume`, when `--autosave-on-exit` was given.
+    /// Generations being computed are finished first, so the saved board is their result.
+    /// Nothing is saved once the device [`GameOfLife::stalled`].
+    ///
+    /// # Panics
+    ///
+    /// - when vulkan fails to wait for the generations.
+    pub fn save_board(&mut self) {
+        let path = match &self.autosave_path {
+            Some(path) => path.clone(),
+            None => return,
+        };
+        self.finish_batch();
+        if self.stalled {
+            return;
+        }
+        let result = self.simulation.try_read_cells(Some(self.gpu_timeout));
+        let cells = match self.finished(result, "Readback of the saved board") {
+            Some(cells) => cells,
+            None => return,
+        };
+        let board = SavedBoard {
+            size: self.simulation.layout().size(),
+            generation: self.simulation.generation(),
+            cells,
+        };
+        match board.save(&path) {
+            Ok(()) => log::info!(
+                "Saved generation {} of the board to {}",
+                board.generation,
+                path.display()
+            ),
+            Err(error) => log::error!("Cannot save board {}: {error}", path.display()),
+        }
+    }
+
+    /// Returns whether generations did not finish within `--gpu-timeout`, most likely because
+    /// the device was lost. Nothing is computed or drawn anymore and the program should exit,
+    /// an event loop owned by the caller should check it after every event.
+    #[inline]
+    #[must_use]
+    pub fn stalled(&self) -> bool {
+        self.stalled
+    }
+
+    /// Returns whether the window is minimized, no frames are drawn until it is restored.
+    #[inline]
+    #[must_use]
+    pub fn minimized(&self) -> bool {
+        self.minimized
+    }
+
+    /// Returns whether the simulation is suspended, because the window is minimized
+    /// or it lost focus and `--run-unfocused` was not given.
+    /// No generations are computed and no frames are drawn until it is resumed.
+    #[inline]
+    #[must_use]
+    pub fn idle(&self) -> bool {
+        self.minimized || (self.pause_unfocused && !self.focused)
+    }
+
+    /// Returns whether cells are being painted or a rectangle is being dragged out,
+    /// generations are not computed until the button is released,
+    /// without pausing the simulation in the controls.
+    #[inline]
+    #[must_use]
+    pub fn editing(&self) -> bool {
+        self.drawing || self.fill_drag.is_some()
+    }
+
+    /// Returns when the next frame should be drawn while nothing changes
+    /// or the frame rate is limited, `None` when frames should be drawn at full rate,
+    /// because the simulation runs, the camera moves or input was received recently.
+    #[must_use]
+    pub fn next_frame(&self) -> Option<Instant> {
+        let active = !self.controller.pause()
+            || self.jump.is_some()
+            || self.presenter.camera().moving()
+            || self.presenter.camera().gliding()
+            || self.last_input.elapsed() < ACTIVE_DURATION;
+        let limited = self
+            .controller
+            .max_fps()
+            .map(|fps| self.drawn + Duration::from_secs(1) / fps);
+        if active {
+            limited
+        } else {
+            let idle = self.timer + self.idle_interval;
+            Some(limited.map_or(idle, |limited| limited.max(idle)))
+        }
+    }
+
+    /// Handles an event of the window created by the simulation.
+    /// The board is not edited anymore once the device [`GameOfLife::stalled`].
+    ///
+    /// # Panics
+    ///
+    /// - when vulkan fails to flip or paint a cell, draw a line or fill a rectangle.
+    pub fn window_event(&mut self, event: &WindowEvent) {
+        self.last_input = Instant::now();
+        if let WindowEvent::Focused(focused) = *event {
+            log::debug!("Window focused: {focused}");
+            if focused && !self.focused {
+                self.timer = Instant::now();
+            }
+            self.focused = focused;
+        }
+        if self.controller.update(event) || self.fill_event(event) {
+            return;
+        }
+        self.presenter.update(event);
+        let resized = match event {
+            WindowEvent::Resized(size) => Some(*size),
+            WindowEvent::ScaleFactorChanged {
+                scale_factor,
+                new_inner_size,
+            } => {
+                log::debug!("Window scale factor changed to {scale_factor}");
+                Some(**new_inner_size)
+            }
+            _ => None,
+        };
+        if let Some(size) = resized {
+            log::debug!("Window resized to {}x{}", size.width, size.height);
+            self.minimized = size.height == 0 || size.width == 0;
+            // The swapchain is recreated by the next acquire, before a frame of the new size.
+            self.renderer.resize();
+        }
+        let camera = self.presenter.camera();
+        let position = camera.cursor_game_position();
+        if let (Some(drag), Some(position)) = (&mut self.fill_drag, position) {
+            drag[1] = position;
+        }
+        if let WindowEvent::MouseInput { state, button, .. } = *event {
+            let bindings = camera.bindings();
+            let tool = self.controller.tool();
+            match (state, position) {
+                (ElementState::Released, Some(position))
+                    if button == MouseButton::Left
+                        && tool == DrawTool::Line
+                        && !camera.dragged() =>
+                {
+                    if let Some(anchor) = self.controller.line_click(position) {
+                        self.finish_batch();
+                        if self.stalled {
+                            return;
+                        }
+                        let future = self.flipper.paint_cells(&line_cells(anchor, position));
+                        if self.wait_for(future, "Line") {
+                            self.edited();
+                        }
+                    }
+                }
+                (ElementState::Pressed, Some(position)) if bindings.flips(button) => {
+                    self.finish_batch();
+                    if self.stalled {
+                        return;
                     }
+                    let future = self.flipper.flip(position);
+                    if self.wait_for(future, "Flip") {
+                        self.edited();
+                    }
+                }
+                (ElementState::Pressed, _) if bindings.draws(button) && tool == DrawTool::Cell => {
+                    self.drawing = !camera.moving();
+                }
+                (ElementState::Released, _) if bindings.draws(button) => {
+                    self.drawing = false;
+                    self.painted = None;
                 }
+                _ => (),
             }
-            Event::UserEvent(Message::Randomize) => {
-                self.simulation
-                    .randomize()
-                    .then_signal_fence_and_flush()
-                    .expect("failed to execute command buffer")
-                    .wait(None)
-                    .expect("failed to wait for command buffer");
-            }
-            Event::UserEvent(Message::Clear) => {
-                self.simulation
-                    .clear()
-                    .then_signal_fence_and_flush()
-                    .expect("failed to execute command buffer")
-                    .wait(None)
-                    .expect("failed to wait for command buffer");
-            }
-            Event::MainEventsCleared => {
-                if minimized {
+        }
+        if let (true, Some(position)) = (self.drawing, position) {
+            if self.painted != Some(position) {
+                self.finish_batch();
+                if self.stalled {
                     return;
                 }
-                let mut future = match self.renderer.acquire() {
-                    Ok(future) => future,
-                    Err(_) => return,
-                };
+                let future = self.flipper.paint(position);
+                self.painted = Some(position);
+                if self.wait_for(future, "Paint") {
+                    self.edited();
+                }
+            }
+        }
+    }
 
-                let now = Instant::now();
-                self.controller.fps_counter.push_back(now);
+    /// Handles dragging out a rectangle with the rectangle tool,
+    /// which is filled when the left button is released.
+    /// Returns whether the event was consumed, so that the camera does not move.
+    fn fill_event(&mut self, event: &WindowEvent) -> bool {
+        if self.controller.tool() != DrawTool::Rectangle {
+            self.fill_drag = None;
+            return false;
+        }
+        match *event {
+            WindowEvent::MouseInput {
+                state: ElementState::Pressed,
+                button: MouseButton::Left,
+                ..
+            } => {
+                self.fill_drag = self
+                    .presenter
+                    .camera()
+                    .cursor_game_position()
+                    .map(|position| [position; 2]);
+                self.fill_drag.is_some()
+            }
+            WindowEvent::MouseInput {
+                state: ElementState::Released,
+                button: MouseButton::Left,
+                ..
+            } => match self.fill_drag.take() {
+                Some(corners) => {
+                    self.finish_batch();
+                    if self.stalled {
+                        return true;
+                    }
+                    let (mode, density) = self.controller.fill();
+                    let future = self.filler.run(corners, mode, density);
+                    if self.wait_for(future, "Fill") {
+                        self.edited();
+                    }
+                    true
+                }
+                None => false,
+            },
+            _ => false,
+        }
+    }
 
-                while let Some(x) = self.controller.fps_counter.pop_front() {
-                    if (now - x).as_millis() < 1000 {
-                        self.controller.fps_counter.push_front(x);
-                        break;
+    /// Handles a [`Message`] sent by the controls.
+    /// Generations being computed are waited for first, edits of the board
+    /// wait at most `--gpu-timeout` like them, see [`GameOfLife::wait_for`].
+    ///
+    /// # Panics
+    ///
+    /// - when vulkan fails to execute any of commands.
+    /// - when vulkan fails to wait for any of commands.
+    pub fn message(&mut self, message: Message) {
+        self.finish_batch();
+        if self.stalled {
+            return;
+        }
+        match message {
+            Message::Randomize(symmetry, distribution, keep_cells) => {
+                log::debug!(
+                    "Randomizing the board with {symmetry:?} symmetry and {distribution:?} \
+                     distribution, keeping cells: {keep_cells}"
+                );
+                let future = self
+                    .simulation
+                    .randomize(symmetry, distribution, keep_cells);
+                if self.wait_for(future, "Randomizing") {
+                    self.edited();
+                }
+            }
+            Message::Clear => {
+                log::debug!("Clearing the board");
+                let future = self.simulation.clear();
+                if self.wait_for(future, "Clearing") {
+                    self.edited();
+                }
+            }
+            Message::Mirror(mirror) => {
+                let future = self.simulation.mirror(mirror);
+                if self.wait_for(future, "Mirroring") {
+                    self.edited();
+                }
+            }
+            Message::Rotate(rotation) => {
+                let future = self.simulation.rotate(rotation);
+                self.controller.set_rotation_rejected(future.is_none());
+                if let Some(future) = future {
+                    if self.wait_for(future, "Rotation") {
+                        self.edited();
+                    }
+                }
+            }
+            Message::Shift(dx, dy, wrap) => {
+                let future = self.simulation.shift(dx, dy, wrap);
+                if self.wait_for(future, "Shift") {
+                    self.edited();
+                }
+            }
+            Message::StepBack => {
+                let futures: Vec<_> = iter::once(&mut self.simulation)
+                    .chain(&mut self.comparison)
+                    .map(Simulation::step_back)
+                    .collect();
+                for future in futures {
+                    if !self.wait_for(future, "Step back") {
+                        return;
                     }
                 }
+                self.board_changed = true;
+                self.snapshot_due = true;
+                self.clear_difference();
+            }
+            Message::SetVsync(vsync) => {
+                let present_mode = vulkan::present_mode(&self.renderer, vsync);
+                self.renderer.set_present_mode(present_mode);
+                self.presenter.clear_framebuffers();
+            }
+            Message::SetBackground(background) => self.presenter.set_background(background),
+            Message::SetPalette(palette) => self.presenter.set_palette(palette.colors()),
+            Message::SetCellAspect(aspect) => self.presenter.camera_mut().set_aspect(aspect),
+            Message::SetNativeView(native) => self.presenter.camera_mut().set_native_mode(native),
+            Message::SetPanInertia(inertia) => self.presenter.camera_mut().set_inertia(inertia),
+            Message::SetCellPadding(padding) => self.presenter.set_cell_padding(padding),
+            Message::SetInverted(inverted) => self.presenter.set_inverted(inverted),
+            Message::SetStateThreshold(threshold) => self.presenter.set_state_threshold(threshold),
+            Message::SetCellShading(shading) => self.presenter.set_shading(shading),
+            Message::SetBorder(width, color) => self.presenter.set_border(width, color),
+            Message::SetRule(rule) => self.simulation.set_rule(rule),
+            Message::SetComparison(rule) => self.set_comparison(rule),
+            Message::SetNeighbourColors(enabled) => self.set_neighbour_colors(enabled),
+            Message::SetNeighbourhood(neighbourhood) => {
+                for simulation in iter::once(&mut self.simulation).chain(&mut self.comparison) {
+                    simulation.set_neighbourhood(neighbourhood);
+                }
+            }
+            Message::SetTopology(topology) => {
+                for simulation in iter::once(&mut self.simulation).chain(&mut self.comparison) {
+                    simulation.set_topology(topology);
+                }
+            }
+            Message::Stabilized(period) => self.controller.stabilized(period),
+            Message::Stamp(index, position) => {
+                let pattern = Pattern::builtin(index);
+                let (width, height) = self.simulation.layout().size();
+                let position = position.unwrap_or([width / 2, height / 2]);
+                let result = self
+                    .simulation
+                    .try_stamp(&pattern, position, Some(self.gpu_timeout));
+                if self.finished(result, "Stamp of a pattern").is_some() {
+                    self.edited();
+                }
+            }
+            Message::ExportPopulation => {
+                let status = match self.population.save_csv(&self.population_path) {
+                    Ok(()) => format!(
+                        "Exported {} samples to {}",
+                        self.population.samples().len(),
+                        self.population_path.display()
+                    ),
+                    Err(error) => {
+                        log::error!(
+                            "Cannot export population {}: {error}",
+                            self.population_path.display()
+                        );
+                        format!("Cannot export population: {error}")
+                    }
+                };
+                self.controller.set_population_status(status);
+            }
+            Message::FrameLiveCells => {
+                if let Some(bounds) = self.live_bounds() {
+                    self.presenter.camera_mut().fit(bounds);
+                }
+            }
+            Message::CenterOnLiveCells => {
+                if let Some(bounds) = self.live_bounds() {
+                    self.presenter.camera_mut().center_on(bounds);
+                }
+            }
+            Message::ToggleRecording => {
+                if self.recorder.take().is_none() {
+                    self.recorder = start_recording(&self.simulation, &self.record_config);
+                }
+                self.controller.set_recording(self.recorder.is_some());
+            }
+            Message::CompareStates(paths) => self.compare_states(paths),
+            Message::JumpTo(target) => {
+                let generation = self.simulation.generation();
+                self.jump = target
+                    .filter(|&target| target > generation)
+                    .map(|target| (generation, target));
+                if let Some((_, target)) = self.jump {
+                    log::info!("Jumping from generation {generation} to {target}");
+                }
+                self.update_jump();
+            }
+            #[cfg(feature = "online")]
+            Message::FetchPattern(name) => self.fetch_pattern(name),
+            #[cfg(feature = "online")]
+            Message::PatternFetched(name, result) => match result {
+                Ok(pattern) => {
+                    let (width, height) = self.simulation.layout().size();
+                    let result = self.simulation.try_stamp(
+                        &pattern,
+                        [width / 2, height / 2],
+                        Some(self.gpu_timeout),
+                    );
+                    if self.finished(result, "Stamp of a pattern").is_some() {
+                        self.edited();
+                        self.controller.set_fetch_status(format!("Placed {name}"));
+                    }
+                }
+                Err(error) => {
+                    log::error!("Cannot fetch pattern {name}: {error}");
+                    self.controller
+                        .set_fetch_status(format!("Cannot fetch {name}: {error}"));
+                }
+            },
+        }
+    }
+
+    /// Submits generations due since the last frame and draws the next frame.
+    /// Does nothing while the simulation is [`GameOfLife::idle`]
+    /// or the frame rate is limited and the next frame is not due yet.
+    ///
+    /// Generations are computed without the host waiting for them, until they finish
+    /// the last snapshot of the board is drawn and no new generations are submitted.
+    ///
+    /// # Panics
+    ///
+    /// - when vulkan fails to create any of structures.
+    /// - when vulkan fails to execute any of commands.
+    /// - when vulkan fails to wait for any of commands.
+    /// - when vulkan fails to present the frame.
+    /// - when the surface or the device is lost.
+    pub fn tick(&mut self) {
+        if self.idle() || self.stalled {
+            return;
+        }
+        if let Some(fps) = self.controller.max_fps() {
+            if self.drawn.elapsed() < Duration::from_secs(1) / fps {
+                return;
+            }
+        }
+        let mut future = match self.acquire() {
+            Some(future) => future,
+            None => return,
+        };
 
-                let duration = (now - timer).as_millis();
-                if !self.controller.pause() && duration > 1000 / self.controller.speed() {
-                    timer = now;
-                    future = self.simulation.step(future);
+        let now = Instant::now();
+        self.presenter.camera_mut().glide(now - self.drawn);
+        self.drawn = now;
+        self.controller.fps_counter.push_back(now);
+
+        while let Some(x) = self.controller.fps_counter.pop_front() {
+            if (now - x).as_millis() < 1000 {
+                self.controller.fps_counter.push_front(x);
+                break;
+            }
+        }
+
+        self.advance(now);
+        self.refresh_title(now);
+
+        let x = self.presenter.draw(
+            &self.renderer,
+            self.controller.grid(),
+            self.simulation.rule().states(),
+        );
+
+        future = future
+            .then_execute(self.renderer.graphics_queue(), x)
+            .expect("failed to execute command buffer")
+            .boxed();
+
+        self.controller
+            .set_cell_mapping(self.presenter.camera().cell_mapping());
+        self.controller.set_fill_preview(self.fill_drag);
+        self.controller
+            .set_hovered_cell(self.presenter.camera().cursor_game_position());
+        future = self
+            .controller
+            .draw(future, self.renderer.swapchain_image_view());
+
+        self.renderer.present(future, true);
+
+        if let Some(hook) = &mut self.frame_hook {
+            hook(FrameInfo {
+                generation: self.simulation.generation(),
+                population: self.controller.population(),
+                fps: u32::try_from(self.controller.fps_counter.len()).unwrap_or(u32::MAX),
+            });
+        }
+    }
+
+    /// Finishes the batch of generations when the GPU is done with it,
+    /// handles its generations and submits generations due since then.
+    /// When the boards changed, a snapshot of them is taken for the presenter
+    /// before the submission. Nothing happens while the batch is running.
+    ///
+    /// # Panics
+    ///
+    /// - when vulkan fails to execute or wait for any of commands.
+    fn advance(&mut self, now: Instant) {
+        if !self.poll_batch() {
+            return;
+        }
+        if self.stepped {
+            self.capture();
+            self.check_stability();
+        }
+        self.refresh_probes(now);
+        if self.stepped {
+            self.expand();
+            self.stepped = false;
+        }
+        if self.snapshot_due {
+            let future = self.presenter.snapshot(&self.renderer);
+            if !self.wait_for(future, "Snapshot") {
+                return;
+            }
+            self.snapshot_due = false;
+        }
+        self.submit_batch(now);
+    }
+
+    /// Acquires the next swapchain image, the swapchain is recreated and the image
+    /// acquired again while it is out of date, at most [`ACQUIRE_ATTEMPTS`] times.
+    /// Returns `None` when the frame has to be skipped.
+    ///
+    /// # Panics
+    ///
+    /// - when the surface or the device is lost.
+    fn acquire(&mut self) -> Option<Box<dyn GpuFuture>> {
+        for _ in 0..ACQUIRE_ATTEMPTS {
+            match self.renderer.acquire() {
+                Ok(future) => return Some(future),
+                Err(AcquireError::OutOfDate) => {
+                    log::debug!("Swapchain is out of date, recreating it");
+                    self.renderer.resize();
+                    self.presenter.clear_framebuffers();
+                }
+                Err(error @ (AcquireError::SurfaceLost | AcquireError::DeviceLost)) => {
+                    panic!("Cannot acquire swapchain image: {error}")
+                }
+                Err(error) => {
+                    log::warn!("Cannot acquire swapchain image, the frame is skipped: {error}");
+                    return None;
+                }
+            }
+        }
+        log::warn!(
+            "Swapchain is still out of date after {ACQUIRE_ATTEMPTS} attempts, the frame is skipped"
+        );
+        None
+    }
+
+    /// Submits generations due since the last frame as a single batch
+    /// the host does not wait for, see [`GameOfLife::poll_batch`].
+    /// When the board changed since it was last read and the readback interval passed,
+    /// the board is copied to host memory at the end of the batch, even without generations.
+    ///
+    /// # Panics
+    ///
+    /// - when vulkan fails to execute any of commands.
+    fn submit_batch(&mut self, now: Instant) {
+        let steps = self.due_steps(now);
+        let read = self.readback_due && now - self.read_at >= self.readback_interval;
+        if steps == 0 && !read {
+            return;
+        }
+        let mut future = sync::now(self.renderer.graphics_queue().device().clone()).boxed();
+        for simulation in iter::once(&mut self.simulation).chain(&mut self.comparison) {
+            let target = simulation.generation() + u64::from(steps);
+            future = simulation.run_to(target, future);
+        }
+        self.controller.record_steps(now, steps);
+        log::trace!("Submitting a batch of {steps} generations, board read back: {read}");
+        if read {
+            future = self.readback.copy(future);
+            self.readback_due = false;
+            self.read_at = now;
+        }
+        self.batch = Some(Batch {
+            fence: future
+                .then_signal_fence_and_flush()
+                .expect("failed to execute command buffer"),
+            submitted: now,
+            steps,
+        });
+    }
+
+    /// Finishes the batch of generations when the GPU is done with it,
+    /// or gives up on it when it takes longer than `--gpu-timeout`.
+    /// Returns whether no batch is running anymore.
+    ///
+    /// # Panics
+    ///
+    /// - when vulkan fails to check the fence of the batch.
+    fn poll_batch(&mut self) -> bool {
+        let (done, overdue) = self.batch.as_ref().map_or((true, false), |batch| {
+            (
+                batch.fence.is_signaled().expect("failed to check fence"),
+                batch.submitted.elapsed() >= self.gpu_timeout,
+            )
+        });
+        if done || overdue {
+            self.finish_batch();
+        }
+        done
+    }
+
+    /// Waits for given work submitted outside of batches, like edits of the board,
+    /// at most `--gpu-timeout` like [`GameOfLife::finish_batch`] waits for batches.
+    /// Returns whether the work finished, see [`GameOfLife::finished`].
+    fn wait_for(&mut self, future: impl GpuFuture, work: &str) -> bool {
+        let result = vulkan::try_wait(future, Some(self.gpu_timeout));
+        self.finished(result, work).is_some()
+    }
+
+    /// Returns result of work waited for at most `--gpu-timeout`, `None` when it failed.
+    /// When the work does not finish in time or the device is lost, the error is logged
+    /// and the application [`GameOfLife::stalled`], other errors are only logged.
+    fn finished<T>(&mut self, result: Result<T, Error>, work: &str) -> Option<T> {
+        match result {
+            Ok(value) => Some(value),
+            Err(error) => {
+                if !self.check_stall(&error, work) {
+                    log::error!("{work} failed: {error}");
                 }
-                let x = self.presenter.draw(&self.renderer, self.controller.grid());
+                None
+            }
+        }
+    }
 
-                future = future
-                    .then_execute(self.renderer.graphics_queue(), x)
-                    .expect("failed to execute command buffer")
-                    .boxed();
+    /// Logs the error and marks the application [`GameOfLife::stalled`] when work
+    /// did not finish within `--gpu-timeout` or the device was lost.
+    /// Returns whether the application stalled.
+    fn check_stall(&mut self, error: &Error, work: &str) -> bool {
+        if error.is_stall() {
+            log::error!(
+                "{work} did not finish within {:?}: {error}, exiting",
+                self.gpu_timeout
+            );
+            self.stalled = true;
+        }
+        error.is_stall()
+    }
 
-                future = self
-                    .controller
-                    .draw(future, self.renderer.swapchain_image_view());
+    /// Scans the board for live cells, see [`Simulation::live_bounds`].
+    /// Returns `None` when the board is empty or the scan did not finish,
+    /// see [`GameOfLife::wait_for`].
+    fn live_bounds(&mut self) -> Option<LiveBounds> {
+        let future = self.simulation.scan_live_bounds();
+        self.wait_for(future, "Scan for live cells")
+            .then(|| self.simulation.scanned_live_bounds())
+            .flatten()
+    }
 
-                self.renderer.present(future, true);
+    /// Waits for the batch of generations being computed, so the boards can be accessed.
+    /// Latency of a generation is measured from the submission until the batch is found done.
+    ///
+    /// When the batch does not finish within `--gpu-timeout` of its submission
+    /// or the device is lost, the error is logged and the application [`GameOfLife::stalled`].
+    ///
+    /// # Panics
+    ///
+    /// - when vulkan fails to wait for the batch for any other reason.
+    fn finish_batch(&mut self) {
+        if let Some(batch) = self.batch.take() {
+            let timeout = self.gpu_timeout.saturating_sub(batch.submitted.elapsed());
+            match batch.fence.wait(Some(timeout)) {
+                Ok(()) => (),
+                Err(error @ (FlushError::Timeout | FlushError::DeviceLost)) => {
+                    log::error!(
+                        "Batch of {} generations did not finish within {:?}: {error}, exiting",
+                        batch.steps,
+                        self.gpu_timeout
+                    );
+                    // Dropping the fence would wait for it again, without a timeout.
+                    mem::forget(batch.fence);
+                    self.stalled = true;
+                    return;
+                }
+                Err(error) => panic!("failed to wait for command buffer: {error}"),
+            }
+            log::trace!(
+                "Batch of {} generations finished after {:?}",
+                batch.steps,
+                batch.submitted.elapsed()
+            );
+            if let Some(words) = self.readback.take() {
+                let population = self.readback.layout().population(&words);
+                self.controller.set_population(population);
+                self.sample_population(population);
+            }
+            if batch.steps == 0 {
+                return;
             }
-            _ => (),
+            self.update_jump();
+            self.readback_due = true;
+            let latency = batch.submitted.elapsed() / batch.steps;
+            self.step_latency = Some(latency);
+            self.controller.set_step_latency(latency);
+            self.controller
+                .set_step_duration(self.simulation.last_step_duration());
+            self.stepped = true;
+            self.board_changed = true;
+            self.snapshot_due = true;
+            self.clear_difference();
+        }
+    }
+
+    /// Marks the board as changed by the user,
+    /// the compared board starts again from a copy of the edited board.
+    ///
+    /// # Panics
+    ///
+    /// - when vulkan fails to copy the board.
+    fn edited(&mut self) {
+        self.simulation.invalidate_period();
+        self.board_changed = true;
+        self.readback_due = true;
+        self.snapshot_due = true;
+        self.clear_difference();
+        if let Some(comparison) = &mut self.comparison {
+            let future = comparison.copy_from(&self.simulation);
+            self.wait_for(future, "Copy of the compared board");
+        }
+    }
+
+    /// Shows a board computed with given rule next to the main one,
+    /// or only the main board when `rule` is `None`.
+    /// A new compared board starts from a copy of the main one,
+    /// only the main board is shown when it cannot be created.
+    fn set_comparison(&mut self, rule: Option<Rule>) {
+        match rule {
+            Some(rule) => {
+                if let Some(comparison) = &mut self.comparison {
+                    comparison.set_rule(rule);
+                } else {
+                    let result = self.simulation.try_fork(rule, Some(self.gpu_timeout));
+                    let (comparison, buffer) =
+                        match self.finished(result, "Creation of the compared board") {
+                            Some(fork) => fork,
+                            None => return,
+                        };
+                    let counts = comparison.neighbour_counts();
+                    self.comparison = Some(comparison);
+                    self.presenter.set_comparison(Some(buffer), counts);
+                }
+            }
+            None => {
+                self.comparison = None;
+                self.presenter.set_comparison(None, None);
+            }
+        }
+        self.snapshot_due = true;
+    }
+
+    /// Colors alive cells of both boards by numbers of their live neighbours,
+    /// or draws them plain when `enabled` is not set.
+    ///
+    /// # Panics
+    ///
+    /// - when vulkan fails to create the buffers of neighbour counts.
+    fn set_neighbour_colors(&mut self, enabled: bool) {
+        self.simulation.set_neighbour_counts(enabled);
+        self.presenter
+            .set_neighbour_counts(self.simulation.neighbour_counts());
+        if let Some(comparison) = &mut self.comparison {
+            comparison.set_neighbour_counts(enabled);
+            self.presenter
+                .set_comparison(Some(comparison.buffer()), comparison.neighbour_counts());
+        }
+        self.snapshot_due = true;
+    }
+
+    /// Loads two saved states and highlights cells which differ between them,
+    /// the first state replaces the board and the simulation is paused.
+    /// Stops highlighting when `paths` is `None`.
+    /// Nothing changes when any of the states cannot be loaded or compared,
+    /// the error is shown in the controls.
+    ///
+    /// # Panics
+    ///
+    /// - when vulkan fails to upload the states or compute their difference.
+    fn compare_states(&mut self, paths: Option<[PathBuf; 2]>) {
+        self.controller.set_compare_error(None);
+        let paths = match paths {
+            Some(paths) => paths,
+            None => {
+                self.clear_difference();
+                return;
+            }
+        };
+        let mut patterns = Vec::with_capacity(paths.len());
+        for path in &paths {
+            match Pattern::load(path) {
+                Ok(pattern) => patterns.push(pattern),
+                Err(error) => {
+                    log::error!("Cannot load state {}: {error}", path.display());
+                    self.controller.set_compare_error(Some(format!(
+                        "Cannot load state {}: {error}",
+                        path.display()
+                    )));
+                    return;
+                }
+            }
+        }
+
+        let layout = self.simulation.layout();
+        let words =
+            [&patterns[0], &patterns[1]].map(|pattern| self.simulation.pattern_words(pattern));
+        let differing = words[0]
+            .iter()
+            .zip(&words[1])
+            .map(|(first, second)| first ^ second)
+            .filter(|word| *word != 0)
+            .map(|word| {
+                (0..layout.cells_per_word())
+                    .filter(|x| layout.cell_from_word(word, *x) != 0)
+                    .count()
+            })
+            .sum();
+
+        let (differ, second_buffer, difference) = match self.try_create_differ() {
+            Ok(differ) => differ,
+            Err(error) => {
+                log::error!("Cannot compare states: {error}");
+                self.controller
+                    .set_compare_error(Some(format!("Cannot compare states: {error}")));
+                return;
+            }
+        };
+
+        let result = self
+            .simulation
+            .try_load_pattern(&patterns[0], Some(self.gpu_timeout));
+        if self.finished(result, "Upload of the first state").is_none() {
+            return;
+        }
+        self.edited();
+        let [_, second] = words;
+        let result = vulkan::try_upload_words(
+            &self.renderer.compute_queue(),
+            second_buffer,
+            second,
+            Some(self.gpu_timeout),
+        );
+        if self
+            .finished(result, "Upload of the second state")
+            .is_none()
+            || !self.wait_for(differ.run(), "Comparison of states")
+        {
+            return;
+        }
+
+        self.presenter.set_difference(Some(difference));
+        self.controller.set_state_difference(Some(differing));
+        self.controller.set_pause(true);
+    }
+
+    /// Creates the [`Differ`] comparing the board with another board of the same layout.
+    /// Returns it together with the buffer of the other board and the buffer of the difference.
+    ///
+    /// # Errors
+    ///
+    /// - when the buffer allocation fails.
+    /// - when [`Differ::try_new`] fails.
+    fn try_create_differ(&self) -> Result<(Differ, Arc<GpuBuffer>, Arc<GpuBuffer>), Error> {
+        let queue = self.renderer.compute_queue();
+        let layout = self.simulation.layout();
+        let second = vulkan::try_create_gpu_buffer(queue.device(), layout, false)?;
+        let difference = vulkan::try_create_gpu_buffer(queue.device(), layout, false)?;
+        let differ = Differ::try_new(
+            queue,
+            [self.simulation.buffer(), second.clone(), difference.clone()],
+            layout,
+            self.simulation.workgroup(),
+        )?;
+        Ok((differ, second, difference))
+    }
+
+    /// Stops highlighting cells which differ between compared states.
+    fn clear_difference(&mut self) {
+        if self.presenter.has_difference() {
+            self.presenter.set_difference(None);
+            self.controller.set_state_difference(None);
+            self.snapshot_due = true;
+        }
+    }
+
+    /// Downloads the pattern with given name on another thread,
+    /// it is placed in the centre of the board once it arrives.
+    #[cfg(feature = "online")]
+    fn fetch_pattern(&mut self, name: String) {
+        let template = match &self.pattern_url {
+            Some(template) => template.clone(),
+            None => return,
+        };
+        self.controller
+            .set_fetch_status(format!("Fetching {name}..."));
+        let proxy = self.proxy.clone();
+        std::thread::spawn(move || {
+            let result = fetch_pattern(&template, &name).map_err(|error| error.to_string());
+            if proxy
+                .send_event(Message::PatternFetched(name, result))
+                .is_err()
+            {
+                log::warn!("Window was closed before the pattern was fetched");
+            }
+        });
+    }
+
+    /// Records population read back at the current generation, with the bounding box
+    /// of live cells, when a sample is due.
+    fn sample_population(&mut self, population: u64) {
+        let generation = self.simulation.generation();
+        if !self.population.due(generation) {
+            return;
+        }
+        self.population.record(PopulationSample {
+            generation,
+            population,
+            bounds: self.live_bounds(),
         });
+        self.controller
+            .set_population_samples(self.population.samples().len());
     }
+
+    /// Stops the jump once its generation is reached and shows its progress in the controls.
+    /// The simulation is paused at the reached generation.
+    fn update_jump(&mut self) {
+        let (start, target) = match self.jump {
+            Some(jump) => jump,
+            None => {
+                self.controller.set_jump_progress(None);
+                return;
+            }
+        };
+        let generation = self.simulation.generation();
+        if generation >= target {
+            log::info!("Jump finished at generation {generation}");
+            self.jump = None;
+            self.controller.set_pause(true);
+            self.controller.set_jump_progress(None);
+        } else {
+            #[allow(clippy::cast_precision_loss)]
+            let progress = generation.saturating_sub(start) as f32 / (target - start) as f32;
+            self.controller.set_jump_progress(Some((target, progress)));
+        }
+    }
+
+    /// Returns number of generations that should be computed in the frame started at `now`.
+    /// At most as many generations are returned as fit into [`BATCH_LATENCY`].
+    /// At max speed and during a jump that many generations are returned
+    /// regardless of the selected speed, a jump never goes past its generation.
+    /// With a fixed step, time not used for generations is kept for the next frames,
+    /// up to [`FIXED_STEP_BACKLOG`]. No generations are due while editing,
+    /// see [`GameOfLife::editing`], and the time spent editing is not made up afterwards.
+    /// While recording or exporting, batches never go past the next captured generation.
+    fn due_steps(&mut self, now: Instant) -> u32 {
+        let max_steps = if self.controller.max_speed() || self.jump.is_some() {
+            UNLIMITED_STEPS_PER_FRAME
+        } else {
+            MAX_STEPS_PER_FRAME
+        };
+        let limit = self.step_latency.map_or(max_steps, |latency| {
+            u32::try_from(BATCH_LATENCY.as_nanos() / latency.as_nanos().max(1))
+                .map_or(max_steps, |steps| steps.clamp(1, max_steps))
+        });
+        // Batches end on generations that are recorded or exported, so they are read back.
+        let limit = self.steps_to_capture().map_or(limit, |steps| {
+            u32::try_from(steps).map_or(limit, |steps| steps.min(limit))
+        });
+        let steps = if let Some((_, target)) = self.jump {
+            self.accumulator = Duration::ZERO;
+            let remaining = target.saturating_sub(self.simulation.generation());
+            u32::try_from(remaining).map_or(limit, |remaining| remaining.min(limit))
+        } else if self.controller.pause() || self.editing() {
+            self.accumulator = Duration::ZERO;
+            0
+        } else if self.controller.max_speed() {
+            self.accumulator = Duration::ZERO;
+            limit
+        } else if let Some(interval) = self.fixed_step {
+            self.accumulator += now - self.timer;
+            let behind = self.accumulator.as_nanos() / interval.as_nanos();
+            let steps = u32::try_from(behind).map_or(limit, |steps| steps.min(limit));
+            self.accumulator = (self.accumulator - interval * steps).min(FIXED_STEP_BACKLOG);
+            steps
+        } else {
+            let interval = Duration::from_secs(1) / self.controller.speed();
+            self.accumulator += now - self.timer;
+            let behind = self.accumulator.as_nanos() / interval.as_nanos();
+            let steps = u32::try_from(behind).map_or(limit, |steps| steps.min(limit));
+            self.accumulator = if steps == limit {
+                Duration::ZERO
+            } else {
+                self.accumulator - interval * steps
+            };
+            steps
+        };
+        self.timer = now;
+        steps
+    }
+
+    /// Returns number of generations until the next one the recording or the export captures,
+    /// `None` when neither is running.
+    fn steps_to_capture(&self) -> Option<u64> {
+        let generation = self.simulation.generation();
+        let recorder = self
+            .recorder
+            .as_ref()
+            .map(|recorder| recorder.steps_to_frame(generation));
+        let exporter = self
+            .exporter
+            .as_ref()
+            .and_then(|exporter| exporter.next_generation(generation))
+            .map(|next| next - generation);
+        recorder.into_iter().chain(exporter).min()
+    }
+
+    /// Captures the current generation by the GIF recorder and the PNG exporter.
+    fn capture(&mut self) {
+        let generation = self.simulation.generation();
+        let copy = self
+            .recorder
+            .as_mut()
+            .and_then(|recorder| recorder.copy(generation));
+        if let Some(future) = copy {
+            if !self.wait_for(future, "Copy of the recorded frame") {
+                return;
+            }
+        }
+        if let Some(recorder) = &mut self.recorder {
+            match recorder.capture(generation) {
+                Ok(true) => (),
+                Ok(false) => {
+                    log::info!("Recording finished, maximum number of frames reached");
+                    self.recorder = None;
+                }
+                Err(error) => {
+                    log::error!("Cannot record frame: {error}");
+                    self.recorder = None;
+                }
+            }
+            self.controller.set_recording(self.recorder.is_some());
+        }
+
+        let copy = self
+            .exporter
+            .as_ref()
+            .and_then(|exporter| exporter.copy(generation));
+        if let Some(future) = copy {
+            if !self.wait_for(future, "Copy of the exported frame") {
+                return;
+            }
+        }
+        if let Some(exporter) = &mut self.exporter {
+            match exporter.capture(generation) {
+                Ok(true) => (),
+                Ok(false) => {
+                    log::info!("Export finished, last generation reached");
+                    self.exporter = None;
+                }
+                Err(error) => {
+                    log::error!("Cannot export frame: {error}");
+                    self.exporter = None;
+                }
+            }
+        }
+    }
+
+    /// Reports when the board becomes stable and clears the report when it changes again.
+    fn check_stability(&mut self) {
+        if self.controller.stability_action() == StabilityAction::Ignore {
+            return;
+        }
+        let period = match self.simulation.compare_generations() {
+            Some(future) => self
+                .wait_for(future, "Stability check")
+                .then(|| self.simulation.compared_period())
+                .flatten(),
+            None => None,
+        };
+        match period {
+            Some(period) if !self.stable => {
+                self.stable = true;
+                self.proxy
+                    .send_event(Message::Stabilized(period))
+                    .expect("Cannot send event");
+            }
+            Some(_) => (),
+            None => {
+                self.stable = false;
+                self.controller.clear_stabilized();
+            }
+        }
+    }
+
+    /// Updates the live cells bounds and the cell under the cursor shown in the controls.
+    fn refresh_probes(&mut self, now: Instant) {
+        let refresh = self.board_changed && now - self.probed.1 >= PROBE_INTERVAL;
+        if refresh {
+            let bounds = self.live_bounds();
+            self.controller.set_live_bounds(bounds);
+        }
+
+        let cursor = self.presenter.camera().cursor_game_position();
+        if cursor != self.probed.0 || refresh {
+            let cell = cursor.and_then(|position| {
+                let future = self.probe.copy(position)?;
+                self.wait_for(future, "Cell probe")
+                    .then(|| (position, self.probe.copied(position)))
+            });
+            self.controller.set_cursor_cell(cell);
+            self.probed = (cursor, now);
+            self.board_changed = false;
+        }
+    }
+
+    /// Sets the window title from its format, at most once every [`TITLE_INTERVAL`].
+    fn refresh_title(&mut self, now: Instant) {
+        let format = match &self.title_format {
+            Some(format) if now - self.titled.1 >= TITLE_INTERVAL => format,
+            _ => return,
+        };
+        let title = window_title(
+            format,
+            &self.base_title,
+            self.simulation.rule(),
+            self.simulation.layout().size(),
+            self.simulation.generation(),
+        );
+        if title != self.titled.0 {
+            self.renderer.window().set_title(&title);
+        }
+        self.titled = (title, now);
+    }
+
+    /// Expands the board when live cells came close to its edge.
+    /// When the expansion fails, the board is not expanded anymore and the error is shown.
+    fn expand(&mut self) {
+        let check = match self.expander.as_ref().and_then(Expander::check) {
+            Some(check) => check,
+            None => return,
+        };
+        if !self.wait_for(check, "Check for expansion") {
+            return;
+        }
+        let layout = match self.expander.as_ref().and_then(Expander::checked_layout) {
+            Some(layout) => layout,
+            None => return,
+        };
+        let (width, height) = layout.size();
+        log::info!("Expanding the board to {width}x{height}");
+        if let Err(error) = self.try_expand(layout) {
+            if self.check_stall(&error, "Expansion of the board") {
+                return;
+            }
+            log::error!("Cannot expand the board: {error}");
+            self.controller
+                .set_expand_error(format!("Cannot expand the board: {error}"));
+            self.expander = None;
+        }
+    }
+
+    /// Expands the board to given layout and recreates everything working with the board.
+    /// Everything is created before anything is replaced, so nothing changes when it fails.
+    ///
+    /// # Errors
+    ///
+    /// - when the expanded boards do not fit in the allowed fraction of device memory.
+    /// - when the boards, the presenter or any of the pipelines cannot be created.
+    fn try_expand(&mut self, layout: BoardLayout) -> Result<(), Error> {
+        let queue = self.renderer.compute_queue();
+        let boards = if self.comparison.is_some() { 2 } else { 1 };
+        vulkan::try_validate_memory(
+            queue.device(),
+            board_memory(
+                layout,
+                self.simulation.history_depth(),
+                self.simulation.neighbour_counts().is_some(),
+            )
+            .saturating_mul(boards),
+            self.memory_fraction,
+        )?;
+
+        let timeout = Some(self.gpu_timeout);
+        let (simulation, buffer) = self.simulation.try_expanded(layout, timeout)?;
+        let comparison = self
+            .comparison
+            .as_ref()
+            .map(|comparison| comparison.try_expanded(layout, timeout))
+            .transpose()?;
+        let workgroup = simulation.workgroup();
+        let flipper = Flipper::try_new(queue.clone(), buffer.clone(), layout)?;
+        let filler = Filler::try_new(queue.clone(), buffer.clone(), layout, workgroup)?;
+        let probe = CellProbe::try_new(queue.clone(), buffer.clone(), layout)?;
+        let readback = AsyncReadback::try_new(
+            vulkan::transfer_queue(&self.renderer),
+            buffer.clone(),
+            layout,
+        )?;
+        let expander = Expander::try_new(
+            queue,
+            buffer.clone(),
+            layout,
+            workgroup,
+            &self.expand_config,
+        )?;
+        let export_readback = self
+            .exporter
+            .as_ref()
+            .map(|_| simulation.try_readback())
+            .transpose()?;
+        // The presenter is left as it was when it fails, so it is replaced last.
+        self.presenter.try_set_board(buffer, layout)?;
+
+        self.presenter
+            .set_neighbour_counts(simulation.neighbour_counts());
+        if let Some((comparison, buffer)) = &comparison {
+            self.presenter
+                .set_comparison(Some(buffer.clone()), comparison.neighbour_counts());
+        }
+        self.simulation = simulation;
+        self.comparison = comparison.map(|(comparison, _)| comparison);
+        self.flipper = flipper;
+        self.filler = filler;
+        self.fill_drag = None;
+        self.probe = probe;
+        self.readback = readback;
+        self.readback_due = true;
+        self.expander = Some(expander);
+        self.controller.set_state_difference(None);
+        self.snapshot_due = true;
+        if self.recorder.take().is_some() {
+            log::info!("Recording stopped, the board was expanded");
+            self.controller.set_recording(false);
+        }
+        if let (Some(exporter), Some(readback)) = (&mut self.exporter, export_readback) {
+            exporter.set_readback(readback);
+        }
+        Ok(())
+    }
+}
+
+/// Restores the board saved on exit from given file, centred when the board size differs.
+fn resume(simulation: &mut Simulation, path: Option<&Path>) {
+    let path = match path {
+        Some(path) => path,
+        None => {
+            log::warn!("Cannot determine where the board was saved, nothing is restored");
+            return;
+        }
+    };
+    match SavedBoard::load(path) {
+        Ok(board) => {
+            log::info!(
+                "Restoring generation {} of the board from {}",
+                board.generation,
+                path.display()
+            );
+            simulation.write_cells(&board.cells_for(simulation.layout().size()));
+            simulation.set_generation(board.generation);
+        }
+        Err(error) if error.kind() == io::ErrorKind::NotFound => {
+            log::info!(
+                "No saved board at {}, starting with an empty board",
+                path.display()
+            );
+        }
+        Err(error) => log::error!("Cannot restore board {}: {error}", path.display()),
+    }
+}
+
+/// Returns the window title, placeholders `{title}`, `{rule}`, `{width}`, `{height}`
+/// and `{generation}` in `format` are replaced with their values.
+fn window_title(
+    format: &str,
+    title: &str,
+    rule: Rule,
+    (width, height): (u32, u32),
+    generation: u64,
+) -> String {
+    format
+        .replace("{title}", title)
+        .replace("{rule}", &rule.to_string())
+        .replace("{width}", &width.to_string())
+        .replace("{height}", &height.to_string())
+        .replace("{generation}", &generation.to_string())
+}
+
+/// Returns number of bytes of device memory taken by buffers of a board with given layout,
+/// the board itself, its snapshot drawn on the screen, two previous generations,
+/// the history and the neighbour counts with their snapshot.
+fn board_memory(layout: BoardLayout, history_depth: usize, neighbour_counts: bool) -> u64 {
+    let buffers = u64::try_from(history_depth).map_or(u64::MAX, |depth| depth.saturating_add(4));
+    let counts = if neighbour_counts {
+        layout.count_len().saturating_mul(2)
+    } else {
+        0
+    };
+    layout
+        .len()
+        .saturating_mul(buffers)
+        .saturating_add(counts)
+        .saturating_mul(u64::from(u32::BITS / 8))
+}
+
+/// Starts a GIF recording of the current generation of the simulation.
+/// Returns `None` and logs the error when the recording cannot be started.
+fn start_recording(simulation: &Simulation, config: &RecordConfig) -> Option<Recorder> {
+    let readback = match simulation.try_readback() {
+        Ok(readback) => readback,
+        Err(error) => {
+            log::error!("Cannot start recording: {error}");
+            return None;
+        }
+    };
+    Recorder::new(readback, config, simulation.generation())
+        .map_err(|error| log::error!("Cannot start recording: {error}"))
+        .ok()
 }
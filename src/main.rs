@@ -1,10 +1,37 @@
 #![forbid(clippy::all)]
 #![warn(clippy::pedantic)]
 #![warn(clippy::unwrap_used)]
-use clap::Parser;
-use game_of_life::{GameOfLife, Config};
+use clap::{error::ErrorKind, CommandFactory, Parser};
+use game_of_life::{benchmark_steps, Config, GameOfLife};
 
 fn main() {
-    let game = GameOfLife::new(&Config::parse());
-    game.run();
+    let config = Config::parse();
+    env_logger::Builder::new()
+        .filter_level(config.log_level())
+        .parse_env(env_logger::Env::default())
+        .init();
+    if let Err(message) = config.validate() {
+        Config::command()
+            .error(ErrorKind::ArgumentConflict, message)
+            .exit();
+    }
+    if let Some(generations) = config.bench() {
+        match benchmark_steps(&config, generations) {
+            Ok(report) => {
+                println!("{report}");
+                std::process::exit(if report.identical { 0 } else { 1 });
+            }
+            Err(error) => {
+                eprintln!("error: {error}");
+                std::process::exit(1);
+            }
+        }
+    }
+    match GameOfLife::try_new(&config) {
+        Ok(game) => game.run(),
+        Err(error) => {
+            eprintln!("error: {error}");
+            std::process::exit(1);
+        }
+    }
 }
@@ -0,0 +1,84 @@
+use std::{error::Error, fmt, io};
+
+use crate::{Pattern, PatternError};
+
+/// URL template of the LifeWiki pattern collection, `{name}` is replaced with the pattern name.
+pub const DEFAULT_PATTERN_URL: &str = "https://conwaylife.com/patterns/{name}.rle";
+
+/// Downloads the run length encoded pattern with given name.
+/// The name is written into `template` in place of `{name}`, in lowercase and without spaces,
+/// as used by LifeWiki, so both `Gosper glider gun` and `gosperglidergun` work.
+///
+/// The request blocks until the whole pattern is downloaded.
+///
+/// # Errors
+///
+/// - when the name is empty or contains characters other than letters, digits, `-` and `_`.
+/// - when the request fails.
+/// - when the response cannot be read.
+/// - when the response is not a valid pattern.
+pub fn fetch_pattern(template: &str, name: &str) -> Result<Pattern, FetchError> {
+    let name: String = name
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .collect();
+    let valid = |c: char| c.is_ascii_alphanumeric() || c == '-' || c == '_';
+    if name.is_empty() || !name.chars().all(valid) {
+        return Err(FetchError::InvalidName(name));
+    }
+
+    let url = template.replace("{name}", &name);
+    log::info!("Fetching pattern from {url}");
+    let text = ureq::get(&url)
+        .call()
+        .map_err(|error| FetchError::Request(Box::new(error)))?
+        .into_string()?;
+    Ok(Pattern::parse_rle(&text)?)
+}
+
+/// Error returned when a pattern cannot be fetched.
+#[derive(Debug)]
+pub enum FetchError {
+    InvalidName(String),
+    Request(Box<ureq::Error>),
+    Io(io::Error),
+    Pattern(PatternError),
+}
+
+impl fmt::Display for FetchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidName(name) => write!(
+                f,
+                "invalid pattern name '{name}', expected letters, digits, '-' or '_'"
+            ),
+            Self::Request(error) => write!(f, "cannot download pattern: {error}"),
+            Self::Io(error) => write!(f, "cannot read downloaded pattern: {error}"),
+            Self::Pattern(error) => write!(f, "invalid downloaded pattern: {error}"),
+        }
+    }
+}
+
+impl Error for FetchError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::InvalidName(_) => None,
+            Self::Request(error) => Some(error),
+            Self::Io(error) => Some(error),
+            Self::Pattern(error) => Some(error),
+        }
+    }
+}
+
+impl From<io::Error> for FetchError {
+    fn from(error: io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+impl From<PatternError> for FetchError {
+    fn from(error: PatternError) -> Self {
+        Self::Pattern(error)
+    }
+}
@@ -0,0 +1,264 @@
+use std::{error::Error, fmt, fs, io, path::Path};
+
+/// Longest run of cells accepted in a run length encoded pattern.
+const MAX_RUN: u32 = 1 << 16;
+
+/// Largest width and height in cells of a run length encoded pattern.
+const MAX_SIZE: u32 = 1 << 20;
+
+/// Largest number of live cells of a run length encoded pattern.
+const MAX_CELLS: usize = 1 << 22;
+
+/// This struct represents a pattern of live cells loaded from a file.
+///
+/// Positions of cells are relative to the top left corner of the pattern.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Pattern {
+    width: u32,
+    height: u32,
+    cells: Vec<[u32; 2]>,
+}
+
+impl Pattern {
+    /// Loads a pattern from a file, the format is selected by the file extension.
+    ///
+    /// Supported formats:
+    /// - `.cells` plaintext with `.` for dead and `O` for alive cells.
+    /// - `.rle` run length encoded cells, see [`Pattern::parse_rle`].
+    ///
+    /// # Errors
+    ///
+    /// - when the format of the file is not supported.
+    /// - when the file cannot be read.
+    /// - when the file is not a valid pattern.
+    pub fn load(path: &Path) -> Result<Self, PatternError> {
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .unwrap_or_default();
+        match extension.to_ascii_lowercase().as_str() {
+            "cells" => Self::parse_cells(&fs::read_to_string(path)?),
+            "rle" => Self::parse_rle(&fs::read_to_string(path)?),
+            _ => Err(PatternError::UnsupportedFormat(extension.to_owned())),
+        }
+    }
+
+    /// Parses a pattern in the plaintext `.cells` format.
+    /// Lines starting with `!` are comments.
+    ///
+    /// # Errors
+    ///
+    /// - when a line contains a character other than `.` or `O`.
+    pub fn parse_cells(text: &str) -> Result<Self, PatternError> {
+        let mut pattern = Self {
+            width: 0,
+            height: 0,
+            cells: Vec::new(),
+        };
+        for line in text.lines().filter(|line| !line.starts_with('!')) {
+            let mut width = 0;
+            for c in line.trim_end().chars() {
+                match c {
+                    '.' => (),
+                    'O' => pattern.cells.push([width, pattern.height]),
+                    _ => return Err(PatternError::InvalidCell(c)),
+                }
+                width += 1;
+            }
+            pattern.width = pattern.width.max(width);
+            pattern.height += 1;
+        }
+        Ok(pattern)
+    }
+
+    /// Parses a pattern in the run length encoded `.rle` format.
+    /// Lines starting with `#` and the header line starting with `x` are skipped,
+    /// every state other than `b` is alive and the pattern ends at `!`.
+    ///
+    /// # Errors
+    ///
+    /// - when the pattern contains a character other than a count, a state, `$` or `!`.
+    /// - when a run is longer than 65536 cells.
+    /// - when the pattern is wider or taller than 1048576 cells.
+    /// - when the pattern has more than 4194304 live cells.
+    pub fn parse_rle(text: &str) -> Result<Self, PatternError> {
+        let mut pattern = Self {
+            width: 0,
+            height: 0,
+            cells: Vec::new(),
+        };
+        let lines = text
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.starts_with('#') && !line.starts_with('x'));
+        let advance = |position: u32, count: u32| {
+            position
+                .checked_add(count)
+                .filter(|position| *position <= MAX_SIZE)
+                .ok_or(PatternError::TooLarge)
+        };
+        let (mut x, mut y, mut run) = (0, 0, 0_u32);
+        'lines: for line in lines {
+            for c in line.chars() {
+                if let Some(digit) = c.to_digit(10) {
+                    run = run.saturating_mul(10).saturating_add(digit);
+                    if run > MAX_RUN {
+                        return Err(PatternError::RunTooLong(run));
+                    }
+                    continue;
+                }
+                let count = run.max(1);
+                match c {
+                    'b' => x = advance(x, count)?,
+                    '$' => {
+                        x = 0;
+                        y = advance(y, count)?;
+                    }
+                    '!' => break 'lines,
+                    _ if c.is_ascii_alphabetic() => {
+                        let end = advance(x, count)?;
+                        let height = advance(y, 1)?;
+                        let room = MAX_CELLS - pattern.cells.len();
+                        if usize::try_from(count).map_or(true, |count| count > room) {
+                            return Err(PatternError::TooManyCells);
+                        }
+                        pattern.cells.extend((x..end).map(|x| [x, y]));
+                        x = end;
+                        pattern.height = height;
+                    }
+                    _ => return Err(PatternError::InvalidRle(c)),
+                }
+                pattern.width = pattern.width.max(x);
+                run = 0;
+            }
+        }
+        Ok(pattern)
+    }
+
+    /// Returns dimensions of the pattern in cells.
+    #[inline]
+    #[must_use]
+    pub fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    /// Returns positions of live cells of the pattern.
+    #[inline]
+    #[must_use]
+    pub fn cells(&self) -> &[[u32; 2]] {
+        &self.cells
+    }
+}
+
+/// Error returned when a pattern cannot be loaded.
+#[derive(Debug)]
+pub enum PatternError {
+    Io(io::Error),
+    UnsupportedFormat(String),
+    InvalidCell(char),
+    InvalidRle(char),
+    RunTooLong(u32),
+    TooLarge,
+    TooManyCells,
+}
+
+impl fmt::Display for PatternError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "cannot read pattern: {error}"),
+            Self::UnsupportedFormat(extension) => {
+                write!(
+                    f,
+                    "unsupported pattern format '{extension}', expected 'cells' or 'rle'"
+                )
+            }
+            Self::InvalidCell(cell) => {
+                write!(f, "invalid cell '{cell}', expected '.' or 'O'")
+            }
+            Self::InvalidRle(c) => {
+                write!(
+                    f,
+                    "invalid character '{c}', expected a count, a state, '$' or '!'"
+                )
+            }
+            Self::RunTooLong(run) => {
+                write!(f, "run of {run} cells is longer than {MAX_RUN}")
+            }
+            Self::TooLarge => {
+                write!(f, "pattern is wider or taller than {MAX_SIZE} cells")
+            }
+            Self::TooManyCells => {
+                write!(f, "pattern has more than {MAX_CELLS} live cells")
+            }
+        }
+    }
+}
+
+impl Error for PatternError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for PatternError {
+    fn from(error: io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rle_glider() {
+        let glider = Pattern::parse_rle("#N Glider\nx = 3, y = 3, rule = B3/S23\nbob$2bo$3o!")
+            .expect("Glider is valid");
+        assert_eq!(glider.size(), (3, 3));
+        assert_eq!(
+            glider.cells(),
+            [[1, 0], [2, 1], [0, 2], [1, 2], [2, 2]].as_slice()
+        );
+    }
+
+    #[test]
+    fn rle_matches_cells() {
+        let rle = Pattern::parse_rle("2o$\n2o!").expect("Block is valid");
+        let cells = Pattern::parse_cells("OO\nOO").expect("Block is valid");
+        assert_eq!(rle, cells);
+    }
+
+    #[test]
+    fn rle_rejects_long_runs() {
+        assert!(matches!(
+            Pattern::parse_rle("99999999o!"),
+            Err(PatternError::RunTooLong(_))
+        ));
+        assert!(matches!(
+            Pattern::parse_rle("4294967295o4294967295o!"),
+            Err(PatternError::RunTooLong(_))
+        ));
+    }
+
+    #[test]
+    fn rle_rejects_large_patterns() {
+        let wide = format!("{}o!", "65536b".repeat(16));
+        assert!(matches!(
+            Pattern::parse_rle(&wide),
+            Err(PatternError::TooLarge)
+        ));
+        let tall = format!("{}o!", "65536$".repeat(16));
+        assert!(matches!(
+            Pattern::parse_rle(&tall),
+            Err(PatternError::TooLarge)
+        ));
+        let dense = "65536o$".repeat(65);
+        assert!(matches!(
+            Pattern::parse_rle(&dense),
+            Err(PatternError::TooManyCells)
+        ));
+    }
+}
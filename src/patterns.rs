@@ -0,0 +1,46 @@
+use crate::Pattern;
+
+/// Patterns built into the application, with names shown in the controls.
+/// Every pattern is stored in the plaintext `.cells` format.
+pub static BUILTIN_PATTERNS: [(&str, &str); 5] = [
+    ("Glider", include_str!("patterns/glider.cells")),
+    ("Lightweight spaceship", include_str!("patterns/lwss.cells")),
+    ("Blinker", include_str!("patterns/blinker.cells")),
+    ("Pulsar", include_str!("patterns/pulsar.cells")),
+    (
+        "Gosper glider gun",
+        include_str!("patterns/gosper_glider_gun.cells"),
+    ),
+];
+
+impl Pattern {
+    /// Returns the built-in pattern with given index into [`BUILTIN_PATTERNS`].
+    ///
+    /// # Panics
+    ///
+    /// - when the index is out of bounds.
+    #[must_use]
+    pub fn builtin(index: usize) -> Self {
+        Self::parse_cells(BUILTIN_PATTERNS[index].1).expect("Built-in patterns are valid")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin_patterns_are_valid() {
+        for (name, text) in &BUILTIN_PATTERNS {
+            let pattern = Pattern::parse_cells(text).expect(name);
+            assert!(!pattern.cells().is_empty(), "{name} has no live cells");
+        }
+    }
+
+    #[test]
+    fn glider_size() {
+        let glider = Pattern::builtin(0);
+        assert_eq!(glider.size(), (3, 3));
+        assert_eq!(glider.cells().len(), 5);
+    }
+}
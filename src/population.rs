@@ -0,0 +1,129 @@
+use std::{fmt::Write, fs, io, path::Path};
+
+use crate::LiveBounds;
+
+/// Population of the board read back at a single generation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PopulationSample {
+    pub generation: u64,
+    pub population: u64,
+    /// Bounding box of live cells, `None` when the board is empty.
+    pub bounds: Option<LiveBounds>,
+}
+
+/// This struct represents population of the board over time, exported as CSV.
+///
+/// Samples are taken from readbacks of the board, at most one every `interval` generations.
+/// When the generation goes back, because the board was cleared, randomized or stepped back,
+/// samples past it are dropped.
+pub struct PopulationHistory {
+    samples: Vec<PopulationSample>,
+    interval: u64,
+}
+
+impl PopulationHistory {
+    /// Creates an empty history taking a sample at most every `interval` generations.
+    #[must_use]
+    pub fn new(interval: u64) -> Self {
+        Self {
+            samples: Vec::new(),
+            interval: interval.max(1),
+        }
+    }
+
+    /// Returns whether a sample at given generation would be recorded.
+    #[must_use]
+    pub fn due(&self, generation: u64) -> bool {
+        self.samples.last().map_or(true, |last| {
+            generation >= last.generation + self.interval || generation < last.generation
+        })
+    }
+
+    /// Records a sample, it is ignored when it comes sooner than the interval after the last one.
+    pub fn record(&mut self, sample: PopulationSample) {
+        if !self.due(sample.generation) {
+            return;
+        }
+        let kept = self
+            .samples
+            .partition_point(|kept| kept.generation < sample.generation);
+        self.samples.truncate(kept);
+        self.samples.push(sample);
+    }
+
+    /// Returns recorded samples, ordered by their generations.
+    #[inline]
+    #[must_use]
+    pub fn samples(&self) -> &[PopulationSample] {
+        &self.samples
+    }
+
+    /// Returns samples as CSV with a header row,
+    /// bounds columns are left empty for samples of an empty board.
+    #[must_use]
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("generation,population,min_x,min_y,max_x,max_y\n");
+        for sample in &self.samples {
+            let bounds = sample.bounds.map_or_else(
+                || String::from(",,,"),
+                |(min_x, min_y, max_x, max_y)| format!("{min_x},{min_y},{max_x},{max_y}"),
+            );
+            writeln!(csv, "{},{},{bounds}", sample.generation, sample.population)
+                .expect("Cannot write into a string");
+        }
+        csv
+    }
+
+    /// Writes samples as CSV into given file.
+    ///
+    /// # Errors
+    ///
+    /// - when the file cannot be written.
+    pub fn save_csv(&self, path: &Path) -> io::Result<()> {
+        fs::write(path, self.to_csv())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(generation: u64, population: u64) -> PopulationSample {
+        PopulationSample {
+            generation,
+            population,
+            bounds: (population > 0).then(|| (1, 2, 3, 4)),
+        }
+    }
+
+    #[test]
+    fn samples_are_taken_every_interval() {
+        let mut history = PopulationHistory::new(10);
+        for generation in [0, 4, 10, 15, 27] {
+            history.record(sample(generation, 5));
+        }
+        let generations: Vec<_> = history.samples().iter().map(|s| s.generation).collect();
+        assert_eq!(generations, [0, 10, 27]);
+    }
+
+    #[test]
+    fn going_back_drops_later_samples() {
+        let mut history = PopulationHistory::new(1);
+        for generation in [0, 1, 2, 3] {
+            history.record(sample(generation, 5));
+        }
+        history.record(sample(1, 7));
+        assert_eq!(history.samples(), [sample(0, 5), sample(1, 7)]);
+    }
+
+    #[test]
+    fn csv_leaves_bounds_of_empty_board_empty() {
+        let mut history = PopulationHistory::new(1);
+        history.record(sample(0, 3));
+        history.record(sample(1, 0));
+        assert_eq!(
+            history.to_csv(),
+            "generation,population,min_x,min_y,max_x,max_y\n0,3,1,2,3,4\n1,0,,,\n"
+        );
+    }
+}
@@ -1,24 +1,93 @@
-use std::sync::Arc;
+use std::{iter, sync::Arc};
 
+use serde::{Deserialize, Serialize};
 use vulkano::{
+    buffer::{BufferUsage, DeviceLocalBuffer, TypedBufferAccess},
     command_buffer::{
-        AutoCommandBufferBuilder, CommandBufferUsage, RenderPassBeginInfo, SubpassContents,
+        pool::standard::StandardCommandPoolAlloc, AutoCommandBufferBuilder,
+        CommandBufferInheritanceInfo, CommandBufferUsage, CopyBufferInfo, PrimaryCommandBuffer,
+        RenderPassBeginInfo, SecondaryAutoCommandBuffer, SubpassContents,
     },
     descriptor_set::{PersistentDescriptorSet, WriteDescriptorSet},
+    device::{Device, Queue},
+    format::Format,
+    image::{
+        view::ImageView, AttachmentImage, ImageAccess, ImageUsage, ImageViewAbstract, SampleCount,
+    },
     pipeline::{
         graphics::{
             input_assembly::{InputAssemblyState, PrimitiveTopology},
+            multisample::MultisampleState,
             render_pass::PipelineRenderPassType,
             viewport::{Viewport, ViewportState},
         },
         GraphicsPipeline, Pipeline, PipelineBindPoint,
     },
-    render_pass::{Framebuffer, FramebufferCreateInfo, Subpass},
+    render_pass::{Framebuffer, FramebufferCreateInfo, RenderPass, Subpass},
+    sync::GpuFuture,
 };
 use vulkano_util::renderer::VulkanoWindowRenderer;
-use winit::event::WindowEvent;
+use winit::{dpi::PhysicalSize, event::WindowEvent};
+
+use crate::{BoardLayout, Camera, CommandBuffer, Error, GpuBuffer, Palette, PaletteColors};
+
+type DrawCommands = SecondaryAutoCommandBuffer<StandardCommandPoolAlloc>;
+
+/// Default color the screen is cleared with before drawing the board, in sRGB.
+pub const DEFAULT_BACKGROUND: [u8; 3] = [188, 188, 188];
+
+/// Largest fraction of a cell left as a gap around it, cells are drawn at half their size.
+pub(crate) const MAX_CELL_PADDING: f32 = 0.5;
+
+/// Default color of the border drawn around the board, in sRGB.
+pub const DEFAULT_BORDER_COLOR: [u8; 3] = [220, 40, 40];
+
+/// Widest border drawn around the board in device pixels.
+pub(crate) const MAX_BORDER_WIDTH: u32 = 16;
+
+/// How states of dying cells are mapped to their brightness,
+/// rules with only dead and alive cells are drawn the same with all of them.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CellShading {
+    /// Cells fade evenly from the alive color towards the dead one with every state.
+    #[default]
+    Linear,
+    /// Cells stay bright for the first states and fade quickly before they die.
+    Quadratic,
+    /// Every shown state is drawn with the alive color.
+    Solid,
+}
+
+impl CellShading {
+    /// All shadings in order they should be displayed.
+    pub const ALL: [Self; 3] = [Self::Linear, Self::Quadratic, Self::Solid];
+
+    /// Returns human readable name of the shading.
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Linear => "Linear",
+            Self::Quadratic => "Quadratic",
+            Self::Solid => "Solid",
+        }
+    }
+
+    /// Returns identifier of the shading used by the presenter shader.
+    #[must_use]
+    fn id(self) -> u32 {
+        match self {
+            Self::Linear => 0,
+            Self::Quadratic => 1,
+            Self::Solid => 2,
+        }
+    }
+}
 
-use crate::{Camera, CommandBuffer, GpuBuffer};
+/// Number of bytes of push constants used by the presenter shaders,
+/// no other pipeline uses more.
+#[allow(clippy::cast_possible_truncation)]
+pub(crate) const PUSH_CONSTANTS_SIZE: u32 = std::mem::size_of::<shader::ty::Camera>() as u32;
 
 /// This module contains compiled vertex and fragment shaders and shader data structures.
 mod shader {
@@ -33,6 +102,7 @@ mod shader {
                 path: "src/shaders/presenter.frag",
             }
         },
+        include: ["src/shaders"],
         types_meta: {
             use bytemuck::{Pod, Zeroable};
             #[derive(Clone, Copy, Pod, Zeroable)]
@@ -40,12 +110,92 @@ mod shader {
     }
 }
 
+/// Copies of a board and its neighbour counts drawn by the [`Presenter`],
+/// so the board can be written by generations computed while it is drawn.
+/// Cells differing from another board are read from a buffer that is not copied.
+struct Snapshot {
+    /// Buffers of the board paired with their copies, the board itself is the first one.
+    copies: Vec<(Arc<GpuBuffer>, Arc<GpuBuffer>)>,
+    descriptor: Arc<PersistentDescriptorSet>,
+}
+
+impl Snapshot {
+    /// Creates copies of the board and its neighbour counts and a descriptor set binding them
+    /// together with the `difference`. Copies are empty until [`Presenter::snapshot`] is called.
+    ///
+    /// # Errors
+    ///
+    /// - when the buffer allocation fails.
+    /// - when the descriptor set creation fails.
+    fn try_new(
+        pipeline: &GraphicsPipeline,
+        buffer: Arc<GpuBuffer>,
+        counts: Option<Arc<GpuBuffer>>,
+        difference: Option<Arc<GpuBuffer>>,
+    ) -> Result<Self, Error> {
+        let board = create_copy(pipeline.device(), &buffer)?;
+        let counts = counts
+            .map(|counts| create_copy(pipeline.device(), &counts).map(|copy| (counts, copy)))
+            .transpose()?;
+        let descriptor = create_descriptor(
+            pipeline,
+            board.clone(),
+            counts.as_ref().map(|(_, copy)| copy.clone()),
+            difference,
+        )?;
+        Ok(Self {
+            copies: iter::once((buffer, board)).chain(counts).collect(),
+            descriptor,
+        })
+    }
+
+    /// Returns the board that is copied.
+    fn source(&self) -> Arc<GpuBuffer> {
+        self.copies[0].0.clone()
+    }
+
+    /// Returns neighbour counts of the board that are copied.
+    fn counts(&self) -> Option<Arc<GpuBuffer>> {
+        self.copies.get(1).map(|(counts, _)| counts.clone())
+    }
+}
+
 /// This struct represents a pipeline that can be used to
 /// present the game of life.
+///
+/// It draws snapshots of the boards taken by [`Presenter::snapshot`],
+/// so generations can be computed while previous ones are drawn.
 pub struct Presenter {
     camera: Camera,
+    samples: SampleCount,
+    /// Format of images drawn into, the one of the swapchain.
+    format: Format,
+    background: [u8; 3],
+    palette: PaletteColors,
+    /// Multisampled attachments by their sizes, one for every size of images drawn into.
+    multisampled: Vec<([u32; 2], Arc<ImageView<AttachmentImage>>)>,
+    framebuffers: Vec<(Arc<dyn ImageViewAbstract>, Arc<Framebuffer>)>,
+    draw_commands: Option<(shader::ty::Camera, [f32; 2], Arc<DrawCommands>)>,
     pipeline: Arc<GraphicsPipeline>,
-    descriptor: Arc<PersistentDescriptorSet>,
+    board: Snapshot,
+    /// Board drawn in the right half of the window, next to the main board.
+    comparison: Option<Snapshot>,
+    /// Commands copying the boards into their snapshots, recorded again when boards change.
+    snapshot_commands: Option<Arc<CommandBuffer>>,
+    /// Whether alive cells are colored by their neighbour counts.
+    neighbour_colors: bool,
+    /// Cells of the main board which differ from another board are highlighted.
+    difference: Option<Arc<GpuBuffer>>,
+    /// Fraction of a cell left as a gap around cells other than dead.
+    cell_padding: f32,
+    /// Whether the board is drawn with [`PaletteColors::inverted`] colors.
+    inverted: bool,
+    /// Highest state of cells drawn, cells in later states are drawn as dead ones.
+    state_threshold: u32,
+    shading: CellShading,
+    /// Width in device pixels and color of the border drawn along the edges of the board,
+    /// no border is drawn when the width is 0.
+    border: (u32, [u8; 3]),
 }
 
 impl Presenter {
@@ -55,78 +205,188 @@ impl Presenter {
     ///
     /// # Panics
     ///
+    /// - when [`Presenter::try_new`] fails.
+    #[must_use]
+    pub fn new(
+        renderer: &VulkanoWindowRenderer,
+        buffer: Arc<GpuBuffer>,
+        layout: BoardLayout,
+        samples: SampleCount,
+    ) -> Self {
+        Self::try_new(renderer, buffer, layout, samples).expect("Cannot create presenter")
+    }
+
+    /// Creates a new [`Presenter`] pipeline.
+    ///
+    /// It creates new [`GraphicsPipeline`] and [`PersistentDescriptorSet`].
+    ///
+    /// # Errors
+    ///
     /// - when the underlying Vulkano struct creations fail.
     /// - when the shader entry point is not found.
     /// - when the descriptor set creation fails.
     /// - when the pipeline creation fails.
     /// - when the pipeline layout creation fails.
-    #[must_use]
-    pub fn new(renderer: &VulkanoWindowRenderer, buffer: Arc<GpuBuffer>, size: (u32, u32)) -> Self {
+    pub fn try_new(
+        renderer: &VulkanoWindowRenderer,
+        buffer: Arc<GpuBuffer>,
+        layout: BoardLayout,
+        samples: SampleCount,
+    ) -> Result<Self, Error> {
         let device = renderer.graphics_queue().device().clone();
 
-        let render_pass = vulkano::single_pass_renderpass!(
-            device.clone(),
-            attachments: {
-                color: {
-                    load: Clear,
-                    store: Store,
-                    format: renderer.swapchain_format(),
-                    samples: 1,
-                }
-            },
-            pass: {
-                color: [color],
-                depth_stencil: {}
-            }
-        )
-        .expect("Cannot create render pass");
-        let subpass = Subpass::from(render_pass, 0).expect("Cannot create subpass");
-
-        let vs = shader::load_vertex(device.clone()).expect("Cannot load vertex shader");
-        let fs = shader::load_fragment(device.clone()).expect("Cannot load fragment shader");
-        let pipeline = GraphicsPipeline::start()
-            .render_pass(subpass)
-            .input_assembly_state(
-                InputAssemblyState::new().topology(PrimitiveTopology::TriangleStrip),
-            )
-            .vertex_shader(
-                vs.entry_point("main").expect("Cannot find entry point"),
-                (),
-            )
-            .viewport_state(ViewportState::viewport_dynamic_scissor_irrelevant())
-            .fragment_shader(
-                fs.entry_point("main").expect("Cannot find entry point"),
-                shader::FragmentSpecializationConstants {
-                    width: size.0,
-                    height: size.1,
-                },
-            )
-            .build(device)
-            .expect("Cannot create graphics pipeline");
-
-        let layout = pipeline
-            .layout()
-            .set_layouts()
-            .get(0)
-            .expect("Cannot get descriptor set layout");
-        let descriptor =
-            PersistentDescriptorSet::new(layout.clone(), [WriteDescriptorSet::buffer(0, buffer)])
-                .expect("Cannot create descriptor set");
-
-        Self {
-            camera: Camera::new(size, renderer.window().inner_size()),
+        let render_pass = create_render_pass(renderer, samples)?;
+        let subpass = Subpass::from(render_pass, 0).ok_or(Error::MissingSubpass)?;
+        let pipeline = create_pipeline(device, subpass, samples, layout)?;
+
+        let board = Snapshot::try_new(&pipeline, buffer, None, None)?;
+
+        Ok(Self {
+            camera: Camera::new(layout.size(), renderer.window().inner_size()),
+            samples,
+            format: renderer.swapchain_format(),
+            background: DEFAULT_BACKGROUND,
+            palette: Palette::default().colors(),
+            multisampled: Vec::new(),
+            framebuffers: Vec::new(),
+            draw_commands: None,
             pipeline,
-            descriptor,
-        }
+            board,
+            comparison: None,
+            snapshot_commands: None,
+            neighbour_colors: false,
+            difference: None,
+            cell_padding: 0.0,
+            inverted: false,
+            state_threshold: u32::MAX,
+            shading: CellShading::default(),
+            border: (0, DEFAULT_BORDER_COLOR),
+        })
     }
 
     /// Updates the camera.
     pub fn update(&mut self, event: &WindowEvent) {
+        if let WindowEvent::Resized(_) | WindowEvent::ScaleFactorChanged { .. } = event {
+            self.clear_framebuffers();
+        }
         self.camera.update(event);
     }
 
+    /// Draws given board instead of the current one, for example after the board was expanded,
+    /// see [`Presenter::try_set_board`].
+    ///
+    /// # Panics
+    ///
+    /// - when [`Presenter::try_set_board`] fails.
+    pub fn set_board(&mut self, buffer: Arc<GpuBuffer>, layout: BoardLayout) {
+        self.try_set_board(buffer, layout)
+            .expect("Cannot change board of presenter");
+    }
+
+    /// Draws given board instead of the current one, for example after the board was expanded.
+    /// The pipeline is created again for the new layout while colors, drawing settings
+    /// and the camera are kept, the camera follows the new size of the board.
+    /// Neighbour counts, the comparison and the difference are dropped and have to be set again.
+    /// Nothing changes when it fails.
+    ///
+    /// # Errors
+    ///
+    /// - when the shader entry point is not found.
+    /// - when the pipeline creation fails.
+    /// - when the snapshot creation fails.
+    pub fn try_set_board(
+        &mut self,
+        buffer: Arc<GpuBuffer>,
+        layout: BoardLayout,
+    ) -> Result<(), Error> {
+        let subpass = match self.pipeline.render_pass() {
+            PipelineRenderPassType::BeginRenderPass(subpass) => subpass.clone(),
+            PipelineRenderPassType::BeginRendering(_) => unreachable!(),
+        };
+        let pipeline = create_pipeline(
+            self.pipeline.device().clone(),
+            subpass,
+            self.samples,
+            layout,
+        )?;
+        self.board = Snapshot::try_new(&pipeline, buffer, None, None)?;
+        self.pipeline = pipeline;
+        self.comparison = None;
+        self.neighbour_colors = false;
+        self.difference = None;
+        self.draw_commands = None;
+        self.snapshot_commands = None;
+        self.camera.set_views(1);
+        self.camera.set_game_size(layout.size());
+        Ok(())
+    }
+
+    /// Forgets cached framebuffers, it has to be called whenever the swapchain is recreated
+    /// so framebuffers of its old images do not keep them alive.
+    #[inline]
+    pub fn clear_framebuffers(&mut self) {
+        self.framebuffers.clear();
+    }
+
     /// Creates a new [`PrimaryAutoCommandBuffer`] that can be used to
-    /// present the game of life.
+    /// present the game of life in the current swapchain image, see [`Presenter::draw_to`].
+    /// The camera follows the size of the swapchain image.
+    ///
+    /// # Panics
+    ///
+    /// - when [`Presenter::draw_to`] fails.
+    #[must_use]
+    pub fn draw(
+        &mut self,
+        renderer: &VulkanoWindowRenderer,
+        grid: Option<u32>,
+        states: u32,
+    ) -> CommandBuffer {
+        // The swapchain may not match the window yet, the board is drawn into the image.
+        let [width, height] = renderer.swapchain_image_size();
+        self.camera
+            .set_screen_size(PhysicalSize::new(width, height));
+        self.draw_to(
+            &renderer.graphics_queue(),
+            renderer.swapchain_image_view(),
+            grid,
+            states,
+        )
+    }
+
+    /// Creates an image the board can be drawn into with [`Presenter::draw_to`]
+    /// instead of the swapchain, so it can be shown by another application,
+    /// for example registered as an egui texture.
+    ///
+    /// # Panics
+    ///
+    /// - when the image creation fails.
+    /// - when the image view creation fails.
+    #[must_use]
+    pub fn create_offscreen_image(&self, size: [u32; 2]) -> Arc<ImageView<AttachmentImage>> {
+        let usage = ImageUsage {
+            color_attachment: true,
+            sampled: true,
+            transfer_src: true,
+            ..ImageUsage::empty()
+        };
+        ImageView::new_default(
+            AttachmentImage::with_usage(self.pipeline.device().clone(), size, self.format, usage)
+                .expect("Failed to create offscreen image"),
+        )
+        .expect("Failed to create offscreen image view")
+    }
+
+    /// Creates a new [`PrimaryAutoCommandBuffer`] drawing the game of life into `target`,
+    /// which has to have the format of the swapchain, as images from
+    /// [`Presenter::create_offscreen_image`] do.
+    /// The board is drawn for the size of the target image, with the zoom and position
+    /// of the camera, which itself is left unchanged.
+    /// When `grid` is given, grid lines are drawn every that many cells
+    /// and between all cells once they are big enough.
+    ///
+    /// Framebuffers are cached for every target, so the same images should be drawn into
+    /// every frame rather than new ones.
     ///
     /// # Panics
     ///
@@ -138,24 +398,57 @@ impl Presenter {
     /// - when the command buffer execution fails.
     /// - when the render pass end fails.
     #[must_use]
-    pub fn draw(&self, renderer: &VulkanoWindowRenderer, draw_grid: bool) -> CommandBuffer {
-        let render_pass = match self.pipeline.render_pass() {
-            PipelineRenderPassType::BeginRenderPass(value) => value.render_pass(),
-            PipelineRenderPassType::BeginRendering(_) => unreachable!(),
+    pub fn draw_to(
+        &mut self,
+        queue: &Arc<Queue>,
+        target: Arc<dyn ImageViewAbstract>,
+        grid: Option<u32>,
+        states: u32,
+    ) -> CommandBuffer {
+        let palette = PaletteColors {
+            background: self.background,
+            ..self.palette
         };
+        let palette = if self.inverted {
+            palette.inverted()
+        } else {
+            palette
+        };
+        let mut clear_values = vec![Some(clear_color(palette.background).into())];
+        if self.samples != SampleCount::Sample1 {
+            clear_values.push(None);
+        }
+        let [width, height] = target.image().dimensions().width_height();
+        let framebuffer = self.framebuffer(target);
+        let extent = PhysicalSize::new(width, height);
+        let mut target_camera = self.camera.clone();
+        target_camera.set_screen_size(extent);
 
-        let framebuffer = Framebuffer::new(
-            render_pass.clone(),
-            FramebufferCreateInfo {
-                attachments: vec![renderer.swapchain_image_view()],
-                ..Default::default()
-            },
-        )
-        .expect("Failed to create framebuffer");
+        let camera = shader::ty::Camera {
+            matrix: target_camera.matrix().to_cols_array_2d(),
+            drawGrid: grid.is_some().into(),
+            states,
+            position: target_camera
+                .cursor_game_position()
+                .unwrap_or([u32::MAX; 2]),
+            pixelSize: target_camera.pixel_size(),
+            neighbourColors: self.neighbour_colors.into(),
+            aliveColor: pack_color(palette.alive),
+            deadColor: pack_color(palette.dead),
+            gridColor: pack_color(palette.grid),
+            highlightDifference: self.difference.is_some().into(),
+            gridInterval: grid.unwrap_or(1).max(1),
+            cellPadding: self.cell_padding,
+            stateThreshold: self.state_threshold,
+            shading: self.shading.id(),
+            border: pack_border(self.border),
+        };
+        let dimensions = extent.into();
+        let commands = self.draw_commands(queue, camera, dimensions);
 
         let mut builder = AutoCommandBufferBuilder::primary(
             self.pipeline.device().clone(),
-            renderer.graphics_queue().queue_family_index(),
+            queue.queue_family_index(),
             CommandBufferUsage::OneTimeSubmit,
         )
         .expect("Failed to create command buffer builder");
@@ -163,49 +456,531 @@ impl Presenter {
         builder
             .begin_render_pass(
                 RenderPassBeginInfo {
-                    clear_values: vec![Some([0.5, 0.5, 0.5, 0.5].into())],
+                    clear_values,
                     ..RenderPassBeginInfo::framebuffer(framebuffer)
                 },
-                SubpassContents::Inline,
+                SubpassContents::SecondaryCommandBuffers,
             )
             .expect("Failed to begin render pass")
-            .set_viewport(
-                0,
-                [Viewport {
-                    origin: [0.0, 0.0],
-                    dimensions: renderer.surface().window().inner_size().into(),
-                    depth_range: 0.0..1.0,
-                }],
-            )
-            .push_constants(
-                self.pipeline.layout().clone(),
-                0,
-                shader::ty::Camera {
-                    matrix: self.camera.matrix().to_cols_array_2d(),
-                    drawGrid: draw_grid.into(),
-                    position: self.camera.cursor_game_position(),
-                    _dummy0: [0; 4],
-                },
-            )
-            .bind_pipeline_graphics(self.pipeline.clone())
-            .bind_descriptor_sets(
-                PipelineBindPoint::Graphics,
-                self.pipeline.layout().clone(),
-                0,
-                self.descriptor.clone(),
-            )
-            .draw(4, 1, 0, 0)
-            .expect("Failed to draw")
+            .execute_commands(commands)
+            .expect("Failed to execute draw commands")
             .end_render_pass()
             .expect("Failed to end render pass");
 
         builder.build().expect("Failed to build command buffer")
     }
 
+    /// Returns secondary command buffer drawing the board.
+    /// The commands are recorded again only when the camera or the viewport changes,
+    /// otherwise the previously recorded buffer is reused.
+    ///
+    /// # Panics
+    ///
+    /// - when the command buffer builder creation fails.
+    /// - when the command buffer recording fails.
+    /// - when the command buffer building fails.
+    fn draw_commands(
+        &mut self,
+        queue: &Arc<Queue>,
+        camera: shader::ty::Camera,
+        dimensions: [f32; 2],
+    ) -> Arc<DrawCommands> {
+        if let Some((cached_camera, cached_dimensions, commands)) = &self.draw_commands {
+            if bytemuck::bytes_of(cached_camera) == bytemuck::bytes_of(&camera)
+                && *cached_dimensions == dimensions
+            {
+                return commands.clone();
+            }
+        }
+
+        let subpass = match self.pipeline.render_pass() {
+            PipelineRenderPassType::BeginRenderPass(subpass) => subpass.clone(),
+            PipelineRenderPassType::BeginRendering(_) => unreachable!(),
+        };
+
+        let mut builder = AutoCommandBufferBuilder::secondary(
+            self.pipeline.device().clone(),
+            queue.queue_family_index(),
+            CommandBufferUsage::SimultaneousUse,
+            CommandBufferInheritanceInfo {
+                render_pass: Some(subpass.into()),
+                ..Default::default()
+            },
+        )
+        .expect("Failed to create command buffer builder");
+
+        builder
+            .push_constants(self.pipeline.layout().clone(), 0, camera)
+            .bind_pipeline_graphics(self.pipeline.clone());
+
+        let views = [Some(&self.board), self.comparison.as_ref()]
+            .map(|snapshot| snapshot.map(|snapshot| &snapshot.descriptor));
+        let width = if self.comparison.is_some() {
+            dimensions[0] / 2.0
+        } else {
+            dimensions[0]
+        };
+        let mut left = 0.0;
+        for descriptor in views.into_iter().flatten() {
+            builder
+                .set_viewport(
+                    0,
+                    [Viewport {
+                        origin: [left, 0.0],
+                        dimensions: [width, dimensions[1]],
+                        depth_range: 0.0..1.0,
+                    }],
+                )
+                .bind_descriptor_sets(
+                    PipelineBindPoint::Graphics,
+                    self.pipeline.layout().clone(),
+                    0,
+                    descriptor.clone(),
+                )
+                .draw(4, 1, 0, 0)
+                .expect("Failed to draw");
+            left += width;
+        }
+
+        let commands = Arc::new(builder.build().expect("Failed to build command buffer"));
+        self.draw_commands = Some((camera, dimensions, commands.clone()));
+        commands
+    }
+
+    /// Returns framebuffer targeting given image.
+    /// Framebuffers are cached per image view and created only on a cache miss.
+    ///
+    /// # Panics
+    ///
+    /// - when the framebuffer creation fails.
+    fn framebuffer(&mut self, image: Arc<dyn ImageViewAbstract>) -> Arc<Framebuffer> {
+        let cached = self
+            .framebuffers
+            .iter()
+            .find(|(view, _)| Arc::as_ptr(view).cast::<()>() == Arc::as_ptr(&image).cast::<()>());
+        if let Some((_, framebuffer)) = cached {
+            return framebuffer.clone();
+        }
+        // Cached images are held by the cache and by their framebuffers, images nobody else holds
+        // are not drawn into anymore, like those of a swapchain recreated by the renderer itself.
+        self.framebuffers
+            .retain(|(view, _)| Arc::strong_count(view) > 2);
+        // Multisampled images are then held only by the cache when no framebuffer uses them.
+        self.multisampled
+            .retain(|(_, image)| Arc::strong_count(image) > 1);
+
+        let mut attachments: Vec<Arc<dyn ImageViewAbstract>> = Vec::with_capacity(2);
+        if self.samples != SampleCount::Sample1 {
+            let dimensions = image.image().dimensions().width_height();
+            attachments.push(self.multisampled_image(dimensions));
+        }
+        attachments.push(image.clone());
+
+        let render_pass = match self.pipeline.render_pass() {
+            PipelineRenderPassType::BeginRenderPass(value) => value.render_pass(),
+            PipelineRenderPassType::BeginRendering(_) => unreachable!(),
+        };
+
+        let framebuffer = Framebuffer::new(
+            render_pass.clone(),
+            FramebufferCreateInfo {
+                attachments,
+                ..Default::default()
+            },
+        )
+        .expect("Failed to create framebuffer");
+
+        self.framebuffers.push((image, framebuffer.clone()));
+        framebuffer
+    }
+
+    /// Returns multisampled image of given size.
+    /// Images are cached per size and created only on a cache miss,
+    /// so targets of different sizes do not replace each other's image.
+    ///
+    /// # Panics
+    ///
+    /// - when the image creation fails.
+    /// - when the image view creation fails.
+    fn multisampled_image(&mut self, dimensions: [u32; 2]) -> Arc<ImageView<AttachmentImage>> {
+        let cached = self
+            .multisampled
+            .iter()
+            .find(|(size, _)| *size == dimensions);
+        if let Some((_, image)) = cached {
+            return image.clone();
+        }
+        let image = ImageView::new_default(
+            AttachmentImage::transient_multisampled(
+                self.pipeline.device().clone(),
+                dimensions,
+                self.samples,
+                self.format,
+            )
+            .expect("Failed to create multisampled image"),
+        )
+        .expect("Failed to create multisampled image view");
+        self.multisampled.push((dimensions, image.clone()));
+        image
+    }
+
     /// Returns the camera.
     #[inline]
     #[must_use]
     pub fn camera(&self) -> &Camera {
         &self.camera
     }
+
+    /// Returns the mutable camera.
+    #[inline]
+    #[must_use]
+    pub fn camera_mut(&mut self) -> &mut Camera {
+        &mut self.camera
+    }
+
+    /// Returns number of samples used for anti-aliasing.
+    #[inline]
+    #[must_use]
+    pub fn samples(&self) -> SampleCount {
+        self.samples
+    }
+
+    /// Draws given board of the same layout in the right half of the window,
+    /// next to the main board, or only the main board when `buffer` is `None`.
+    /// The camera is split into two views showing the same part of both boards.
+    /// Neighbour counts of the board are given by `counts`, see [`Presenter::set_neighbour_counts`].
+    ///
+    /// # Panics
+    ///
+    /// - when the pipeline has no descriptor set layout.
+    /// - when the descriptor set creation fails.
+    pub fn set_comparison(
+        &mut self,
+        buffer: Option<Arc<GpuBuffer>>,
+        counts: Option<Arc<GpuBuffer>>,
+    ) {
+        self.comparison = buffer.map(|buffer| {
+            Snapshot::try_new(&self.pipeline, buffer, counts, None).expect("Cannot create snapshot")
+        });
+        self.camera
+            .set_views(if self.comparison.is_some() { 2 } else { 1 });
+        self.draw_commands = None;
+        self.snapshot_commands = None;
+    }
+
+    /// Colors alive cells of the main board by numbers of their live neighbours
+    /// read from `counts`, see [`crate::Simulation::neighbour_counts`],
+    /// or draws them plain when `counts` is `None`.
+    /// Counts of the compared board have to be given to [`Presenter::set_comparison`].
+    ///
+    /// # Panics
+    ///
+    /// - when the pipeline has no descriptor set layout.
+    /// - when the descriptor set creation fails.
+    pub fn set_neighbour_counts(&mut self, counts: Option<Arc<GpuBuffer>>) {
+        self.neighbour_colors = counts.is_some();
+        self.board = Snapshot::try_new(
+            &self.pipeline,
+            self.board.source(),
+            counts,
+            self.difference.clone(),
+        )
+        .expect("Cannot create snapshot");
+        self.draw_commands = None;
+        self.snapshot_commands = None;
+    }
+
+    /// Highlights cells of the main board which are non-zero in `difference`,
+    /// see [`crate::Differ`], or stops highlighting when it is `None`.
+    /// The difference is read as it is, it is not part of the snapshots.
+    ///
+    /// # Panics
+    ///
+    /// - when the pipeline has no descriptor set layout.
+    /// - when the descriptor set creation fails.
+    pub fn set_difference(&mut self, difference: Option<Arc<GpuBuffer>>) {
+        self.difference = difference;
+        self.board = Snapshot::try_new(
+            &self.pipeline,
+            self.board.source(),
+            self.board.counts(),
+            self.difference.clone(),
+        )
+        .expect("Cannot create snapshot");
+        self.draw_commands = None;
+        self.snapshot_commands = None;
+    }
+
+    /// Returns whether cells differing from another board are highlighted.
+    #[inline]
+    #[must_use]
+    pub fn has_difference(&self) -> bool {
+        self.difference.is_some()
+    }
+
+    /// Copies the boards and their neighbour counts into the snapshots that are drawn.
+    /// Snapshots stay drawn until it is called again, so the boards can be written meanwhile.
+    /// It has to be called after the boards change, when nothing is writing them.
+    /// Returns a new [`GpuFuture`] that can be used to wait for the copy to finish.
+    ///
+    /// # Panics
+    ///
+    /// - when the command buffer recording fails.
+    /// - when the command buffer execution fails.
+    #[must_use]
+    pub fn snapshot(&mut self, renderer: &VulkanoWindowRenderer) -> Box<dyn GpuFuture> {
+        let queue = renderer.graphics_queue();
+        let commands = match &self.snapshot_commands {
+            Some(commands) => commands.clone(),
+            None => {
+                let mut builder = AutoCommandBufferBuilder::primary(
+                    self.pipeline.device().clone(),
+                    queue.queue_family_index(),
+                    CommandBufferUsage::MultipleSubmit,
+                )
+                .expect("Failed to create command buffer builder");
+                let snapshots = iter::once(&self.board).chain(&self.comparison);
+                for (source, copy) in snapshots.flat_map(|snapshot| &snapshot.copies) {
+                    builder
+                        .copy_buffer(CopyBufferInfo::buffers(source.clone(), copy.clone()))
+                        .expect("Failed to record copy");
+                }
+                let commands = Arc::new(builder.build().expect("Failed to build command buffer"));
+                self.snapshot_commands = Some(commands.clone());
+                commands
+            }
+        };
+        commands
+            .execute(queue)
+            .expect("Failed to execute command buffer")
+            .boxed()
+    }
+
+    /// Returns color the screen is cleared with, in sRGB.
+    #[inline]
+    #[must_use]
+    pub fn background(&self) -> [u8; 3] {
+        self.background
+    }
+
+    /// Changes color the screen is cleared with, in sRGB.
+    #[inline]
+    pub fn set_background(&mut self, background: [u8; 3]) {
+        self.background = background;
+    }
+
+    /// Changes fraction of a cell left as a gap around cells other than dead,
+    /// from 0 for solid cells to 0.5 for cells of half the size.
+    #[inline]
+    pub fn set_cell_padding(&mut self, padding: f32) {
+        self.cell_padding = padding.clamp(0.0, MAX_CELL_PADDING);
+    }
+
+    /// Returns colors the board is drawn with.
+    #[inline]
+    #[must_use]
+    pub fn palette(&self) -> PaletteColors {
+        self.palette
+    }
+
+    /// Changes colors the board is drawn with, including the background.
+    #[inline]
+    pub fn set_palette(&mut self, palette: PaletteColors) {
+        self.palette = palette;
+        self.background = palette.background;
+    }
+
+    /// Sets whether the board is drawn with inverted colors,
+    /// the palette and the background themselves are left unchanged.
+    #[inline]
+    pub fn set_inverted(&mut self, inverted: bool) {
+        self.inverted = inverted;
+    }
+
+    /// Changes the highest state of cells that is drawn, cells in later states are drawn
+    /// as dead ones. It is clamped to states of the rule, alive cells are always drawn.
+    #[inline]
+    pub fn set_state_threshold(&mut self, threshold: u32) {
+        self.state_threshold = threshold;
+    }
+
+    /// Changes how states of dying cells are mapped to their brightness.
+    #[inline]
+    pub fn set_shading(&mut self, shading: CellShading) {
+        self.shading = shading;
+    }
+
+    /// Changes width in device pixels and color of the border drawn along the edges
+    /// of the board, so it is visible where bounded boards end.
+    /// The width is clamped to [`MAX_BORDER_WIDTH`], no border is drawn when it is 0.
+    #[inline]
+    pub fn set_border(&mut self, width: u32, color: [u8; 3]) {
+        self.border = (width.min(MAX_BORDER_WIDTH), color);
+    }
+}
+
+/// Creates the pipeline drawing boards of given layout in given subpass.
+///
+/// # Errors
+///
+/// - when the shader loading fails.
+/// - when the shader entry point is not found.
+/// - when the pipeline creation fails.
+fn create_pipeline(
+    device: Arc<Device>,
+    subpass: Subpass,
+    samples: SampleCount,
+    layout: BoardLayout,
+) -> Result<Arc<GraphicsPipeline>, Error> {
+    let vs = shader::load_vertex(device.clone())?;
+    let fs = shader::load_fragment(device.clone())?;
+    Ok(GraphicsPipeline::start()
+        .render_pass(subpass)
+        .input_assembly_state(InputAssemblyState::new().topology(PrimitiveTopology::TriangleStrip))
+        .vertex_shader(vs.entry_point("main").ok_or(Error::MissingEntryPoint)?, ())
+        .viewport_state(ViewportState::viewport_dynamic_scissor_irrelevant())
+        .multisample_state(MultisampleState {
+            rasterization_samples: samples,
+            ..MultisampleState::default()
+        })
+        .fragment_shader(
+            fs.entry_point("main").ok_or(Error::MissingEntryPoint)?,
+            shader::FragmentSpecializationConstants {
+                width: layout.size().0,
+                height: layout.size().1,
+                cellBits: layout.cell_bits(),
+            },
+        )
+        .build(device)?)
+}
+
+/// Creates a buffer of the same size as `source` that it can be copied into.
+///
+/// # Errors
+///
+/// - when the buffer allocation fails.
+fn create_copy(device: &Arc<Device>, source: &GpuBuffer) -> Result<Arc<GpuBuffer>, Error> {
+    let buffer = DeviceLocalBuffer::array(
+        device.clone(),
+        source.len(),
+        BufferUsage {
+            storage_buffer: true,
+            transfer_dst: true,
+            ..BufferUsage::empty()
+        },
+        device.active_queue_family_indices().iter().copied(),
+    )?;
+    Ok(buffer)
+}
+
+/// Creates a descriptor set binding the board, its neighbour counts and its difference.
+///
+/// # Errors
+///
+/// - when the pipeline has no descriptor set layout.
+/// - when the descriptor set creation fails.
+fn create_descriptor(
+    pipeline: &GraphicsPipeline,
+    buffer: Arc<GpuBuffer>,
+    counts: Option<Arc<GpuBuffer>>,
+    difference: Option<Arc<GpuBuffer>>,
+) -> Result<Arc<PersistentDescriptorSet>, Error> {
+    let layout = pipeline
+        .layout()
+        .set_layouts()
+        .get(0)
+        .ok_or(Error::MissingDescriptorSetLayout)?;
+    let descriptor = PersistentDescriptorSet::new(
+        layout.clone(),
+        [
+            WriteDescriptorSet::buffer(0, buffer.clone()),
+            // Counts and the difference are only read when enabled,
+            // otherwise any buffer can take the binding.
+            WriteDescriptorSet::buffer(1, counts.unwrap_or_else(|| buffer.clone())),
+            WriteDescriptorSet::buffer(2, difference.unwrap_or(buffer)),
+        ],
+    )?;
+    Ok(descriptor)
+}
+
+/// Packs an sRGB color into a word read by the shader with `unpackUnorm4x8`.
+fn pack_color([r, g, b]: [u8; 3]) -> u32 {
+    u32::from_le_bytes([r, g, b, u8::MAX])
+}
+
+/// Packs an sRGB color of the border together with its width in place of the alpha,
+/// so the push constants stay within the 128 bytes every device supports.
+#[allow(clippy::cast_possible_truncation)]
+fn pack_border((width, [r, g, b]): (u32, [u8; 3])) -> u32 {
+    u32::from_le_bytes([r, g, b, width.min(MAX_BORDER_WIDTH) as u8])
+}
+
+/// Converts an opaque sRGB color into linear clear value of the sRGB swapchain image.
+fn clear_color(rgb: [u8; 3]) -> [f32; 4] {
+    let [r, g, b] = rgb.map(|channel| {
+        let value = f32::from(channel) / 255.0;
+        if value <= 0.040_45 {
+            value / 12.92
+        } else {
+            ((value + 0.055) / 1.055).powf(2.4)
+        }
+    });
+    [r, g, b, 1.0]
+}
+
+/// Creates a render pass drawing into the swapchain image.
+///
+/// When multisampling is enabled the pass draws into a transient
+/// multisampled attachment which is then resolved into the swapchain image.
+///
+/// # Errors
+///
+/// - when the render pass creation fails.
+fn create_render_pass(
+    renderer: &VulkanoWindowRenderer,
+    samples: SampleCount,
+) -> Result<Arc<RenderPass>, Error> {
+    let device = renderer.graphics_queue().device().clone();
+
+    let render_pass = if samples == SampleCount::Sample1 {
+        vulkano::single_pass_renderpass!(
+            device,
+            attachments: {
+                color: {
+                    load: Clear,
+                    store: Store,
+                    format: renderer.swapchain_format(),
+                    samples: 1,
+                }
+            },
+            pass: {
+                color: [color],
+                depth_stencil: {}
+            }
+        )
+    } else {
+        vulkano::single_pass_renderpass!(
+            device,
+            attachments: {
+                multisampled: {
+                    load: Clear,
+                    store: DontCare,
+                    format: renderer.swapchain_format(),
+                    samples: samples,
+                },
+                color: {
+                    load: DontCare,
+                    store: Store,
+                    format: renderer.swapchain_format(),
+                    samples: 1,
+                }
+            },
+            pass: {
+                color: [multisampled],
+                depth_stencil: {},
+                resolve: [color]
+            }
+        )
+    };
+    Ok(render_pass?)
 }
@@ -8,13 +8,14 @@ use vulkano::{
     sync::GpuFuture,
 };
 
-use crate::GpuBuffer;
+use crate::{BoardLayout, Error, GpuBuffer};
 
 /// This module contains compiled compute shader and shader data structures.
 mod shader {
     vulkano_shaders::shader! {
         ty: "compute",
         path: "src/shaders/randomizer.comp",
+        include: ["src/shaders"],
         types_meta: {
             use bytemuck::{Pod, Zeroable};
             #[derive(Clone, Copy, Pod, Zeroable)]
@@ -22,6 +23,73 @@ mod shader {
     }
 }
 
+/// Symmetry of the randomized board.
+/// Mirrored cells share the same random state.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Symmetry {
+    #[default]
+    None,
+    Vertical,
+    Horizontal,
+    Both,
+}
+
+impl Symmetry {
+    /// All symmetry modes in order they should be displayed.
+    pub const ALL: [Self; 4] = [Self::None, Self::Vertical, Self::Horizontal, Self::Both];
+
+    /// Returns human readable name of the symmetry.
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::None => "None",
+            Self::Vertical => "Vertical axis",
+            Self::Horizontal => "Horizontal axis",
+            Self::Both => "Both axes",
+        }
+    }
+
+    /// Returns symmetry as bit flags understood by the shader.
+    /// First bit mirrors across the vertical axis, second across the horizontal axis.
+    fn bits(self) -> u32 {
+        match self {
+            Self::None => 0,
+            Self::Vertical => 1,
+            Self::Horizontal => 2,
+            Self::Both => 3,
+        }
+    }
+}
+
+/// Smallest and largest frequency of the noise distribution, in noise features per cell.
+pub(crate) const MIN_NOISE_FREQUENCY: f32 = 0.005;
+pub(crate) const MAX_NOISE_FREQUENCY: f32 = 0.5;
+
+/// Frequency of the noise distribution until it is changed, clusters span about 20 cells.
+pub(crate) const DEFAULT_NOISE_FREQUENCY: f32 = 0.05;
+
+/// Distribution of alive cells on the randomized board.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Distribution {
+    /// Every cell is alive with probability of one half.
+    #[default]
+    Uniform,
+    /// Cells are alive with probability of one half inside of clusters where value noise
+    /// with given `frequency` is above a threshold, clusters cover about `density` of the board.
+    Noise { frequency: f32, density: f32 },
+}
+
+impl Distribution {
+    /// Returns the distribution as push constants understood by the shader:
+    /// mode, frequency and density.
+    fn constants(self) -> (u32, f32, f32) {
+        match self {
+            Self::Uniform => (0, 0.0, 1.0),
+            Self::Noise { frequency, density } => (1, frequency, density),
+        }
+    }
+}
+
 /// This struct represents a pipeline that can be used to
 /// randomize the board of the game of life.
 pub struct Randomizer {
@@ -38,53 +106,72 @@ impl Randomizer {
     ///
     /// # Panics
     ///
+    /// - when [`Randomizer::try_new`] fails.
+    #[must_use]
+    pub fn new(
+        compute_queue: Arc<Queue>,
+        output: Arc<GpuBuffer>,
+        layout: BoardLayout,
+        workgroup: u32,
+    ) -> Self {
+        Self::try_new(compute_queue, output, layout, workgroup).expect("Cannot create randomizer")
+    }
+
+    /// Creates a new [`Randomizer`] pipeline.
+    ///
+    /// It creates new [`ComputePipeline`] and [`PersistentDescriptorSet`].
+    ///
+    /// # Errors
+    ///
     /// - when the underlying Vulkano struct creations fail.
     /// - when the shader entry point is not found.
     /// - when the descriptor set creation fails.
-    #[must_use]
-    pub fn new(compute_queue: Arc<Queue>, output: Arc<GpuBuffer>, size: (u32, u32)) -> Self {
+    pub fn try_new(
+        compute_queue: Arc<Queue>,
+        output: Arc<GpuBuffer>,
+        layout: BoardLayout,
+        workgroup: u32,
+    ) -> Result<Self, Error> {
         let device = compute_queue.device().clone();
 
-        let shader = shader::load(device.clone()).expect("Cannot load compute shader");
+        let shader = shader::load(device.clone())?;
         let pipeline = ComputePipeline::new(
             device,
-            shader.entry_point("main").expect("Cannot find entry point"),
+            shader.entry_point("main").ok_or(Error::MissingEntryPoint)?,
             &shader::SpecializationConstants {
-                width: size.0,
-                height: size.1,
+                width: layout.size().0,
+                height: layout.size().1,
+                cellBits: layout.cell_bits(),
+                constant_100: workgroup,
+                constant_101: workgroup,
             },
             None,
             |_| {},
-        )
-        .expect("Cannot create compute pipeline");
+        )?;
 
-        let layout = pipeline
+        let set_layout = pipeline
             .layout()
             .set_layouts()
             .get(0)
-            .expect("Cannot get descriptor set layout");
+            .ok_or(Error::MissingDescriptorSetLayout)?;
 
-        let descriptor =
-            PersistentDescriptorSet::new(layout.clone(), [WriteDescriptorSet::buffer(0, output)])
-                .expect("Cannot create descriptor set");
+        let descriptor = PersistentDescriptorSet::new(
+            set_layout.clone(),
+            [WriteDescriptorSet::buffer(0, output)],
+        )?;
 
-        let mut group_size = [size.0 / 32, size.1 / 32, 1];
-        if size.0 % 32 != 0 {
-            group_size[0] += 1;
-        }
-        if size.1 % 32 != 0 {
-            group_size[1] += 1;
-        }
-
-        Self {
-            group_size,
+        Ok(Self {
+            group_size: layout.group_count(workgroup),
             compute_queue,
             pipeline,
             descriptor,
-        }
+        })
     }
 
     /// Returns a gpu future that can be used to execute the pipeline.
+    /// Generated board is mirrored according to given [`Symmetry`]
+    /// and its cells follow given [`Distribution`].
+    /// With `keep_cells` random alive cells are added to the board instead of replacing it.
     /// The future will be executed on the compute queue.
     ///
     /// # Panics
@@ -94,7 +181,13 @@ impl Randomizer {
     /// - when the command buffer recording fails.
     /// - when the command buffer execution fails.
     #[must_use]
-    pub fn run(&self) -> Box<dyn GpuFuture> {
+    pub fn run(
+        &self,
+        symmetry: Symmetry,
+        distribution: Distribution,
+        keep_cells: bool,
+    ) -> Box<dyn GpuFuture> {
+        let (mode, frequency, density) = distribution.constants();
         let mut builder = AutoCommandBufferBuilder::primary(
             self.compute_queue.device().clone(),
             self.compute_queue.queue_family_index(),
@@ -112,7 +205,14 @@ impl Randomizer {
             .push_constants(
                 self.pipeline.layout().clone(),
                 0,
-                shader::ty::PushConstants { seed: rand::random() },
+                shader::ty::PushConstants {
+                    seed: rand::random(),
+                    symmetry: symmetry.bits(),
+                    keepCells: keep_cells.into(),
+                    distribution: mode,
+                    frequency,
+                    density,
+                },
             )
             .bind_pipeline_compute(self.pipeline.clone())
             .dispatch(self.group_size)
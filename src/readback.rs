@@ -0,0 +1,367 @@
+use std::sync::Arc;
+
+use vulkano::{
+    buffer::{BufferUsage, CpuAccessibleBuffer},
+    command_buffer::{
+        AutoCommandBufferBuilder, BufferCopy, CommandBufferUsage, CopyBufferInfo,
+        PrimaryCommandBuffer,
+    },
+    device::Queue,
+    sync::GpuFuture,
+};
+
+use crate::{vulkan, BoardLayout, CommandBuffer, Error, GpuBuffer};
+
+/// This struct represents a host visible copy of the board.
+///
+/// It keeps a staging buffer and a recorded copy command,
+/// so the board can be read repeatedly without new allocations.
+pub struct Readback {
+    layout: BoardLayout,
+    queue: Arc<Queue>,
+    staging: Arc<CpuAccessibleBuffer<[u32]>>,
+    copy_buffer: Arc<CommandBuffer>,
+}
+
+impl Readback {
+    /// Creates a new [`Readback`] of given buffer.
+    ///
+    /// # Panics
+    ///
+    /// - when [`Readback::try_new`] fails.
+    #[must_use]
+    pub fn new(queue: Arc<Queue>, source: Arc<GpuBuffer>, layout: BoardLayout) -> Self {
+        Self::try_new(queue, source, layout).expect("Cannot create readback")
+    }
+
+    /// Creates a new [`Readback`] of given buffer.
+    ///
+    /// # Errors
+    ///
+    /// - when the staging buffer creation fails.
+    /// - when the command buffer creation fails.
+    /// - when the command buffer recording fails.
+    ///
+    /// # Panics
+    ///
+    /// - when the board does not fit in host memory.
+    pub fn try_new(
+        queue: Arc<Queue>,
+        source: Arc<GpuBuffer>,
+        layout: BoardLayout,
+    ) -> Result<Self, Error> {
+        let device = queue.device().clone();
+        let len = usize::try_from(layout.len()).expect("Board does not fit in memory");
+
+        let staging = CpuAccessibleBuffer::from_iter(
+            device.clone(),
+            BufferUsage {
+                transfer_dst: true,
+                ..BufferUsage::empty()
+            },
+            true,
+            vec![0; len],
+        )?;
+
+        let mut builder = AutoCommandBufferBuilder::primary(
+            device,
+            queue.queue_family_index(),
+            CommandBufferUsage::MultipleSubmit,
+        )?;
+        builder.copy_buffer(CopyBufferInfo::buffers(source, staging.clone()))?;
+
+        Ok(Self {
+            layout,
+            queue,
+            staging,
+            copy_buffer: Arc::new(builder.build()?),
+        })
+    }
+
+    /// Copies the board to host memory and waits for the copy to finish.
+    /// Returns words of the board stored according to [`Readback::layout`].
+    ///
+    /// # Panics
+    ///
+    /// - when [`Readback::copy`] fails.
+    /// - when waiting for the copy fails.
+    /// - when [`Readback::copied`] fails.
+    #[must_use]
+    pub fn read(&self) -> Vec<u32> {
+        vulkan::try_wait(self.copy(), None).expect("Cannot wait for command buffer");
+        self.copied()
+    }
+
+    /// Starts copying the board to host memory, the words are read
+    /// by [`Readback::copied`] once the returned [`GpuFuture`] finishes.
+    ///
+    /// # Panics
+    ///
+    /// - when the command buffer execution fails.
+    #[must_use]
+    pub fn copy(&self) -> Box<dyn GpuFuture> {
+        self.copy_buffer
+            .clone()
+            .execute(self.queue.clone())
+            .expect("Cannot execute command buffer")
+            .boxed()
+    }
+
+    /// Returns words of the board copied by the last finished [`Readback::copy`].
+    ///
+    /// # Panics
+    ///
+    /// - when the staging buffer cannot be read.
+    #[must_use]
+    pub fn copied(&self) -> Vec<u32> {
+        self.staging
+            .read()
+            .expect("Cannot read staging buffer")
+            .to_vec()
+    }
+
+    /// Returns layout of the words returned by [`Readback::read`].
+    #[inline]
+    #[must_use]
+    pub fn layout(&self) -> BoardLayout {
+        self.layout
+    }
+}
+
+/// This struct represents a host visible copy of a single word of the board.
+///
+/// It reads individual cells on demand without copying the whole board.
+pub struct CellProbe {
+    layout: BoardLayout,
+    queue: Arc<Queue>,
+    source: Arc<GpuBuffer>,
+    staging: Arc<CpuAccessibleBuffer<[u32]>>,
+}
+
+impl CellProbe {
+    /// Creates a new [`CellProbe`] of given buffer.
+    ///
+    /// # Panics
+    ///
+    /// - when [`CellProbe::try_new`] fails.
+    #[must_use]
+    pub fn new(queue: Arc<Queue>, source: Arc<GpuBuffer>, layout: BoardLayout) -> Self {
+        Self::try_new(queue, source, layout).expect("Cannot create cell probe")
+    }
+
+    /// Creates a new [`CellProbe`] of given buffer.
+    ///
+    /// # Errors
+    ///
+    /// - when the staging buffer creation fails.
+    pub fn try_new(
+        queue: Arc<Queue>,
+        source: Arc<GpuBuffer>,
+        layout: BoardLayout,
+    ) -> Result<Self, Error> {
+        let staging = CpuAccessibleBuffer::from_iter(
+            queue.device().clone(),
+            BufferUsage {
+                transfer_dst: true,
+                ..BufferUsage::empty()
+            },
+            true,
+            [0],
+        )?;
+
+        Ok(Self {
+            layout,
+            queue,
+            source,
+            staging,
+        })
+    }
+
+    /// Copies the word containing the cell to host memory and waits for the copy to finish.
+    /// Returns value of the cell or `None` when the position is outside of the board.
+    ///
+    /// # Panics
+    ///
+    /// - when [`CellProbe::copy`] fails.
+    /// - when waiting for the copy fails.
+    /// - when [`CellProbe::copied`] fails.
+    #[must_use]
+    pub fn read(&self, position: [u32; 2]) -> Option<u32> {
+        vulkan::try_wait(self.copy(position)?, None).expect("Cannot wait for command buffer");
+        Some(self.copied(position))
+    }
+
+    /// Starts copying the word containing the cell to host memory, the cell is read
+    /// by [`CellProbe::copied`] once the returned [`GpuFuture`] finishes.
+    /// Returns `None` when the position is outside of the board.
+    ///
+    /// # Panics
+    ///
+    /// - when the command buffer creation fails.
+    /// - when the command buffer recording fails.
+    /// - when the command buffer execution fails.
+    #[must_use]
+    pub fn copy(&self, position: [u32; 2]) -> Option<Box<dyn GpuFuture>> {
+        let [x, y] = position;
+        let (width, height) = self.layout.size();
+        if x >= width || y >= height {
+            return None;
+        }
+
+        let mut builder = AutoCommandBufferBuilder::primary(
+            self.queue.device().clone(),
+            self.queue.queue_family_index(),
+            CommandBufferUsage::OneTimeSubmit,
+        )
+        .expect("Cannot create command buffer builder");
+
+        let word_size = u64::from(u32::BITS / 8);
+        builder
+            .copy_buffer(CopyBufferInfo {
+                regions: [BufferCopy {
+                    src_offset: self.layout.word_index(x, y) * word_size,
+                    dst_offset: 0,
+                    size: word_size,
+                    ..Default::default()
+                }]
+                .into(),
+                ..CopyBufferInfo::buffers(self.source.clone(), self.staging.clone())
+            })
+            .expect("Cannot copy buffer");
+
+        let future = builder
+            .build()
+            .expect("Cannot build command buffer")
+            .execute(self.queue.clone())
+            .expect("Cannot execute command buffer");
+        Some(future.boxed())
+    }
+
+    /// Returns value of the cell at given position copied by the last finished [`CellProbe::copy`].
+    ///
+    /// # Panics
+    ///
+    /// - when the staging buffer cannot be read.
+    #[must_use]
+    pub fn copied(&self, position: [u32; 2]) -> u32 {
+        let word = self.staging.read().expect("Cannot read staging buffer")[0];
+        self.layout.cell_from_word(word, position[0])
+    }
+}
+
+/// This struct represents a host visible copy of the board refreshed without waiting for it.
+///
+/// The copy is recorded after other commands, so it is finished together with them
+/// and its result is taken once the host already knows the GPU is done.
+/// It may run on a different queue than those commands, see [`crate::vulkan::transfer_queue`].
+pub struct AsyncReadback {
+    layout: BoardLayout,
+    queue: Arc<Queue>,
+    staging: Arc<CpuAccessibleBuffer<[u32]>>,
+    copy_buffer: Arc<CommandBuffer>,
+    copied: bool,
+}
+
+impl AsyncReadback {
+    /// Creates a new [`AsyncReadback`] of given buffer.
+    ///
+    /// # Panics
+    ///
+    /// - when [`AsyncReadback::try_new`] fails.
+    #[must_use]
+    pub fn new(queue: Arc<Queue>, source: Arc<GpuBuffer>, layout: BoardLayout) -> Self {
+        Self::try_new(queue, source, layout).expect("Cannot create readback")
+    }
+
+    /// Creates a new [`AsyncReadback`] of given buffer.
+    ///
+    /// # Errors
+    ///
+    /// - when the staging buffer creation fails.
+    /// - when the command buffer creation fails.
+    /// - when the command buffer recording fails.
+    ///
+    /// # Panics
+    ///
+    /// - when the board does not fit in host memory.
+    pub fn try_new(
+        queue: Arc<Queue>,
+        source: Arc<GpuBuffer>,
+        layout: BoardLayout,
+    ) -> Result<Self, Error> {
+        let device = queue.device().clone();
+        let len = usize::try_from(layout.len()).expect("Board does not fit in memory");
+
+        let staging = CpuAccessibleBuffer::from_iter(
+            device.clone(),
+            BufferUsage {
+                transfer_dst: true,
+                ..BufferUsage::empty()
+            },
+            true,
+            vec![0; len],
+        )?;
+
+        let mut builder = AutoCommandBufferBuilder::primary(
+            device,
+            queue.queue_family_index(),
+            CommandBufferUsage::MultipleSubmit,
+        )?;
+        builder.copy_buffer(CopyBufferInfo::buffers(source, staging.clone()))?;
+
+        Ok(Self {
+            layout,
+            queue,
+            staging,
+            copy_buffer: Arc::new(builder.build()?),
+            copied: false,
+        })
+    }
+
+    /// Copies the board to host memory after `future` without waiting for the copy.
+    /// Returns a new [`GpuFuture`] that has to be finished before [`AsyncReadback::take`].
+    /// When `future` runs on another queue, the copy waits for it with a semaphore.
+    ///
+    /// # Panics
+    ///
+    /// - when the command buffer execution fails.
+    #[must_use]
+    pub fn copy(&mut self, future: Box<dyn GpuFuture>) -> Box<dyn GpuFuture> {
+        self.copied = true;
+        let future = match future.queue() {
+            Some(queue) if queue != self.queue => future.then_signal_semaphore().boxed(),
+            _ => future,
+        };
+        future
+            .then_execute(self.queue.clone(), self.copy_buffer.clone())
+            .expect("Cannot execute command buffer")
+            .boxed()
+    }
+
+    /// Returns words of the board copied by the last [`AsyncReadback::copy`],
+    /// `None` when nothing was copied since the last call.
+    /// Never waits, the future returned by the copy has to be finished already.
+    ///
+    /// # Panics
+    ///
+    /// - when the staging buffer is still used by the GPU.
+    #[must_use]
+    pub fn take(&mut self) -> Option<Vec<u32>> {
+        if !std::mem::take(&mut self.copied) {
+            return None;
+        }
+        Some(
+            self.staging
+                .read()
+                .expect("Cannot read staging buffer")
+                .to_vec(),
+        )
+    }
+
+    /// Returns layout of the words returned by [`AsyncReadback::take`].
+    #[inline]
+    #[must_use]
+    pub fn layout(&self) -> BoardLayout {
+        self.layout
+    }
+}
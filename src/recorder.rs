@@ -0,0 +1,146 @@
+use std::{borrow::Cow, fs::File, io::BufWriter};
+
+use gif::{Encoder, EncodingError, Frame, Repeat};
+use vulkano::sync::GpuFuture;
+
+use crate::{Readback, RecordConfig};
+
+/// Palette of the recording, dead cells are white and alive cells are black.
+const PALETTE: [u8; 6] = [0xFF, 0xFF, 0xFF, 0x00, 0x00, 0x00];
+
+/// Delay between frames of the recording in hundredths of a second.
+const FRAME_DELAY: u16 = 4;
+
+/// This struct represents a GIF recording of the simulation.
+///
+/// Frames are written to the file as they are captured.
+/// The file is finished when the [`Recorder`] is dropped.
+pub struct Recorder {
+    readback: Readback,
+    encoder: Encoder<BufWriter<File>>,
+    region: [u32; 4],
+    scale: u32,
+    stride: u32,
+    max_frames: u32,
+    frames: u32,
+    /// Generation the recording started at, frames are taken every stride generations from it.
+    start: u64,
+}
+
+impl Recorder {
+    /// Creates a new [`Recorder`] writing to the path from the [`RecordConfig`],
+    /// starting at given generation.
+    ///
+    /// When the board scaled by the configured cell size exceeds
+    /// the maximum size of the recording only the center of the board is recorded.
+    ///
+    /// # Errors
+    ///
+    /// - when the file cannot be created.
+    /// - when the GIF header cannot be written.
+    pub fn new(
+        readback: Readback,
+        config: &RecordConfig,
+        generation: u64,
+    ) -> Result<Self, EncodingError> {
+        let (width, height) = readback.layout().size();
+        let scale = u32::from(config.scale().max(1));
+        let max_cells = (u32::from(config.max_size()) / scale).max(1);
+        let region_width = width.min(max_cells);
+        let region_height = height.min(max_cells);
+
+        let file = BufWriter::new(File::create(config.path())?);
+        #[allow(clippy::cast_possible_truncation)]
+        let mut encoder = Encoder::new(
+            file,
+            (region_width * scale) as u16,
+            (region_height * scale) as u16,
+            &PALETTE,
+        )?;
+        encoder.set_repeat(Repeat::Infinite)?;
+
+        Ok(Self {
+            readback,
+            encoder,
+            region: [
+                (width - region_width) / 2,
+                (height - region_height) / 2,
+                region_width,
+                region_height,
+            ],
+            scale,
+            stride: config.stride().max(1),
+            max_frames: config.max_frames(),
+            frames: 0,
+            start: generation,
+        })
+    }
+
+    /// Starts copying given generation into host memory when it falls on the configured stride,
+    /// the frame is written by [`Recorder::capture`] once the returned [`GpuFuture`] finishes.
+    /// The stride starts again from generations before the start,
+    /// reached when the board is cleared or stepped back.
+    ///
+    /// # Panics
+    ///
+    /// - when [`Readback::copy`] fails.
+    #[must_use]
+    pub fn copy(&mut self, generation: u64) -> Option<Box<dyn GpuFuture>> {
+        self.start = self.start.min(generation);
+        self.captures(generation).then(|| self.readback.copy())
+    }
+
+    /// Writes the frame of given generation copied by [`Recorder::copy`]
+    /// when it falls on the configured stride.
+    /// Returns `false` once the maximum number of frames has been recorded.
+    ///
+    /// # Errors
+    ///
+    /// - when the frame cannot be written.
+    pub fn capture(&mut self, generation: u64) -> Result<bool, EncodingError> {
+        if self.captures(generation) {
+            let frame = self.frame(&self.readback.copied());
+            self.encoder.write_frame(&frame)?;
+            self.frames += 1;
+        }
+        Ok(self.frames < self.max_frames)
+    }
+
+    /// Returns whether given generation falls on the configured stride.
+    fn captures(&self, generation: u64) -> bool {
+        generation
+            .checked_sub(self.start)
+            .map_or(false, |steps| steps % u64::from(self.stride) == 0)
+    }
+
+    /// Returns number of generations from given one to the next captured generation.
+    #[must_use]
+    pub fn steps_to_frame(&self, generation: u64) -> u64 {
+        let stride = u64::from(self.stride);
+        stride - generation.saturating_sub(self.start) % stride
+    }
+
+    /// Converts recorded region of the board into a frame of the recording.
+    #[allow(clippy::cast_possible_truncation)]
+    fn frame(&self, words: &[u32]) -> Frame<'static> {
+        let layout = self.readback.layout();
+        let [left, top, width, height] = self.region;
+        let (width, height) = (width * self.scale, height * self.scale);
+
+        let buffer = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| {
+                let cell = layout.get(words, left + x / self.scale, top + y / self.scale);
+                u8::from(cell != 0)
+            })
+            .collect();
+
+        Frame {
+            width: width as u16,
+            height: height as u16,
+            delay: FRAME_DELAY,
+            buffer: Cow::Owned(buffer),
+            ..Frame::default()
+        }
+    }
+}
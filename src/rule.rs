@@ -0,0 +1,301 @@
+use std::{error::Error, fmt, str::FromStr};
+
+use clap::ValueEnum;
+
+/// Maximum number of neighbours a cell can have.
+const MAX_NEIGHBOURS: u32 = 8;
+
+/// Number of states of life-like rules, dead and alive.
+const LIFE_STATES: u32 = 2;
+
+/// Named rules that can be selected in the controls.
+pub static PRESETS: [(&str, Rule); 7] = [
+    ("Conway", Rule::new(0b1000, 0b1100)),
+    ("HighLife", Rule::new(0b100_1000, 0b1100)),
+    ("Day & Night", Rule::new(0b1_1100_1000, 0b1_1101_1000)),
+    ("Seeds", Rule::new(0b100, 0)),
+    ("Replicator", Rule::new(0b1010_1010, 0b1010_1010)),
+    ("Brian's Brain", Rule::new(0b100, 0).with_states(3)),
+    ("Star Wars", Rule::new(0b100, 0b11_1000).with_states(4)),
+];
+
+/// This struct represents a life-like rule in `B3/S23` notation.
+///
+/// Both values are bit masks where bit `n` is set
+/// when a cell with `n` alive neighbours is born or survives.
+///
+/// Rules with more than two states, written as `B2/S/C3`, are "Generations" rules.
+/// Cell state `0` is dead and `1` is alive, a cell which does not survive
+/// goes through the remaining states one generation each before it dies.
+/// Only alive cells are counted as neighbours.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Rule {
+    birth: u32,
+    survival: u32,
+    states: u32,
+}
+
+impl Rule {
+    /// Creates a new [`Rule`] from birth and survival masks.
+    #[inline]
+    #[must_use]
+    pub const fn new(birth: u32, survival: u32) -> Self {
+        Self {
+            birth,
+            survival,
+            states: LIFE_STATES,
+        }
+    }
+
+    /// Returns the rule with given number of cell states.
+    ///
+    /// # Panics
+    ///
+    /// - when `states` is smaller than 2.
+    #[inline]
+    #[must_use]
+    pub const fn with_states(self, states: u32) -> Self {
+        assert!(states >= LIFE_STATES, "Rule needs at least 2 states");
+        Self { states, ..self }
+    }
+
+    /// Returns mask of neighbour counts for which a dead cell becomes alive.
+    #[inline]
+    #[must_use]
+    pub fn birth(&self) -> u32 {
+        self.birth
+    }
+
+    /// Returns mask of neighbour counts for which an alive cell stays alive.
+    #[inline]
+    #[must_use]
+    pub fn survival(&self) -> u32 {
+        self.survival
+    }
+
+    /// Returns number of cell states including dead and alive.
+    #[inline]
+    #[must_use]
+    pub fn states(&self) -> u32 {
+        self.states
+    }
+
+    /// Returns whether every cell state can be stored in `cell_bits` bits.
+    #[must_use]
+    pub fn fits_cell_bits(&self, cell_bits: u32) -> bool {
+        u64::from(self.states) <= 1 << cell_bits
+    }
+
+    /// Returns whether every neighbour count used by the rule
+    /// can be reached in given [`Neighbourhood`].
+    #[must_use]
+    pub fn fits(&self, neighbourhood: Neighbourhood) -> bool {
+        (self.birth | self.survival) >> (neighbourhood.max_neighbours() + 1) == 0
+    }
+
+    /// Returns name of the preset matching this rule.
+    #[must_use]
+    pub fn preset_name(&self) -> Option<&'static str> {
+        PRESETS
+            .iter()
+            .find(|(_, rule)| rule == self)
+            .map(|(name, _)| *name)
+    }
+}
+
+impl Default for Rule {
+    fn default() -> Self {
+        PRESETS[0].1
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("B")?;
+        write_counts(f, self.birth)?;
+        f.write_str("/S")?;
+        write_counts(f, self.survival)?;
+        if self.states != LIFE_STATES {
+            write!(f, "/C{}", self.states)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Rule {
+    type Err = ParseRuleError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (birth, survival) = s.split_once('/').ok_or(ParseRuleError::MissingSeparator)?;
+        let birth = birth
+            .strip_prefix(['B', 'b'])
+            .ok_or(ParseRuleError::MissingPrefix('B'))?;
+        let (survival, states) = match survival.split_once('/') {
+            Some((survival, states)) => (survival, Some(states)),
+            None => (survival, None),
+        };
+        let survival = survival
+            .strip_prefix(['S', 's'])
+            .ok_or(ParseRuleError::MissingPrefix('S'))?;
+        let states = match states {
+            Some(states) => parse_states(
+                states
+                    .strip_prefix(['C', 'c'])
+                    .ok_or(ParseRuleError::MissingPrefix('C'))?,
+            )?,
+            None => LIFE_STATES,
+        };
+
+        Ok(Self {
+            birth: parse_counts(birth)?,
+            survival: parse_counts(survival)?,
+            states,
+        })
+    }
+}
+
+/// Cells counted as neighbours of a cell.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum Neighbourhood {
+    /// Eight surrounding cells.
+    #[default]
+    Moore,
+    /// Four orthogonally adjacent cells.
+    VonNeumann,
+}
+
+impl Neighbourhood {
+    /// All neighbourhoods in order they should be displayed.
+    pub const ALL: [Self; 2] = [Self::Moore, Self::VonNeumann];
+
+    /// Returns human readable name of the neighbourhood.
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Moore => "Moore",
+            Self::VonNeumann => "Von Neumann",
+        }
+    }
+
+    /// Returns number of neighbours of a cell.
+    #[must_use]
+    pub fn max_neighbours(self) -> u32 {
+        match self {
+            Self::Moore => MAX_NEIGHBOURS,
+            Self::VonNeumann => 4,
+        }
+    }
+
+    /// Returns identifier of the neighbourhood understood by the shader.
+    pub(crate) fn id(self) -> u32 {
+        match self {
+            Self::Moore => 0,
+            Self::VonNeumann => 1,
+        }
+    }
+}
+
+/// Error returned when a rulestring cannot be parsed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParseRuleError {
+    MissingSeparator,
+    MissingPrefix(char),
+    InvalidCount(char),
+    InvalidStates(String),
+}
+
+impl fmt::Display for ParseRuleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingSeparator => f.write_str("expected rule in B3/S23 notation"),
+            Self::MissingPrefix(prefix) => write!(f, "expected '{prefix}' prefix"),
+            Self::InvalidCount(count) => write!(
+                f,
+                "invalid neighbour count '{count}', expected 0 to {MAX_NEIGHBOURS}"
+            ),
+            Self::InvalidStates(states) => write!(
+                f,
+                "invalid number of states '{states}', expected at least {LIFE_STATES}"
+            ),
+        }
+    }
+}
+
+impl Error for ParseRuleError {}
+
+/// Parses neighbour counts like `23` into a bit mask.
+fn parse_counts(counts: &str) -> Result<u32, ParseRuleError> {
+    counts.chars().try_fold(0, |mask, c| match c.to_digit(10) {
+        Some(count) if count <= MAX_NEIGHBOURS => Ok(mask | (1 << count)),
+        _ => Err(ParseRuleError::InvalidCount(c)),
+    })
+}
+
+/// Parses number of states like `3`.
+fn parse_states(states: &str) -> Result<u32, ParseRuleError> {
+    match states.parse() {
+        Ok(count) if count >= LIFE_STATES => Ok(count),
+        _ => Err(ParseRuleError::InvalidStates(states.to_owned())),
+    }
+}
+
+/// Writes neighbour counts stored in a bit mask.
+fn write_counts(f: &mut fmt::Formatter<'_>, mask: u32) -> fmt::Result {
+    (0..=MAX_NEIGHBOURS)
+        .filter(|count| mask & (1 << count) != 0)
+        .try_for_each(|count| write!(f, "{count}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rules_round_trip() {
+        for text in ["B3/S23", "B36/S23", "B2/S/C3"] {
+            let rule: Rule = text.parse().expect("Valid rule");
+            assert_eq!(rule.to_string(), text);
+        }
+    }
+
+    #[test]
+    fn parses_counts_and_states() {
+        assert_eq!("B3/S23".parse(), Ok(Rule::default()));
+        assert_eq!("b36/s23".parse(), Ok(Rule::new(0b100_1000, 0b1100)));
+        assert_eq!("B2/S/C3".parse(), Ok(Rule::new(0b100, 0).with_states(3)));
+    }
+
+    #[test]
+    fn rejects_missing_prefixes() {
+        assert_eq!(
+            "3/S23".parse::<Rule>(),
+            Err(ParseRuleError::MissingPrefix('B'))
+        );
+        assert_eq!(
+            "B3/23".parse::<Rule>(),
+            Err(ParseRuleError::MissingPrefix('S'))
+        );
+        assert_eq!(
+            "B3S23".parse::<Rule>(),
+            Err(ParseRuleError::MissingSeparator)
+        );
+    }
+
+    #[test]
+    fn rejects_counts_above_eight() {
+        assert_eq!(
+            "B39/S23".parse::<Rule>(),
+            Err(ParseRuleError::InvalidCount('9'))
+        );
+    }
+
+    #[test]
+    fn rejects_fewer_than_two_states() {
+        for states in ["0", "1"] {
+            assert_eq!(
+                format!("B2/S/C{states}").parse::<Rule>(),
+                Err(ParseRuleError::InvalidStates(states.to_owned()))
+            );
+        }
+    }
+}
@@ -0,0 +1,210 @@
+use std::{
+    env, fs, io,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    CellAspect, CellShading, Config, MouseBindings, Palette, StabilityAction, DEFAULT_BACKGROUND,
+    DEFAULT_BORDER_COLOR,
+};
+
+/// Name of the directory holding the settings file inside of the platform config directory.
+static APPLICATION_NAME: &str = env!("CARGO_PKG_NAME");
+
+/// Name of the settings file.
+static FILE_NAME: &str = "settings.toml";
+
+/// This struct represents controls and camera settings kept between runs.
+///
+/// It is saved as TOML in the platform config directory when the window is closed
+/// and loaded on startup, options given on the command line take precedence over it.
+/// Missing values are replaced with defaults, so older files can still be loaded.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct Settings {
+    pub(crate) speed: u32,
+    pub(crate) max_speed: bool,
+    pub(crate) limit_fps: bool,
+    pub(crate) max_fps: u32,
+    pub(crate) grid: bool,
+    pub(crate) grid_interval: u32,
+    pub(crate) ruler: bool,
+    pub(crate) neighbour_colors: bool,
+    pub(crate) vsync: bool,
+    /// Physical pixels per point of the controls, `None` for the window scale factor.
+    pub(crate) ui_scale: Option<f32>,
+    pub(crate) controls_visible: bool,
+    pub(crate) wrap_shift: bool,
+    pub(crate) background: [u8; 3],
+    pub(crate) palette: Palette,
+    pub(crate) stability_action: StabilityAction,
+    pub(crate) mouse_bindings: MouseBindings,
+    pub(crate) cell_aspect: CellAspect,
+    pub(crate) brush_outline: bool,
+    pub(crate) native_view: bool,
+    pub(crate) pan_inertia: f32,
+    pub(crate) cell_padding: f32,
+    pub(crate) inverted: bool,
+    pub(crate) state_threshold: u32,
+    pub(crate) cell_shading: CellShading,
+    pub(crate) border: bool,
+    pub(crate) border_width: u32,
+    pub(crate) border_color: [u8; 3],
+    pub(crate) camera: CameraSettings,
+}
+
+/// Position and zoom of the camera.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CameraSettings {
+    pub(crate) scale: f32,
+    pub(crate) translation: [f32; 2],
+}
+
+impl Settings {
+    /// Returns path of the settings file in the platform config directory,
+    /// `None` when the directory cannot be determined.
+    #[must_use]
+    pub fn path() -> Option<PathBuf> {
+        config_dir().map(|directory| directory.join(APPLICATION_NAME).join(FILE_NAME))
+    }
+
+    /// Loads settings from given file.
+    /// Returns default settings and logs the problem when the file cannot be loaded.
+    #[must_use]
+    pub fn load(path: &Path) -> Self {
+        match fs::read_to_string(path) {
+            Ok(text) => toml::from_str(&text).unwrap_or_else(|error| {
+                log::warn!("Cannot parse settings {}: {error}", path.display());
+                Self::default()
+            }),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Self::default(),
+            Err(error) => {
+                log::warn!("Cannot read settings {}: {error}", path.display());
+                Self::default()
+            }
+        }
+    }
+
+    /// Saves settings to given file, creating its directory when needed.
+    ///
+    /// # Errors
+    ///
+    /// - when the directory or the file cannot be written.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let text =
+            toml::to_string(self).map_err(|error| io::Error::new(io::ErrorKind::Other, error))?;
+        if let Some(directory) = path.parent() {
+            fs::create_dir_all(directory)?;
+        }
+        fs::write(path, text)
+    }
+
+    /// Returns settings with values given on the command line replacing the saved ones.
+    #[must_use]
+    pub fn with_overrides(mut self, config: &Config) -> Self {
+        if let Some(speed) = config.speed() {
+            self.speed = speed;
+        }
+        if let Some(fps) = config.max_fps() {
+            self.limit_fps = true;
+            self.max_fps = fps;
+        }
+        if let Some(bindings) = config.mouse_bindings() {
+            self.mouse_bindings = bindings;
+        }
+        if let Some(palette) = config.palette() {
+            self.palette = palette;
+            self.background = palette.colors().background;
+        }
+        if let Some(background) = config.background() {
+            self.background = background;
+        }
+        if let Some(scale) = config.ui_scale() {
+            self.ui_scale = Some(scale);
+        }
+        if let Some(zoom) = config.zoom() {
+            self.camera.scale = zoom;
+        }
+        // The board spans -1 to 1 and a cell is centred when moved by minus its position.
+        let (width, height) = config.size();
+        #[allow(clippy::cast_precision_loss)]
+        for ((translation, pan), cells) in self
+            .camera
+            .translation
+            .iter_mut()
+            .zip(config.pan())
+            .zip([width, height])
+        {
+            if let Some(pan) = pan {
+                *translation = 1.0 - 2.0 * pan / cells as f32;
+            }
+        }
+        self.grid |= config.grid();
+        self.vsync &= config.vsync();
+        self
+    }
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            speed: 60,
+            max_speed: false,
+            limit_fps: false,
+            max_fps: 60,
+            grid: false,
+            grid_interval: 10,
+            ruler: false,
+            neighbour_colors: false,
+            vsync: true,
+            ui_scale: None,
+            controls_visible: true,
+            wrap_shift: true,
+            background: DEFAULT_BACKGROUND,
+            palette: Palette::default(),
+            stability_action: StabilityAction::default(),
+            mouse_bindings: MouseBindings::default(),
+            cell_aspect: CellAspect::default(),
+            brush_outline: true,
+            native_view: false,
+            pan_inertia: 0.25,
+            cell_padding: 0.0,
+            inverted: false,
+            state_threshold: u32::MAX,
+            cell_shading: CellShading::default(),
+            border: false,
+            border_width: 2,
+            border_color: DEFAULT_BORDER_COLOR,
+            camera: CameraSettings::default(),
+        }
+    }
+}
+
+impl Default for CameraSettings {
+    fn default() -> Self {
+        Self {
+            scale: 1.0,
+            translation: [0.0, 0.0],
+        }
+    }
+}
+
+/// Returns the platform config directory.
+///
+/// It is `%APPDATA%` on Windows, `~/Library/Application Support` on macOS
+/// and `$XDG_CONFIG_HOME` or `~/.config` elsewhere.
+fn config_dir() -> Option<PathBuf> {
+    if cfg!(target_os = "windows") {
+        env::var_os("APPDATA").map(PathBuf::from)
+    } else if cfg!(target_os = "macos") {
+        env::var_os("HOME").map(|home| Path::new(&home).join("Library/Application Support"))
+    } else {
+        env::var_os("XDG_CONFIG_HOME")
+            .filter(|directory| !directory.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".config")))
+    }
+}
@@ -0,0 +1,38 @@
+// Layout of cells packed into 32 bit words, matching `BoardLayout`.
+// Requires `width` and `cellBits` constants to be declared before inclusion.
+// Every row starts with a new word, so the last word of a row may contain unused bits.
+
+const uint cellsPerWord = 32u / cellBits;
+const uint cellMask = 0xFFFFFFFFu >> (32u - cellBits);
+const uint wordsPerRow = (width + cellsPerWord - 1u) / cellsPerWord;
+
+// Returns index of the word containing given cell.
+uint wordIndex(uvec2 cell) {
+    return cell.y * wordsPerRow + cell.x / cellsPerWord;
+}
+
+// Returns offset of given cell in its word.
+uint cellShift(uvec2 cell) {
+    return (cell.x % cellsPerWord) * cellBits;
+}
+
+// Extracts value of given cell from the word containing it.
+uint cellFromWord(uint word, uvec2 cell) {
+    return (word >> cellShift(cell)) & cellMask;
+}
+
+// Neighbour counts take 4 bits each, matching `BoardLayout::count_len`.
+// Counts of cells of a single word are stored in `countWordsPerWord` consecutive words.
+const uint countBits = 4u;
+const uint countsPerWord = 32u / countBits;
+const uint countWordsPerWord = (cellsPerWord + countsPerWord - 1u) / countsPerWord;
+
+// Returns index of the word containing neighbour count of given cell.
+uint countIndex(uvec2 cell) {
+    return wordIndex(cell) * countWordsPerWord + (cell.x % cellsPerWord) / countsPerWord;
+}
+
+// Returns offset of neighbour count of given cell in its word.
+uint countShift(uvec2 cell) {
+    return (cell.x % cellsPerWord) % countsPerWord * countBits;
+}
@@ -1,4 +1,5 @@
 #version 460 core
+#extension GL_GOOGLE_include_directive : require
 
 layout(location = 0) in vec2 position;
 
@@ -6,25 +7,137 @@ layout(set = 0, binding = 0) readonly buffer Data {
     uint data[];
 } inputData;
 
+// Only read when camera.neighbourColors is set.
+layout(set = 0, binding = 1) readonly buffer Counts {
+    uint data[];
+} counts;
+
+// Only read when camera.highlightDifference is set, non-zero cells differ from another board.
+layout(set = 0, binding = 2) readonly buffer Difference {
+    uint data[];
+} difference;
+
 layout(constant_id = 0) const uint width = 1024;
 layout(constant_id = 1) const uint height = 1024;
+layout(constant_id = 2) const uint cellBits = 32;
+
+#include "cells.glsl"
 
 layout (push_constant) uniform Camera {
     mat4 matrix;
     uint drawGrid;
+    uint states;
     uvec2 position;
+    vec2 pixelSize;
+    uint neighbourColors;
+    uint aliveColor;
+    uint deadColor;
+    uint gridColor;
+    uint highlightDifference;
+    uint gridInterval;
+    float cellPadding;
+    uint stateThreshold;
+    uint shading;
+    uint border;
 } camera;
 
 layout(location = 0) out vec4 color;
 
+// Colors of alive cells by number of their alive neighbours, from isolated to crowded.
+const vec3 palette[9] = {
+    vec3(0.35, 0.0, 0.45),
+    vec3(0.25, 0.2, 0.6),
+    vec3(0.1, 0.4, 0.7),
+    vec3(0.0, 0.55, 0.55),
+    vec3(0.1, 0.6, 0.3),
+    vec3(0.5, 0.65, 0.1),
+    vec3(0.85, 0.6, 0.0),
+    vec3(0.9, 0.35, 0.0),
+    vec3(0.8, 0.1, 0.1),
+};
+
+// Smallest size of a cell in device pixels at which lines between all cells are drawn.
+const float minorLineSpacing = 4.0;
+
+// Strength of lines between cells compared to lines every grid interval.
+const float minorLineStrength = 0.5;
+
+// Ways of mapping states of dying cells to their brightness, same as `CellShading`.
+const uint shadingLinear = 0;
+const uint shadingQuadratic = 1;
+const uint shadingSolid = 2;
+
+// Color of cells which differ from another board.
+const vec3 differenceColor = vec3(0.9, 0.05, 0.5);
+
+// Converts a color packed as 8 bit sRGB channels into linear values.
+vec3 unpackColor(uint packed) {
+    vec3 srgb = unpackUnorm4x8(packed).rgb;
+    vec3 low = srgb / 12.92;
+    vec3 high = pow((srgb + 0.055) / 1.055, vec3(2.4));
+    return mix(low, high, greaterThan(srgb, vec3(0.04045)));
+}
+
+// Returns 1 when the pixel is the one closest to a border of cells `interval` apart
+// and 0 otherwise. Distance to the border is measured in device pixels using
+// the exact pixel size, so every line is exactly one pixel wide regardless of zoom.
+float gridLine(vec2 positionScaled, float interval) {
+    vec2 lines = positionScaled / interval;
+    vec2 pixels = (lines - round(lines)) * interval / camera.pixelSize;
+    bvec2 line = bvec2(
+        pixels.x >= -0.5 && pixels.x < 0.5,
+        pixels.y >= -0.5 && pixels.y < 0.5
+    );
+    return any(line) ? 1.0 : 0.0;
+}
+
 void main() {
     vec2 positionScaled = position * vec2(uvec2(width, height));
     uvec2 index = uvec2(positionScaled);
-    float value = float(1 - inputData.data[index.x + index.y * width]);
-    if (camera.drawGrid == 1 && (fract(positionScaled.x) < 0.07 || fract(positionScaled.y) < 0.07 || fract(positionScaled.x) > 0.93 || fract(positionScaled.y) > 0.93)) {
-        value = 0.9;
-    } else if (index == camera.position) {
-        value = value * 0.33 + 0.33;
+    uint cell = cellFromWord(inputData.data[wordIndex(index)], index);
+    // Dying cells fade from the alive color towards the dead one with every state shown,
+    // cells in states past the threshold are drawn as dead ones.
+    uint threshold = clamp(camera.stateThreshold, 1u, camera.states - 1);
+    bool shown = cell != 0 && cell <= threshold;
+    float value = shown ? float(cell - 1) / float(threshold) : 1.0;
+    if (shown && camera.shading == shadingQuadratic) {
+        value *= value;
+    } else if (shown && camera.shading == shadingSolid) {
+        value = 0.0;
+    }
+    vec3 rgb = mix(unpackColor(camera.aliveColor), unpackColor(camera.deadColor), value);
+    if (camera.neighbourColors == 1 && cell == 1) {
+        uint count = (counts.data[countIndex(index)] >> countShift(index)) & 0xFu;
+        rgb = palette[min(count, 8u)];
+    }
+    if (camera.highlightDifference == 1
+            && cellFromWord(difference.data[wordIndex(index)], index) != 0) {
+        rgb = differenceColor;
+    }
+    // Shown cells are inset by half of the padding on every side,
+    // the gap around them is drawn as a dead cell.
+    vec2 inCell = fract(positionScaled);
+    if (shown && any(lessThan(min(inCell, 1.0 - inCell), vec2(camera.cellPadding * 0.5)))) {
+        rgb = unpackColor(camera.deadColor);
+        value = 1.0;
+    }
+    // Lines every grid interval are always drawn, lines between all cells
+    // only when cells are big enough for them not to cover the board.
+    float line = gridLine(positionScaled, float(camera.gridInterval));
+    if (all(lessThanEqual(camera.pixelSize, vec2(1.0 / minorLineSpacing)))) {
+        line = max(line, gridLine(positionScaled, 1.0) * minorLineStrength);
+    }
+    if (index == camera.position) {
+        rgb = rgb * 0.33 + 0.33;
+    }
+    if (camera.drawGrid == 1) {
+        rgb = mix(rgb, unpackColor(camera.gridColor), line);
+    }
+    // Pixels closer to the edges of the board than the border width are drawn with
+    // the border color, the width in device pixels is packed in place of its alpha.
+    vec2 edges = min(positionScaled, vec2(uvec2(width, height)) - positionScaled);
+    if (any(lessThan(edges / camera.pixelSize, vec2(float(camera.border >> 24))))) {
+        rgb = unpackColor(camera.border);
     }
-    color = vec4(value);
+    color = vec4(rgb, value);
 }
\ No newline at end of file
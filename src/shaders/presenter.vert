@@ -17,7 +17,19 @@ const uvec2 texcoords[4] = {
 layout (push_constant) uniform Camera {
     mat4 matrix;
     uint drawGrid;
+    uint states;
     uvec2 position;
+    vec2 pixelSize;
+    uint neighbourColors;
+    uint aliveColor;
+    uint deadColor;
+    uint gridColor;
+    uint highlightDifference;
+    uint gridInterval;
+    float cellPadding;
+    uint stateThreshold;
+    uint shading;
+    uint border;
 } camera;
 
 layout(location = 0) out vec2 position;
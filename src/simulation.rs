@@ -1,17 +1,23 @@
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
 use vulkano::{
+    buffer::{BufferUsage, CpuAccessibleBuffer, DeviceLocalBuffer},
     command_buffer::{
         AutoCommandBufferBuilder, CommandBufferUsage, CopyBufferInfo, FillBufferInfo,
         PrimaryCommandBuffer,
     },
     descriptor_set::{PersistentDescriptorSet, WriteDescriptorSet},
-    device::Queue,
+    device::{Device, Queue},
     pipeline::{ComputePipeline, Pipeline, PipelineBindPoint},
-    sync::GpuFuture,
+    query::{QueryPool, QueryPoolCreateInfo, QueryResultFlags, QueryType},
+    sync::{self, GpuFuture, PipelineStage},
 };
 
-use crate::{vulkan, CommandBuffer, GpuBuffer, Randomizer};
+use crate::{
+    vulkan, BoardLayout, BoundsScanner, CellProbe, CommandBuffer, Distribution, Error, GpuBuffer,
+    LiveBounds, Neighbourhood, Pattern, Randomizer, Readback, Rule, Symmetry, Topology,
+    WeightedRule,
+};
 
 /// This module contains compiled compute shader and shader data structures.
 mod shader {
@@ -21,6 +27,58 @@ mod shader {
                 ty: "compute",
                 path: "src/shaders/simulation.comp",
             },
+            compare: {
+                ty: "compute",
+                path: "src/shaders/compare.comp",
+            },
+            transform: {
+                ty: "compute",
+                path: "src/shaders/transform.comp",
+            },
+        },
+        include: ["src/shaders"],
+        types_meta: {
+            use bytemuck::{Pod, Zeroable};
+            #[derive(Clone, Copy, Pod, Zeroable)]
+        }
+    }
+}
+
+/// Axis the whole board is mirrored along by [`Simulation::mirror`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mirror {
+    /// Left and right edges are swapped.
+    Horizontal,
+    /// Top and bottom edges are swapped.
+    Vertical,
+}
+
+impl Mirror {
+    /// Returns identifier of the transformation understood by the shader.
+    fn mode(self) -> u32 {
+        match self {
+            Self::Horizontal => 0,
+            Self::Vertical => 1,
+        }
+    }
+}
+
+/// Identifier of the shift transformation understood by the shader.
+const SHIFT_MODE: u32 = 4;
+
+/// Direction the whole board is rotated in by [`Simulation::rotate`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Rotation {
+    Clockwise,
+    CounterClockwise,
+}
+
+impl Rotation {
+    /// Returns identifier of the transformation understood by the shader.
+    fn mode(self) -> u32 {
+        match self {
+            Self::Clockwise => 2,
+            Self::CounterClockwise => 3,
         }
     }
 }
@@ -28,11 +86,55 @@ mod shader {
 /// This struct represents a pipeline that can be used to
 /// compute the next generation of the game of life.
 pub struct Simulation {
+    generation: u64,
+    rule: Rule,
+    neighbourhood: Neighbourhood,
+    topology: Topology,
+    layout: BoardLayout,
+    workgroup: u32,
+    input: Arc<GpuBuffer>,
+    output: Arc<GpuBuffer>,
     randomizer: Randomizer,
+    bounds: BoundsScanner,
     compute_queue: Arc<Queue>,
     main_buffer: Arc<CommandBuffer>,
     copy_buffer: Arc<CommandBuffer>,
     clear_buffer: Arc<CommandBuffer>,
+    history: History,
+    changes: Arc<CpuAccessibleBuffer<[u32]>>,
+    compare_buffer: Arc<CommandBuffer>,
+    transform: Transform,
+    mirror_buffers: [Arc<CommandBuffer>; 2],
+    /// Rotations are only possible on square boards.
+    rotate_buffers: Option<[Arc<CommandBuffer>; 2]>,
+    /// Number of generations computed since the board was replaced, at most 2.
+    chain: u32,
+    /// Live neighbours of cells of the current generation, only written when enabled.
+    counts: Option<Arc<GpuBuffer>>,
+    /// Timestamps written before and after the dispatch of every generation,
+    /// `None` when the compute queue does not support timestamps.
+    timestamps: Option<Arc<QueryPool>>,
+    /// Whether words of every work group are loaded into shared memory before computing cells.
+    tiled: bool,
+    /// Weighted rule replacing birth and survival counts of the rule, when given.
+    weights: Option<WeightedRule>,
+}
+
+/// Pipeline moving cells of the whole board, used to mirror, rotate and shift it.
+struct Transform {
+    pipeline: Arc<ComputePipeline>,
+    descriptor: Arc<PersistentDescriptorSet>,
+}
+
+/// Ring buffer of copies of past generations, used to step back.
+#[derive(Default)]
+struct History {
+    /// Command buffers saving the board into a slot and restoring it from the slot.
+    slots: Vec<(Arc<CommandBuffer>, Arc<CommandBuffer>)>,
+    /// Index of the slot the next generation is saved into.
+    next: usize,
+    /// Number of slots holding past generations.
+    len: usize,
 }
 
 impl Simulation {
@@ -43,169 +145,1371 @@ impl Simulation {
     ///
     /// # Panics
     ///
+    /// - when [`Simulation::try_new`] fails.
+    #[must_use]
+    pub fn new(
+        compute_queue: Arc<Queue>,
+        output: Arc<GpuBuffer>,
+        layout: BoardLayout,
+        rule: Rule,
+        neighbourhood: Neighbourhood,
+        topology: Topology,
+        workgroup: u32,
+    ) -> Self {
+        Self::try_new(
+            compute_queue,
+            output,
+            layout,
+            rule,
+            neighbourhood,
+            topology,
+            workgroup,
+        )
+        .expect("Cannot create simulation")
+    }
+
+    /// Creates a new [`Simulation`] pipeline.
+    ///
+    /// It creates new [`GpuBuffer`], [`ComputePipeline`] and [`PersistentDescriptorSet`].
+    /// Then it records a command buffer that can be used to execute the pipeline.
+    ///
+    /// # Errors
+    ///
     /// - when the underlying Vulkano struct creations fail.
     /// - when the shader entry point is not found.
     /// - when the descriptor set creation fails.
     /// - when the command buffer creation fails.
     /// - when the command buffer building fails.
     /// - when the command buffer recording fails.
-    #[must_use]
-    pub fn new(compute_queue: Arc<Queue>, output: Arc<GpuBuffer>, size: (u32, u32)) -> Self {
+    pub fn try_new(
+        compute_queue: Arc<Queue>,
+        output: Arc<GpuBuffer>,
+        layout: BoardLayout,
+        rule: Rule,
+        neighbourhood: Neighbourhood,
+        topology: Topology,
+        workgroup: u32,
+    ) -> Result<Self, Error> {
         let device = compute_queue.device().clone();
-        let input = vulkan::create_gpu_buffer(&device, size, false);
-
-        let mut group_size = [size.0 / 32, size.1 / 32, 1];
-        if size.0 % 32 != 0 {
-            group_size[0] += 1;
-        }
-        if size.1 % 32 != 0 {
-            group_size[1] += 1;
-        }
+        let input = vulkan::try_create_gpu_buffer(&device, layout, true)?;
+        let older = vulkan::try_create_gpu_buffer(&device, layout, false)?;
+        let timestamps = create_timestamps(&compute_queue)?;
 
         let main_buffer = create_simulation_buffer(
             &compute_queue,
             output.clone(),
             input.clone(),
-            size,
-            group_size,
-        );
+            None,
+            timestamps.as_ref(),
+            layout,
+            workgroup,
+            rule,
+            neighbourhood,
+            topology,
+            false,
+            None,
+        )?;
 
+        // Command buffers of a generation are submitted again before the previous one finishes.
         let mut builder = AutoCommandBufferBuilder::primary(
             device.clone(),
             compute_queue.queue_family_index(),
-            CommandBufferUsage::MultipleSubmit,
-        )
-        .expect("Cannot create command buffer builder");
+            CommandBufferUsage::SimultaneousUse,
+        )?;
 
         builder
-            .copy_buffer(CopyBufferInfo::buffers(output.clone(), input))
-            .expect("Cannot copy buffer");
+            .copy_buffer(CopyBufferInfo::buffers(input.clone(), older.clone()))?
+            .copy_buffer(CopyBufferInfo::buffers(output.clone(), input.clone()))?;
 
-        let copy_buffer = Arc::new(builder.build().expect("Cannot build command buffer"));
+        let copy_buffer = Arc::new(builder.build()?);
+
+        let changes = CpuAccessibleBuffer::from_iter(
+            device.clone(),
+            BufferUsage {
+                storage_buffer: true,
+                transfer_dst: true,
+                ..BufferUsage::empty()
+            },
+            true,
+            [0, 0],
+        )?;
+
+        let compare_buffer = create_compare_buffer(
+            &compute_queue,
+            [output.clone(), input.clone(), older],
+            changes.clone(),
+            layout,
+            workgroup,
+        )?;
+
+        let transform = create_transform_pipeline(
+            &compute_queue,
+            output.clone(),
+            input.clone(),
+            layout,
+            workgroup,
+        )?;
+        let record = |mode| {
+            create_transform_buffer(
+                &compute_queue,
+                &transform,
+                [output.clone(), input.clone()],
+                layout.group_count(workgroup),
+                shader::ty::PushConstants {
+                    mode,
+                    wrap: 0,
+                    offset: [0, 0],
+                },
+            )
+        };
+        let mirror_buffers = [
+            record(Mirror::Horizontal.mode())?,
+            record(Mirror::Vertical.mode())?,
+        ];
+        let (width, height) = layout.size();
+        let rotate_buffers = if width == height {
+            Some([
+                record(Rotation::Clockwise.mode())?,
+                record(Rotation::CounterClockwise.mode())?,
+            ])
+        } else {
+            None
+        };
 
         let mut builder = AutoCommandBufferBuilder::primary(
             device,
             compute_queue.queue_family_index(),
             CommandBufferUsage::MultipleSubmit,
-        )
-        .expect("Cannot create command buffer builder");
+        )?;
 
-        builder
-            .fill_buffer(FillBufferInfo::dst_buffer(output.clone()))
-            .expect("Cannot copy buffer");
+        builder.fill_buffer(FillBufferInfo::dst_buffer(output.clone()))?;
 
-        let clear_buffer = Arc::new(builder.build().expect("Cannot build command buffer"));
+        let clear_buffer = Arc::new(builder.build()?);
 
-        Self {
-            randomizer: Randomizer::new(compute_queue.clone(), output, size),
+        Ok(Self {
+            generation: 0,
+            rule,
+            neighbourhood,
+            topology,
+            layout,
+            workgroup,
+            input,
+            output: output.clone(),
+            randomizer: Randomizer::try_new(
+                compute_queue.clone(),
+                output.clone(),
+                layout,
+                workgroup,
+            )?,
+            bounds: BoundsScanner::try_new(compute_queue.clone(), output, layout, workgroup)?,
             compute_queue,
             main_buffer,
             copy_buffer,
             clear_buffer,
+            history: History::default(),
+            changes,
+            compare_buffer,
+            transform,
+            mirror_buffers,
+            rotate_buffers,
+            chain: 0,
+            counts: None,
+            timestamps,
+            tiled: false,
+            weights: None,
+        })
+    }
+
+    /// Changes number of past generations kept for [`Simulation::step_back`].
+    /// Previously kept generations are forgotten.
+    ///
+    /// # Panics
+    ///
+    /// - when [`Simulation::try_set_history_depth`] fails.
+    pub fn set_history_depth(&mut self, depth: usize) {
+        self.try_set_history_depth(depth)
+            .expect("Cannot create history");
+    }
+
+    /// Changes number of past generations kept, see [`Simulation::set_history_depth`].
+    /// The history is left untouched when it fails.
+    ///
+    /// # Errors
+    ///
+    /// - when the buffer allocation fails.
+    /// - when the command buffer creation fails.
+    /// - when the command buffer recording fails.
+    pub fn try_set_history_depth(&mut self, depth: usize) -> Result<(), Error> {
+        let device = self.compute_queue.device();
+        let slots = (0..depth)
+            .map(|_| {
+                let slot = vulkan::try_create_gpu_buffer(device, self.layout, true)?;
+                Ok((
+                    create_copy_buffer(&self.compute_queue, self.output.clone(), slot.clone())?,
+                    create_copy_buffer(&self.compute_queue, slot, self.output.clone())?,
+                ))
+            })
+            .collect::<Result<_, Error>>()?;
+        self.history = History {
+            slots,
+            next: 0,
+            len: 0,
+        };
+        Ok(())
+    }
+
+    /// Returns number of past generations kept for [`Simulation::step_back`].
+    #[inline]
+    #[must_use]
+    pub fn history_depth(&self) -> usize {
+        self.history.slots.len()
+    }
+
+    /// Returns number of past generations [`Simulation::step_back`] can restore.
+    #[inline]
+    #[must_use]
+    pub fn history_len(&self) -> usize {
+        self.history.len
+    }
+
+    /// Restores the previous generation from the history.
+    /// Returns a new [`GpuFuture`] that can be used to wait for the restore to finish.
+    /// When the history is empty nothing happens.
+    ///
+    /// # Panics
+    ///
+    /// - when the command buffer execution fails.
+    #[must_use]
+    pub fn step_back(&mut self) -> Box<dyn GpuFuture> {
+        let depth = self.history.slots.len();
+        if self.history.len == 0 {
+            return sync::now(self.compute_queue.device().clone()).boxed();
         }
+        self.history.len -= 1;
+        self.chain = 0;
+        self.history.next = (self.history.next + depth - 1) % depth;
+        self.generation -= 1;
+        self.history.slots[self.history.next]
+            .1
+            .clone()
+            .execute(self.compute_queue.clone())
+            .expect("Cannot execute command buffer")
+            .boxed()
     }
 
     /// Executes the pipeline after given [`GpuFuture`].
     /// Returns a new [`GpuFuture`] that can be used to wait for the pipeline to finish.
     /// After the pipeline is finished, simulation of the next generation is ready.
     ///
+    /// Nothing is submitted or waited for, so many generations can be chained
+    /// and submitted at once without the host waiting between them.
+    ///
     /// # Panics
     ///
-    /// - when the command buffer submission fails.
-    /// - when the command buffer copy fails.
+    /// - when the command buffer execution fails.
     #[must_use]
-    pub fn step(&self, future: Box<dyn GpuFuture>) -> Box<dyn GpuFuture> {
-        future
+    pub fn step(&mut self, future: Box<dyn GpuFuture>) -> Box<dyn GpuFuture> {
+        self.generation += 1;
+        self.chain = (self.chain + 1).min(2);
+        let mut future = future
             .then_execute(self.compute_queue.clone(), self.copy_buffer.clone())
             .expect("Cannot execute command buffer")
-            .then_signal_fence_and_flush()
-            .expect("Cannot flush command buffer")
-            .wait(None)
+            .boxed();
+        if let Some((save, _)) = self.history.slots.get(self.history.next) {
+            future = future
+                .then_execute(self.compute_queue.clone(), save.clone())
+                .expect("Cannot execute command buffer")
+                .boxed();
+            self.history.next = (self.history.next + 1) % self.history.slots.len();
+            self.history.len = (self.history.len + 1).min(self.history.slots.len());
+        }
+        future
+            .then_execute(self.compute_queue.clone(), self.main_buffer.clone())
+            .expect("Cannot execute command buffer")
+            .boxed()
+    }
+
+    /// Executes generations after given [`GpuFuture`] until generation `target` is reached,
+    /// see [`Simulation::step`]. Nothing is executed when the target was already reached.
+    /// Returns a new [`GpuFuture`] that can be used to wait for the last generation to finish.
+    ///
+    /// # Panics
+    ///
+    /// - when the command buffer execution fails.
+    #[must_use]
+    pub fn run_to(&mut self, target: u64, mut future: Box<dyn GpuFuture>) -> Box<dyn GpuFuture> {
+        while self.generation < target {
+            future = self.step(future);
+        }
+        future
+    }
+
+    /// Changes the rule used to compute next generations.
+    /// The simulation command buffer is recorded again, the board is left untouched.
+    ///
+    /// # Panics
+    ///
+    /// - when the pipeline creation fails.
+    /// - when the command buffer recording fails.
+    pub fn set_rule(&mut self, rule: Rule) {
+        if self.rule != rule {
+            self.rule = rule;
+            self.rebuild();
+        }
+    }
+
+    /// Changes the neighbourhood used to count neighbours of cells.
+    /// The simulation command buffer is recorded again, the board is left untouched.
+    ///
+    /// # Panics
+    ///
+    /// - when the pipeline creation fails.
+    /// - when the command buffer recording fails.
+    pub fn set_neighbourhood(&mut self, neighbourhood: Neighbourhood) {
+        if self.neighbourhood != neighbourhood {
+            self.neighbourhood = neighbourhood;
+            self.rebuild();
+        }
+    }
+
+    /// Changes the way edges of the board are connected.
+    /// The simulation command buffer is recorded again, the board is left untouched.
+    ///
+    /// # Panics
+    ///
+    /// - when the pipeline creation fails.
+    /// - when the command buffer recording fails.
+    pub fn set_topology(&mut self, topology: Topology) {
+        if self.topology != topology {
+            self.topology = topology;
+            self.rebuild();
+        }
+    }
+
+    /// Starts or stops counting live neighbours of computed cells,
+    /// see [`Simulation::neighbour_counts`].
+    /// The simulation command buffer is recorded again, the board is left untouched.
+    ///
+    /// # Panics
+    ///
+    /// - when the buffer creation fails.
+    /// - when the pipeline creation fails.
+    /// - when the command buffer recording fails.
+    pub fn set_neighbour_counts(&mut self, enabled: bool) {
+        if self.counts.is_some() != enabled {
+            self.counts = enabled.then(|| {
+                create_count_buffer(self.compute_queue.device(), self.layout)
+                    .expect("Cannot create neighbour counts buffer")
+            });
+            self.rebuild();
+        }
+    }
+
+    /// Starts or stops loading words of every work group with their neighbours
+    /// into shared memory before computing cells, so every word is read from the board once
+    /// instead of for every neighbour of its cells. Results are the same either way.
+    /// The simulation command buffer is recorded again, the board is left untouched.
+    ///
+    /// # Panics
+    ///
+    /// - when the pipeline creation fails.
+    /// - when the command buffer recording fails.
+    pub fn set_tiled(&mut self, tiled: bool) {
+        if self.tiled != tiled {
+            self.tiled = tiled;
+            self.rebuild();
+        }
+    }
+
+    /// Sums live neighbours with weights of given rule and compares the sum to its ranges
+    /// instead of the birth and survival counts of the rule, `None` counts them again.
+    /// Forks, see [`Simulation::fork`], use only their own rule.
+    /// The simulation command buffer is recorded again, the board is left untouched.
+    ///
+    /// # Panics
+    ///
+    /// - when the pipeline creation fails.
+    /// - when the command buffer recording fails.
+    pub fn set_weights(&mut self, weights: Option<WeightedRule>) {
+        if self.weights != weights {
+            self.weights = weights;
+            self.rebuild();
+        }
+    }
+
+    /// Returns the weighted rule replacing birth and survival counts, if any.
+    #[inline]
+    #[must_use]
+    pub fn weights(&self) -> Option<WeightedRule> {
+        self.weights
+    }
+
+    /// Returns whether words of every work group are loaded into shared memory first.
+    #[inline]
+    #[must_use]
+    pub fn tiled(&self) -> bool {
+        self.tiled
+    }
+
+    /// Returns the buffer with numbers of live neighbours cells of the current generation
+    /// were computed from, laid out as described by [`BoardLayout::count_len`].
+    /// Returns `None` when counting is disabled.
+    /// Counts are written while computing generations, so they are zero until the next step.
+    #[inline]
+    #[must_use]
+    pub fn neighbour_counts(&self) -> Option<Arc<GpuBuffer>> {
+        self.counts.clone()
+    }
+
+    /// Returns layout of the board.
+    #[inline]
+    #[must_use]
+    pub fn layout(&self) -> BoardLayout {
+        self.layout
+    }
+
+    /// Returns the buffer holding the current generation.
+    #[inline]
+    #[must_use]
+    pub fn buffer(&self) -> Arc<GpuBuffer> {
+        self.output.clone()
+    }
+
+    /// Records the simulation command buffer again with current settings.
+    fn rebuild(&mut self) {
+        self.try_rebuild().expect("Cannot create command buffer");
+    }
+
+    /// Records the simulation command buffer again, see [`Simulation::rebuild`].
+    fn try_rebuild(&mut self) -> Result<(), Error> {
+        self.main_buffer = create_simulation_buffer(
+            &self.compute_queue,
+            self.output.clone(),
+            self.input.clone(),
+            self.counts.clone(),
+            self.timestamps.as_ref(),
+            self.layout,
+            self.workgroup,
+            self.rule,
+            self.neighbourhood,
+            self.topology,
+            self.tiled,
+            self.weights.as_ref(),
+        )?;
+        Ok(())
+    }
+
+    /// Moves the board into the middle of a new, bigger board with given layout.
+    /// The generation, rule and neighbourhood are kept.
+    /// Returns the new output buffer, which replaces the previous one.
+    ///
+    /// # Panics
+    ///
+    /// - when [`Simulation::try_expand`] fails.
+    #[must_use]
+    pub fn expand(&mut self, layout: BoardLayout) -> Arc<GpuBuffer> {
+        self.try_expand(layout, None).expect("Cannot expand board")
+    }
+
+    /// Moves the board into the middle of a new, bigger board, see [`Simulation::expand`].
+    /// The readback and upload of the board are waited for at most `timeout` when it is given.
+    /// The simulation is left untouched when it fails.
+    ///
+    /// # Errors
+    ///
+    /// - when [`Simulation::try_expanded`] fails.
+    ///
+    /// # Panics
+    ///
+    /// - when [`Simulation::try_expanded`] panics.
+    pub fn try_expand(
+        &mut self,
+        layout: BoardLayout,
+        timeout: Option<Duration>,
+    ) -> Result<Arc<GpuBuffer>, Error> {
+        let (expanded, output) = self.try_expanded(layout, timeout)?;
+        *self = expanded;
+        Ok(output)
+    }
+
+    /// Creates a new [`Simulation`] with the board moved into the middle of a new, bigger board,
+    /// the simulation itself is left untouched. Generation, rule, neighbourhood, history depth
+    /// and other settings are kept. Returns the simulation together with the buffer holding its board.
+    /// The readback and upload of the board are waited for at most `timeout` when it is given.
+    ///
+    /// # Errors
+    ///
+    /// - when [`Simulation::try_readback`] fails.
+    /// - when the buffer allocation fails.
+    /// - when [`Simulation::try_new`] fails.
+    /// - when the command buffer creation or recording fails.
+    /// - when the readback or upload of the board fails or does not finish in time.
+    ///
+    /// # Panics
+    ///
+    /// - when the new board is smaller than the current one.
+    /// - when the new board does not fit in host memory.
+    pub fn try_expanded(
+        &self,
+        layout: BoardLayout,
+        timeout: Option<Duration>,
+    ) -> Result<(Self, Arc<GpuBuffer>), Error> {
+        let (width, height) = self.layout.size();
+        let (expanded_width, expanded_height) = layout.size();
+        assert!(
+            expanded_width >= width && expanded_height >= height,
+            "Board cannot shrink"
+        );
+        let left = (expanded_width - width) / 2;
+        let top = (expanded_height - height) / 2;
+
+        let words = self.try_read_words(timeout)?;
+        let len = usize::try_from(layout.len()).expect("Board does not fit in memory");
+        let mut expanded = vec![0; len];
+        for y in 0..height {
+            for x in 0..width {
+                let cell = self.layout.get(&words, x, y);
+                layout.set(&mut expanded, left + x, top + y, cell);
+            }
+        }
+
+        let output = vulkan::try_create_gpu_buffer(self.compute_queue.device(), layout, true)?;
+        let mut expanded_simulation = Self::try_new(
+            self.compute_queue.clone(),
+            output.clone(),
+            layout,
+            self.rule,
+            self.neighbourhood,
+            self.topology,
+            self.workgroup,
+        )?;
+        if self.counts.is_some() {
+            expanded_simulation.counts =
+                Some(create_count_buffer(self.compute_queue.device(), layout)?);
+        }
+        expanded_simulation.tiled = self.tiled;
+        expanded_simulation.weights = self.weights;
+        expanded_simulation.try_rebuild()?;
+        expanded_simulation.try_set_history_depth(self.history.slots.len())?;
+        expanded_simulation.generation = self.generation;
+        vulkan::try_upload_words(&self.compute_queue, output.clone(), expanded, timeout)?;
+        Ok((expanded_simulation, output))
+    }
+
+    /// Returns how long the dispatch of the last computed generation took on the GPU,
+    /// `None` when timestamps are not supported or the generation has not finished yet.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn last_step_duration(&self) -> Option<Duration> {
+        let timestamps = self.timestamps.as_ref()?;
+        let mut ticks = [0_u64; 2];
+        let available = timestamps
+            .queries_range(0..2)?
+            .get_results(&mut ticks, QueryResultFlags::default())
+            .ok()?;
+        if !available {
+            return None;
+        }
+        let period = self
+            .compute_queue
+            .device()
+            .physical_device()
+            .properties()
+            .timestamp_period;
+        let nanoseconds = ticks[1].saturating_sub(ticks[0]) as f64 * f64::from(period);
+        Some(Duration::from_secs_f64(nanoseconds / 1e9))
+    }
+
+    /// Returns width and height of work groups used by the compute shaders.
+    #[inline]
+    #[must_use]
+    pub fn workgroup(&self) -> u32 {
+        self.workgroup
+    }
+
+    /// Compares the current generation with the two previous ones and waits for the result.
+    /// Returns `Some(1)` when the board did not change in the last generation,
+    /// `Some(2)` when it is the same as two generations ago and `None` otherwise.
+    ///
+    /// Generations computed before the board was last randomized, cleared
+    /// or stepped back are not taken into account.
+    ///
+    /// # Panics
+    ///
+    /// - when [`Simulation::compare_generations`] fails.
+    /// - when waiting for the comparison fails.
+    /// - when [`Simulation::compared_period`] fails.
+    #[must_use]
+    pub fn period(&self) -> Option<u32> {
+        vulkan::try_wait(self.compare_generations()?, None)
             .expect("Cannot wait for command buffer");
-        self.main_buffer
+        self.compared_period()
+    }
+
+    /// Starts comparing the current generation with the two previous ones, the result is read
+    /// by [`Simulation::compared_period`] once the returned [`GpuFuture`] finishes.
+    /// Returns `None` when there are no generations to compare, see [`Simulation::period`].
+    ///
+    /// # Panics
+    ///
+    /// - when the command buffer execution fails.
+    #[must_use]
+    pub fn compare_generations(&self) -> Option<Box<dyn GpuFuture>> {
+        if self.chain == 0 {
+            return None;
+        }
+        let future = self
+            .compare_buffer
             .clone()
             .execute(self.compute_queue.clone())
-            .expect("Cannot execute command buffer")
-            .then_signal_semaphore_and_flush()
-            .expect("Cannot flush command buffer")
-            .boxed()
+            .expect("Cannot execute command buffer");
+        Some(future.boxed())
+    }
+
+    /// Returns the period found by the last finished [`Simulation::compare_generations`],
+    /// see [`Simulation::period`].
+    ///
+    /// # Panics
+    ///
+    /// - when the changes buffer cannot be read.
+    #[must_use]
+    pub fn compared_period(&self) -> Option<u32> {
+        if self.chain == 0 {
+            return None;
+        }
+        let changes = self.changes.read().expect("Cannot read changes buffer");
+        match (changes[0], changes[1]) {
+            (0, _) => Some(1),
+            (_, 0) if self.chain >= 2 => Some(2),
+            _ => None,
+        }
+    }
+
+    /// Makes [`Simulation::period`] ignore generations computed so far,
+    /// it has to be called after the board is edited by other pipelines.
+    #[inline]
+    pub fn invalidate_period(&mut self) {
+        self.chain = 0;
+    }
+
+    /// Returns the smallest rectangle containing every live cell of the current generation,
+    /// `None` when the board is empty. Waits for the scan to finish.
+    #[inline]
+    #[must_use]
+    pub fn live_bounds(&self) -> Option<LiveBounds> {
+        self.bounds.scan()
+    }
+
+    /// Starts scanning the current generation for live cells, the result is read
+    /// by [`Simulation::scanned_live_bounds`] once the returned [`GpuFuture`] finishes.
+    #[inline]
+    #[must_use]
+    pub fn scan_live_bounds(&self) -> Box<dyn GpuFuture> {
+        self.bounds.start()
+    }
+
+    /// Returns live cells bounds found by the last finished [`Simulation::scan_live_bounds`],
+    /// `None` when the board was empty.
+    #[inline]
+    #[must_use]
+    pub fn scanned_live_bounds(&self) -> Option<LiveBounds> {
+        self.bounds.result()
+    }
+
+    /// Creates a new [`CellProbe`] reading single cells of the current generation.
+    #[must_use]
+    pub fn probe(&self) -> CellProbe {
+        CellProbe::new(self.compute_queue.clone(), self.output.clone(), self.layout)
+    }
+
+    /// Returns the rule used to compute next generations.
+    #[inline]
+    #[must_use]
+    pub fn rule(&self) -> Rule {
+        self.rule
     }
 
-    /// Runs randomizer to fill the buffer with random values.
+    /// Returns number of generations computed since the board was last randomized or cleared.
+    #[inline]
+    #[must_use]
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Creates a new [`Readback`] of the buffer holding current generation.
+    ///
+    /// # Panics
+    ///
+    /// - when [`Simulation::try_readback`] fails.
+    #[must_use]
+    pub fn readback(&self) -> Readback {
+        self.try_readback().expect("Cannot create readback")
+    }
+
+    /// Creates a new [`Readback`] of the buffer holding current generation.
+    ///
+    /// # Errors
+    ///
+    /// - when [`Readback::try_new`] fails.
+    pub fn try_readback(&self) -> Result<Readback, Error> {
+        Readback::try_new(self.compute_queue.clone(), self.output.clone(), self.layout)
+    }
+
+    /// Runs randomizer to fill the buffer with random values mirrored according to [`Symmetry`]
+    /// and following given [`Distribution`].
+    /// Resets the generation counter, unless `keep_cells` is set and random alive cells
+    /// are only added to the board like any other edit.
+    /// Previous generations are not taken into account by [`Simulation::period`] either way.
     /// Returns a new [`GpuFuture`] that can be used to wait for the randomizer to finish.
     #[must_use]
-    pub fn randomize(&self) -> Box<dyn GpuFuture> {
-        self.randomizer.run()
+    pub fn randomize(
+        &mut self,
+        symmetry: Symmetry,
+        distribution: Distribution,
+        keep_cells: bool,
+    ) -> Box<dyn GpuFuture> {
+        if !keep_cells {
+            self.generation = 0;
+            self.history.len = 0;
+        }
+        self.chain = 0;
+        self.randomizer.run(symmetry, distribution, keep_cells)
     }
 
     /// Runs the clean pipeline to fill the buffer with zeros.
+    /// Resets the generation counter.
     /// Returns a new [`GpuFuture`] that can be used to wait for the clean pipeline to finish.
     ///
     /// # Panics
     ///
     /// - when the command buffer execution fails.
     #[must_use]
-    pub fn clear(&self) -> Box<dyn GpuFuture> {
+    pub fn clear(&mut self) -> Box<dyn GpuFuture> {
+        self.generation = 0;
+        self.history.len = 0;
+        self.chain = 0;
         self.clear_buffer
             .clone()
             .execute(self.compute_queue.clone())
             .expect("Cannot execute command buffer")
             .boxed()
     }
+
+    /// Mirrors the whole board along given axis.
+    /// Returns a new [`GpuFuture`] that can be used to wait for the board to be mirrored.
+    ///
+    /// # Panics
+    ///
+    /// - when the command buffer execution fails.
+    #[must_use]
+    pub fn mirror(&mut self, mirror: Mirror) -> Box<dyn GpuFuture> {
+        self.chain = 0;
+        self.mirror_buffers[mirror as usize]
+            .clone()
+            .execute(self.compute_queue.clone())
+            .expect("Cannot execute command buffer")
+            .boxed()
+    }
+
+    /// Rotates the whole board by 90 degrees in given direction.
+    /// Returns a new [`GpuFuture`] that can be used to wait for the board to be rotated
+    /// or `None` when the board is not square and cannot be rotated.
+    ///
+    /// # Panics
+    ///
+    /// - when the command buffer execution fails.
+    #[must_use]
+    pub fn rotate(&mut self, rotation: Rotation) -> Option<Box<dyn GpuFuture>> {
+        let buffers = self.rotate_buffers.as_ref()?;
+        self.chain = 0;
+        Some(
+            buffers[rotation as usize]
+                .clone()
+                .execute(self.compute_queue.clone())
+                .expect("Cannot execute command buffer")
+                .boxed(),
+        )
+    }
+
+    /// Moves every cell of the board by given offset.
+    /// Cells moved over an edge either reappear at the opposite edge when `wrap` is set,
+    /// or are dropped otherwise.
+    /// Returns a new [`GpuFuture`] that can be used to wait for the board to be shifted.
+    ///
+    /// # Panics
+    ///
+    /// - when the command buffer recording fails.
+    /// - when the command buffer execution fails.
+    #[must_use]
+    pub fn shift(&mut self, dx: i32, dy: i32, wrap: bool) -> Box<dyn GpuFuture> {
+        let (width, height) = self.layout.size();
+        let reduce = |offset: i32, size: u32| {
+            let size = i32::try_from(size).unwrap_or(i32::MAX);
+            if wrap {
+                offset.rem_euclid(size)
+            } else {
+                offset.clamp(-size, size)
+            }
+        };
+        let constants = shader::ty::PushConstants {
+            mode: SHIFT_MODE,
+            wrap: wrap.into(),
+            offset: [reduce(dx, width), reduce(dy, height)],
+        };
+
+        self.chain = 0;
+        create_transform_buffer(
+            &self.compute_queue,
+            &self.transform,
+            [self.output.clone(), self.input.clone()],
+            self.layout.group_count(self.workgroup),
+            constants,
+        )
+        .expect("Cannot create command buffer")
+        .execute(self.compute_queue.clone())
+        .expect("Cannot execute command buffer")
+        .boxed()
+    }
+
+    /// Creates a new [`Simulation`] of a board with the same layout, neighbourhood, topology
+    /// and history depth, which computes next generations with given rule.
+    /// It starts from a copy of the current board.
+    /// Returns the simulation together with the buffer holding its board.
+    ///
+    /// # Panics
+    ///
+    /// - when [`Simulation::try_fork`] fails.
+    #[must_use]
+    pub fn fork(&self, rule: Rule) -> (Self, Arc<GpuBuffer>) {
+        self.try_fork(rule, None).expect("Cannot create simulation")
+    }
+
+    /// Creates a new [`Simulation`] computed with given rule, see [`Simulation::fork`].
+    /// The copy of the board is waited for at most `timeout` when it is given.
+    ///
+    /// # Errors
+    ///
+    /// - when the buffer allocation fails.
+    /// - when [`Simulation::try_new`] fails.
+    /// - when the command buffer creation or recording fails.
+    /// - when [`Simulation::try_set_history_depth`] fails.
+    /// - when the copy of the board fails or does not finish in time.
+    ///
+    /// # Panics
+    ///
+    /// - when the command buffer execution fails.
+    pub fn try_fork(
+        &self,
+        rule: Rule,
+        timeout: Option<Duration>,
+    ) -> Result<(Self, Arc<GpuBuffer>), Error> {
+        let buffer = vulkan::try_create_gpu_buffer(self.compute_queue.device(), self.layout, true)?;
+        let mut fork = Self::try_new(
+            self.compute_queue.clone(),
+            buffer.clone(),
+            self.layout,
+            rule,
+            self.neighbourhood,
+            self.topology,
+            self.workgroup,
+        )?;
+        if self.counts.is_some() {
+            fork.counts = Some(create_count_buffer(
+                self.compute_queue.device(),
+                self.layout,
+            )?);
+        }
+        fork.tiled = self.tiled;
+        fork.try_rebuild()?;
+        fork.try_set_history_depth(self.history_depth())?;
+        let future = fork.copy_from(self);
+        vulkan::try_wait(future, timeout)?;
+        Ok((fork, buffer))
+    }
+
+    /// Replaces the board with the current board of `source`, both boards need the same layout.
+    /// The generation counter is copied and the history is forgotten.
+    /// Returns a new [`GpuFuture`] that can be used to wait for the copy to finish.
+    ///
+    /// # Panics
+    ///
+    /// - when layouts of the boards differ.
+    /// - when the command buffer recording fails.
+    /// - when the command buffer execution fails.
+    #[must_use]
+    pub fn copy_from(&mut self, source: &Self) -> Box<dyn GpuFuture> {
+        assert_eq!(
+            self.layout, source.layout,
+            "Boards must have the same layout"
+        );
+        self.generation = source.generation;
+        self.history.len = 0;
+        self.chain = 0;
+        create_copy_buffer(
+            &self.compute_queue,
+            source.output.clone(),
+            self.output.clone(),
+        )
+        .expect("Cannot create command buffer")
+        .execute(self.compute_queue.clone())
+        .expect("Cannot execute command buffer")
+        .boxed()
+    }
+
+    /// Replaces the board with given [`Pattern`] centred on an empty board
+    /// and waits for the upload to finish. Cells outside of the board are dropped.
+    /// Resets the generation counter.
+    ///
+    /// # Panics
+    ///
+    /// - when [`Simulation::try_load_pattern`] fails.
+    pub fn load_pattern(&mut self, pattern: &Pattern) {
+        self.try_load_pattern(pattern, None)
+            .expect("Cannot upload board");
+    }
+
+    /// Replaces the board with given [`Pattern`], see [`Simulation::load_pattern`].
+    /// The upload is waited for at most `timeout` when it is given.
+    ///
+    /// # Errors
+    ///
+    /// - when [`vulkan::try_upload_words`] fails.
+    ///
+    /// # Panics
+    ///
+    /// - when the board does not fit in host memory.
+    pub fn try_load_pattern(
+        &mut self,
+        pattern: &Pattern,
+        timeout: Option<Duration>,
+    ) -> Result<(), Error> {
+        let words = self.pattern_words(pattern);
+        vulkan::try_upload_words(&self.compute_queue, self.output.clone(), words, timeout)?;
+        self.generation = 0;
+        self.history.len = 0;
+        self.chain = 0;
+        Ok(())
+    }
+
+    /// Changes the generation counter, so that a restored board continues from its generation.
+    /// The history is forgotten.
+    pub fn set_generation(&mut self, generation: u64) {
+        self.generation = generation;
+        self.history.len = 0;
+        self.chain = 0;
+    }
+
+    /// Copies the current generation into host memory and waits for the copy to finish.
+    /// Returns value of every cell, row after row, unpacked from words of the board.
+    ///
+    /// # Panics
+    ///
+    /// - when [`Simulation::try_read_cells`] fails.
+    #[must_use]
+    pub fn read_cells(&self) -> Vec<u32> {
+        self.try_read_cells(None).expect("Cannot read board")
+    }
+
+    /// Returns value of every cell, see [`Simulation::read_cells`].
+    /// The copy is waited for at most `timeout` when it is given.
+    ///
+    /// # Errors
+    ///
+    /// - when [`Simulation::try_readback`] fails.
+    /// - when the copy fails or does not finish in time.
+    pub fn try_read_cells(&self, timeout: Option<Duration>) -> Result<Vec<u32>, Error> {
+        let words = self.try_read_words(timeout)?;
+        let (width, height) = self.layout.size();
+        Ok((0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| self.layout.get(&words, x, y))
+            .collect())
+    }
+
+    /// Copies words of the current generation into host memory,
+    /// waiting for the copy at most `timeout` when it is given.
+    fn try_read_words(&self, timeout: Option<Duration>) -> Result<Vec<u32>, Error> {
+        let readback = self.try_readback()?;
+        vulkan::try_wait(readback.copy(), timeout)?;
+        Ok(readback.copied())
+    }
+
+    /// Replaces the board with given values of every cell, row after row,
+    /// and waits for the upload to finish. Bits of values that do not fit into a cell
+    /// are ignored. Resets the generation counter.
+    ///
+    /// # Panics
+    ///
+    /// - when number of `cells` differs from number of cells of the board.
+    /// - when the board does not fit in host memory.
+    /// - when the upload of the board fails.
+    pub fn write_cells(&mut self, cells: &[u32]) {
+        let (width, height) = self.layout.size();
+        assert_eq!(
+            cells.len() as u64,
+            u64::from(width) * u64::from(height),
+            "Cells do not match the board"
+        );
+        let len = usize::try_from(self.layout.len()).expect("Board does not fit in memory");
+        let mut words = vec![0; len];
+        let positions = (0..height).flat_map(|y| (0..width).map(move |x| (x, y)));
+        for ((x, y), &value) in positions.zip(cells) {
+            self.layout.set(&mut words, x, y, value);
+        }
+        vulkan::upload_words(&self.compute_queue, self.output.clone(), words);
+        self.generation = 0;
+        self.history.len = 0;
+        self.chain = 0;
+    }
+
+    /// Returns words of an empty board with given [`Pattern`] centred on it,
+    /// as loaded by [`Simulation::load_pattern`]. Cells outside of the board are dropped.
+    ///
+    /// # Panics
+    ///
+    /// - when the board does not fit in host memory.
+    #[must_use]
+    pub fn pattern_words(&self, pattern: &Pattern) -> Vec<u32> {
+        let (width, height) = self.layout.size();
+        let len = usize::try_from(self.layout.len()).expect("Board does not fit in memory");
+        let mut words = vec![0; len];
+        self.place(&mut words, pattern, [width / 2, height / 2]);
+        words
+    }
+
+    /// Adds live cells of given [`Pattern`] centred at `position` to the board
+    /// and waits for the upload to finish. Other cells are kept, cells outside of the board
+    /// are dropped. The generation counter is kept.
+    ///
+    /// # Panics
+    ///
+    /// - when [`Simulation::try_stamp`] fails.
+    pub fn stamp(&mut self, pattern: &Pattern, position: [u32; 2]) {
+        self.try_stamp(pattern, position, None)
+            .expect("Cannot stamp pattern");
+    }
+
+    /// Adds live cells of given [`Pattern`] to the board, see [`Simulation::stamp`].
+    /// The readback and upload are waited for at most `timeout` when it is given.
+    ///
+    /// # Errors
+    ///
+    /// - when [`Simulation::try_readback`] fails.
+    /// - when the readback or upload of the board fails or does not finish in time.
+    pub fn try_stamp(
+        &mut self,
+        pattern: &Pattern,
+        position: [u32; 2],
+        timeout: Option<Duration>,
+    ) -> Result<(), Error> {
+        let mut words = self.try_read_words(timeout)?;
+        self.place(&mut words, pattern, position);
+
+        vulkan::try_upload_words(&self.compute_queue, self.output.clone(), words, timeout)?;
+        self.chain = 0;
+        Ok(())
+    }
+
+    /// Sets live cells of given [`Pattern`] centred at `centre` in the words of the board.
+    fn place(&self, words: &mut [u32], pattern: &Pattern, centre: [u32; 2]) {
+        let (width, height) = self.layout.size();
+        let (pattern_width, pattern_height) = pattern.size();
+        let left = i64::from(centre[0]) - i64::from(pattern_width) / 2;
+        let top = i64::from(centre[1]) - i64::from(pattern_height) / 2;
+
+        for &[x, y] in pattern.cells() {
+            let x = u32::try_from(left + i64::from(x))
+                .ok()
+                .filter(|&x| x < width);
+            let y = u32::try_from(top + i64::from(y))
+                .ok()
+                .filter(|&y| y < height);
+            if let (Some(x), Some(y)) = (x, y) {
+                self.layout.set(words, x, y, 1);
+            }
+        }
+    }
+}
+
+/// Records a command buffer copying the whole `source` buffer into `destination`.
+/// It can be submitted again before the previous submission finishes,
+/// as history slots are saved by chained generations.
+///
+/// # Errors
+///
+/// - when the command buffer creation fails.
+/// - when the command buffer recording fails.
+fn create_copy_buffer(
+    queue: &Queue,
+    source: Arc<GpuBuffer>,
+    destination: Arc<GpuBuffer>,
+) -> Result<Arc<CommandBuffer>, Error> {
+    let mut builder = AutoCommandBufferBuilder::primary(
+        queue.device().clone(),
+        queue.queue_family_index(),
+        CommandBufferUsage::SimultaneousUse,
+    )?;
+
+    builder.copy_buffer(CopyBufferInfo::buffers(source, destination))?;
+
+    Ok(Arc::new(builder.build()?))
+}
+
+/// Creates a new [`ComputePipeline`] moving cells of the board to new positions,
+/// reading them from `input` and writing them into `output`.
+///
+/// # Errors
+///
+/// - when the pipeline creation fails.
+/// - when the descriptor set creation fails.
+fn create_transform_pipeline(
+    queue: &Queue,
+    output: Arc<GpuBuffer>,
+    input: Arc<GpuBuffer>,
+    layout: BoardLayout,
+    workgroup: u32,
+) -> Result<Transform, Error> {
+    let device = queue.device().clone();
+
+    let shader = shader::load_transform(device.clone())?;
+    let pipeline = ComputePipeline::new(
+        device,
+        shader.entry_point("main").ok_or(Error::MissingEntryPoint)?,
+        &shader::TransformSpecializationConstants {
+            width: layout.size().0,
+            height: layout.size().1,
+            cellBits: layout.cell_bits(),
+            constant_100: workgroup,
+            constant_101: workgroup,
+        },
+        None,
+        |_| {},
+    )?;
+
+    let descriptor = PersistentDescriptorSet::new(
+        pipeline
+            .layout()
+            .set_layouts()
+            .get(0)
+            .ok_or(Error::MissingDescriptorSetLayout)?
+            .clone(),
+        [
+            WriteDescriptorSet::buffer(0, output),
+            WriteDescriptorSet::buffer(1, input),
+        ],
+    )?;
+
+    Ok(Transform {
+        pipeline,
+        descriptor,
+    })
+}
+
+/// Records a command buffer that copies the board into `input`
+/// and then writes cells transformed by the [`Transform`] pipeline back into `output`.
+///
+/// # Errors
+///
+/// - when the command buffer creation fails.
+/// - when the command buffer recording fails.
+/// - when the command buffer building fails.
+fn create_transform_buffer(
+    queue: &Queue,
+    transform: &Transform,
+    [output, input]: [Arc<GpuBuffer>; 2],
+    group_count: [u32; 3],
+    constants: shader::ty::PushConstants,
+) -> Result<Arc<CommandBuffer>, Error> {
+    let mut builder = AutoCommandBufferBuilder::primary(
+        queue.device().clone(),
+        queue.queue_family_index(),
+        CommandBufferUsage::MultipleSubmit,
+    )?;
+
+    builder
+        .copy_buffer(CopyBufferInfo::buffers(output, input))?
+        .push_constants(transform.pipeline.layout().clone(), 0, constants)
+        .bind_descriptor_sets(
+            PipelineBindPoint::Compute,
+            transform.pipeline.layout().clone(),
+            0,
+            transform.descriptor.clone(),
+        )
+        .bind_pipeline_compute(transform.pipeline.clone())
+        .dispatch(group_count)?;
+
+    Ok(Arc::new(builder.build()?))
+}
+
+/// Creates a new [`ComputePipeline`] comparing the current generation with two previous ones.
+/// Returns a new [`PrimaryCommandBuffer`] that resets the changes flags and executes the pipeline.
+///
+/// # Errors
+///
+/// - when the pipeline creation fails.
+/// - when the descriptor set creation fails.
+/// - when the command buffer creation fails.
+/// - when the command buffer building fails.
+fn create_compare_buffer(
+    queue: &Queue,
+    [current, previous, older]: [Arc<GpuBuffer>; 3],
+    changes: Arc<CpuAccessibleBuffer<[u32]>>,
+    layout: BoardLayout,
+    workgroup: u32,
+) -> Result<Arc<CommandBuffer>, Error> {
+    let device = queue.device().clone();
+
+    let shader = shader::load_compare(device.clone())?;
+    let pipeline = ComputePipeline::new(
+        device.clone(),
+        shader.entry_point("main").ok_or(Error::MissingEntryPoint)?,
+        &shader::CompareSpecializationConstants {
+            width: layout.size().0,
+            height: layout.size().1,
+            cellBits: layout.cell_bits(),
+            constant_100: workgroup,
+            constant_101: workgroup,
+        },
+        None,
+        |_| {},
+    )?;
+
+    let descriptor = PersistentDescriptorSet::new(
+        pipeline
+            .layout()
+            .set_layouts()
+            .get(0)
+            .ok_or(Error::MissingDescriptorSetLayout)?
+            .clone(),
+        [
+            WriteDescriptorSet::buffer(0, current),
+            WriteDescriptorSet::buffer(1, previous),
+            WriteDescriptorSet::buffer(2, older),
+            WriteDescriptorSet::buffer(3, changes.clone()),
+        ],
+    )?;
+
+    let mut builder = AutoCommandBufferBuilder::primary(
+        device,
+        queue.queue_family_index(),
+        CommandBufferUsage::MultipleSubmit,
+    )?;
+
+    builder
+        .fill_buffer(FillBufferInfo::dst_buffer(changes))?
+        .bind_descriptor_sets(
+            PipelineBindPoint::Compute,
+            pipeline.layout().clone(),
+            0,
+            descriptor,
+        )
+        .bind_pipeline_compute(pipeline)
+        .dispatch(layout.group_count(workgroup))?;
+
+    Ok(Arc::new(builder.build()?))
 }
 
 /// Creates a new [`ComputePipeline`] that can be used to compute the next generation of the game of life.
 /// Returns a new [`PrimaryCommandBuffer`] that can be used to execute the pipeline.
+/// Neighbour counts are written into `counts` when it is given.
+/// Timestamps are written into the first two queries of `timestamps`
+/// before and after the dispatch, when it is given.
+/// Words are loaded into shared memory first when `tiled` is set
+/// and neighbours are summed with `weights` when they are given.
 ///
-/// # Panics
+/// # Errors
 ///
 /// - when the pipeline creation fails.
 /// - when the descriptor set creation fails.
 /// - when the command buffer creation fails.
+/// - when the query recording fails.
 /// - when the command buffer building fails.
 #[inline]
-fn create_simulation_buffer(
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn create_simulation_buffer(
     queue: &Queue,
     output: Arc<GpuBuffer>,
     input: Arc<GpuBuffer>,
-    size: (u32, u32),
-    group_size: [u32; 3],
-) -> Arc<CommandBuffer> {
+    counts: Option<Arc<GpuBuffer>>,
+    timestamps: Option<&Arc<QueryPool>>,
+    layout: BoardLayout,
+    workgroup: u32,
+    rule: Rule,
+    neighbourhood: Neighbourhood,
+    topology: Topology,
+    tiled: bool,
+    weights: Option<&WeightedRule>,
+) -> Result<Arc<CommandBuffer>, Error> {
+    let weighted = weights.copied().unwrap_or_default();
+    let [weight0, weight1, weight2, weight3, weight4, weight5, weight6, weight7] = weighted.weights;
     let device = queue.device().clone();
 
-    let shader = shader::load_simulation(device.clone()).expect("Cannot load compute shader");
+    let shader = shader::load_simulation(device.clone())?;
     let pipeline = ComputePipeline::new(
         device.clone(),
-        shader.entry_point("main").expect("Cannot find entry point"),
+        shader.entry_point("main").ok_or(Error::MissingEntryPoint)?,
         &shader::SimulationSpecializationConstants {
-            width: size.0,
-            height: size.1,
+            width: layout.size().0,
+            height: layout.size().1,
+            birth: rule.birth(),
+            survival: rule.survival(),
+            cellBits: layout.cell_bits(),
+            neighbourhood: neighbourhood.id(),
+            states: rule.states(),
+            topology: topology.id(),
+            countNeighbours: counts.is_some().into(),
+            tiled: tiled.into(),
+            weighted: weights.is_some().into(),
+            weight0,
+            weight1,
+            weight2,
+            weight3,
+            weight4,
+            weight5,
+            weight6,
+            weight7,
+            birthMin: weighted.birth[0],
+            birthMax: weighted.birth[1],
+            survivalMin: weighted.survival[0],
+            survivalMax: weighted.survival[1],
+            constant_100: workgroup,
+            constant_101: workgroup,
         },
         None,
         |_| {},
-    )
-    .expect("Cannot create compute pipeline");
+    )?;
 
     let descriptor = PersistentDescriptorSet::new(
         pipeline
             .layout()
             .set_layouts()
             .get(0)
-            .expect("Cannot get descriptor set layout")
+            .ok_or(Error::MissingDescriptorSetLayout)?
             .clone(),
         [
             WriteDescriptorSet::buffer(0, output),
-            WriteDescriptorSet::buffer(1, input),
+            WriteDescriptorSet::buffer(1, input.clone()),
+            // Without counting the binding is never written, any buffer can take it.
+            WriteDescriptorSet::buffer(2, counts.unwrap_or(input)),
         ],
-    )
-    .expect("Cannot create descriptor set");
+    )?;
 
     let mut builder = AutoCommandBufferBuilder::primary(
         device,
         queue.queue_family_index(),
-        vulkano::command_buffer::CommandBufferUsage::MultipleSubmit,
-    )
-    .expect("Cannot create command buffer builder");
+        CommandBufferUsage::SimultaneousUse,
+    )?;
+
+    if let Some(timestamps) = timestamps {
+        // SAFETY: the queries are reset before they are written in every submission
+        // and generations are executed one after another, so no other command
+        // buffer uses them meanwhile. Results are read only after the submission finishes.
+        unsafe {
+            builder
+                .reset_query_pool(timestamps.clone(), 0..2)?
+                .write_timestamp(timestamps.clone(), 0, PipelineStage::TopOfPipe)?;
+        }
+    }
 
     builder
         .bind_descriptor_sets(
@@ -215,8 +1519,60 @@ fn create_simulation_buffer(
             descriptor,
         )
         .bind_pipeline_compute(pipeline)
-        .dispatch(group_size)
-        .expect("Cannot record command buffer");
+        .dispatch(layout.group_count(workgroup))?;
 
-    Arc::new(builder.build().expect("Cannot build command buffer"))
+    if let Some(timestamps) = timestamps {
+        // SAFETY: the query was reset above, see the first timestamp.
+        unsafe {
+            builder.write_timestamp(timestamps.clone(), 1, PipelineStage::BottomOfPipe)?;
+        }
+    }
+
+    Ok(Arc::new(builder.build()?))
+}
+
+/// Creates a pool of two timestamp queries measuring the dispatch of a generation,
+/// `None` when the queue family of `queue` does not support timestamps.
+///
+/// # Errors
+///
+/// - when the query pool creation fails.
+fn create_timestamps(queue: &Queue) -> Result<Option<Arc<QueryPool>>, Error> {
+    let device = queue.device();
+    let supported = device
+        .physical_device()
+        .queue_family_properties()
+        .get(queue.queue_family_index() as usize)
+        .and_then(|family| family.timestamp_valid_bits)
+        .is_some();
+    if !supported {
+        return Ok(None);
+    }
+    let pool = QueryPool::new(
+        device.clone(),
+        QueryPoolCreateInfo {
+            query_count: 2,
+            ..QueryPoolCreateInfo::query_type(QueryType::Timestamp)
+        },
+    )?;
+    Ok(Some(pool))
+}
+
+/// Creates a buffer for neighbour counts of every cell of the board.
+///
+/// # Errors
+///
+/// - when the buffer allocation fails.
+fn create_count_buffer(device: &Arc<Device>, layout: BoardLayout) -> Result<Arc<GpuBuffer>, Error> {
+    let buffer = DeviceLocalBuffer::array(
+        device.clone(),
+        layout.count_len(),
+        BufferUsage {
+            storage_buffer: true,
+            transfer_src: true,
+            ..BufferUsage::empty()
+        },
+        device.active_queue_family_indices().iter().copied(),
+    )?;
+    Ok(buffer)
 }
@@ -0,0 +1,49 @@
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+/// Returns values of cells of an empty board with given dimensions and a random square
+/// of `soup_size` cells in its centre, a soup. Every cell of the square is alive
+/// with probability `density` and the same `seed` always gives the same soup.
+/// The square is cropped to the board when it is bigger.
+#[must_use]
+pub fn soup_cells(
+    (width, height): (u32, u32),
+    soup_size: u32,
+    density: f64,
+    seed: u64,
+) -> Vec<u32> {
+    let mut cells = vec![0; width as usize * height as usize];
+    let mut rng = StdRng::seed_from_u64(seed);
+    let (soup_width, soup_height) = (soup_size.min(width), soup_size.min(height));
+    let (left, top) = ((width - soup_width) / 2, (height - soup_height) / 2);
+    for y in top..top + soup_height {
+        for x in left..left + soup_width {
+            cells[(y * width + x) as usize] = rng.gen_bool(density).into();
+        }
+    }
+    cells
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn soup_is_centred() {
+        let cells = soup_cells((6, 4), 2, 1.0, 0);
+        #[rustfmt::skip]
+        let expected = [
+            0, 0, 0, 0, 0, 0,
+            0, 0, 1, 1, 0, 0,
+            0, 0, 1, 1, 0, 0,
+            0, 0, 0, 0, 0, 0,
+        ];
+        assert_eq!(cells, expected);
+    }
+
+    #[test]
+    fn same_seed_gives_same_soup() {
+        let soup = |seed| soup_cells((32, 32), 16, 0.5, seed);
+        assert_eq!(soup(7), soup(7));
+        assert_ne!(soup(7), soup(8));
+    }
+}
@@ -0,0 +1,46 @@
+use clap::ValueEnum;
+
+/// Way the edges of the board are connected.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum Topology {
+    /// Cells outside of the board are dead.
+    #[default]
+    Bounded,
+    /// Opposite edges are connected.
+    Torus,
+    /// Opposite edges are connected, left and right edges with the board flipped vertically.
+    KleinBottle,
+    /// Opposite edges are connected, both with the board flipped.
+    CrossSurface,
+}
+
+impl Topology {
+    /// All topologies in order they should be displayed.
+    pub const ALL: [Self; 4] = [
+        Self::Bounded,
+        Self::Torus,
+        Self::KleinBottle,
+        Self::CrossSurface,
+    ];
+
+    /// Returns human readable name of the topology.
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Bounded => "Bounded",
+            Self::Torus => "Torus",
+            Self::KleinBottle => "Klein bottle",
+            Self::CrossSurface => "Cross-surface",
+        }
+    }
+
+    /// Returns identifier of the topology understood by the shader.
+    pub(crate) fn id(self) -> u32 {
+        match self {
+            Self::Bounded => 0,
+            Self::Torus => 1,
+            Self::KleinBottle => 2,
+            Self::CrossSurface => 3,
+        }
+    }
+}
@@ -3,12 +3,26 @@
 //! It can only be compiled using cargo as it requires
 //! environment variables at compile time to be set.
 //!
-use std::sync::Arc;
+use std::{mem, sync::Arc, time::Duration};
 
+use clap::ValueEnum;
+use log::Level;
 use vulkano::{
-    buffer::{BufferUsage, DeviceLocalBuffer},
-    device::Device,
-    instance::{InstanceCreateInfo, InstanceExtensions},
+    buffer::{BufferUsage, CpuAccessibleBuffer, DeviceLocalBuffer},
+    command_buffer::{
+        AutoCommandBufferBuilder, CommandBufferUsage, CopyBufferInfo, PrimaryCommandBuffer,
+    },
+    device::{Device, Queue},
+    image::SampleCount,
+    instance::{
+        debug::{
+            DebugUtilsMessageSeverity, DebugUtilsMessageType, DebugUtilsMessengerCreateInfo,
+            Message,
+        },
+        InstanceCreateInfo, InstanceExtensions,
+    },
+    swapchain::PresentMode,
+    sync::GpuFuture,
     Version,
 };
 use vulkano_util::{
@@ -17,18 +31,54 @@ use vulkano_util::{
     window::WindowDescriptor,
 };
 use winit::{
+    dpi::PhysicalSize,
+    error::OsError,
     event_loop::EventLoop,
-    window::{Window, WindowBuilder},
+    window::{Icon, Window, WindowBuilder},
 };
 
-use crate::GpuBuffer;
+use crate::{BoardLayout, Error, GpuBuffer};
 
 static APPLICATION_NAME: &str = env!("CARGO_PKG_NAME");
 
+/// PNG image shown as the icon of the window.
+static ICON: &[u8] = include_bytes!("icon.png");
+
+/// Number of storage buffers bound at once by the compute shader using the most of them.
+const STORAGE_BUFFERS: u32 = 4;
+
+/// Lowest severity of validation layer messages that are reported.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+pub enum ValidationLevel {
+    /// Only errors.
+    Error,
+    /// Errors and warnings.
+    #[default]
+    Warning,
+    /// Errors, warnings and informational messages.
+    Info,
+    /// Every message including diagnostics of the loader and the layers.
+    Verbose,
+}
+
+impl ValidationLevel {
+    /// Returns severities of messages reported at this level.
+    fn severity(self) -> DebugUtilsMessageSeverity {
+        DebugUtilsMessageSeverity {
+            error: true,
+            warning: self >= Self::Warning,
+            information: self >= Self::Info,
+            verbose: self >= Self::Verbose,
+            ..DebugUtilsMessageSeverity::empty()
+        }
+    }
+}
+
 /// Creates [`VulkanoContext`] with custom values.
 ///
 /// It provides application name and version from `Cargo.toml`.
-/// On debug compilation it enables `VK_LAYER_KHRONOS_validation` layer.
+/// On debug compilation it enables `VK_LAYER_KHRONOS_validation` layer
+/// and reports its messages of at least `validation` severity through [`log`].
 /// On macOS it enables `VK_KHR_portability_subset`.
 ///
 /// # Panics
@@ -37,7 +87,7 @@ static APPLICATION_NAME: &str = env!("CARGO_PKG_NAME");
 /// - when cargo version numbers cannot be parsed.
 #[inline]
 #[must_use]
-pub fn vulkano_context() -> VulkanoContext {
+pub fn vulkano_context(validation: ValidationLevel) -> VulkanoContext {
     static PARSE_ERROR: &str = "Cargo version is not valid.";
 
     VulkanoContext::new(VulkanoConfig {
@@ -50,67 +100,179 @@ pub fn vulkano_context() -> VulkanoContext {
             },
             #[cfg(debug_assertions)]
             enabled_layers: vec!["VK_LAYER_KHRONOS_validation".to_string()],
-            enabled_extensions: InstanceExtensions::empty(),
+            enabled_extensions: InstanceExtensions {
+                ext_debug_utils: cfg!(debug_assertions),
+                ..InstanceExtensions::empty()
+            },
             #[cfg(target_os = "macos")]
             enumerate_portability: true,
             ..Default::default()
         },
+        debug_create_info: cfg!(debug_assertions).then(|| DebugUtilsMessengerCreateInfo {
+            message_severity: validation.severity(),
+            message_type: DebugUtilsMessageType::all(),
+            ..DebugUtilsMessengerCreateInfo::user_callback(Arc::new(log_message))
+        }),
         ..Default::default()
     })
 }
 
+/// Logs a message of the validation layer with level matching its severity.
+/// Messages use `vulkan` target, so they can be filtered separately.
+fn log_message(message: &Message<'_>) {
+    let level = if message.severity.error {
+        Level::Error
+    } else if message.severity.warning {
+        Level::Warn
+    } else if message.severity.information {
+        Level::Info
+    } else {
+        Level::Debug
+    };
+    log::log!(
+        target: "vulkan",
+        level,
+        "{}: {}",
+        message.layer_prefix.unwrap_or("unknown layer"),
+        message.description
+    );
+}
+
 /// Creates [`Window`] with custom values.
 ///
-/// Window title is set to application name from `Cargo.toml`.
+/// Window title defaults to application name from `Cargo.toml`
+/// and the icon is the embedded PNG image.
+/// The inner size is `size` in physical pixels, clamped to the primary monitor when it is known.
 ///
-/// # Panics
+/// # Errors
 ///
 /// - when window creating returned an error.
 #[inline]
-fn create_window<T>(event_loop: &EventLoop<T>) -> Window {
+fn create_window<T>(
+    event_loop: &EventLoop<T>,
+    title: Option<&str>,
+    (width, height): (u32, u32),
+) -> Result<Window, OsError> {
+    let (width, height) = match event_loop.primary_monitor() {
+        Some(monitor) => {
+            let available = monitor.size();
+            (width.min(available.width), height.min(available.height))
+        }
+        None => (width, height),
+    };
     WindowBuilder::default()
-        .with_title(APPLICATION_NAME)
+        .with_title(title.unwrap_or(APPLICATION_NAME))
+        .with_window_icon(window_icon())
+        .with_inner_size(PhysicalSize::new(width.max(1), height.max(1)))
         .build(event_loop)
-        .expect("Cannot create window with winit")
+}
+
+/// Decodes the embedded icon, returns `None` when it is not a valid image.
+fn window_icon() -> Option<Icon> {
+    let image = image::load_from_memory(ICON).ok()?.into_rgba8();
+    let (width, height) = image.dimensions();
+    Icon::from_rgba(image.into_raw(), width, height).ok()
 }
 
 /// Creates [`VulkanoWindowRenderer`] with custom values.
+/// The window is titled `title` or the application name when it is `None`
+/// and starts with the inner size of `size` pixels, clamped to the primary monitor.
 ///
 /// # Panics
 ///
-/// - when the underlying Vulkano struct creations fail.
+/// - when [`try_vulkano_renderer`] fails.
 #[inline]
 #[must_use]
 pub fn vulkano_renderer<T>(
     context: &VulkanoContext,
     event_loop: &EventLoop<T>,
+    title: Option<&str>,
+    size: (u32, u32),
 ) -> VulkanoWindowRenderer {
-    VulkanoWindowRenderer::new(
+    try_vulkano_renderer(context, event_loop, title, size).expect("Cannot create window with winit")
+}
+
+/// Creates [`VulkanoWindowRenderer`] with custom values.
+/// The window is titled `title` or the application name when it is `None`
+/// and starts with the inner size of `size` pixels, clamped to the primary monitor.
+///
+/// # Errors
+///
+/// - when the window creation fails.
+///
+/// # Panics
+///
+/// - when the underlying Vulkano struct creations fail,
+///   [`VulkanoWindowRenderer`] does not report them as errors.
+#[inline]
+#[allow(clippy::cast_precision_loss)]
+pub fn try_vulkano_renderer<T>(
+    context: &VulkanoContext,
+    event_loop: &EventLoop<T>,
+    title: Option<&str>,
+    size: (u32, u32),
+) -> Result<VulkanoWindowRenderer, Error> {
+    let window = create_window(event_loop, title, size)?;
+    let inner = window.inner_size();
+    let descriptor = WindowDescriptor {
+        width: inner.width as f32,
+        height: inner.height as f32,
+        ..WindowDescriptor::default()
+    };
+    Ok(VulkanoWindowRenderer::new(
         context,
-        create_window(event_loop),
-        &WindowDescriptor::default(),
+        window,
+        &descriptor,
         |_| {},
-    )
+    ))
 }
 
 /// Creates [`GpuBuffer`] with custom values.
 ///
-/// Size argument is tuple representing width and height of the buffer.
-/// In reality buffer is 1D array of `u32` values with size of size.0 * size.1.
+/// Buffer is 1D array of `u32` words holding cells packed according to the [`BoardLayout`].
 ///
 /// # Panics
 ///
-/// - when the underlying Vulkano struct creations fail.
+/// - when [`try_create_gpu_buffer`] fails.
 #[inline]
 #[must_use]
 pub fn create_gpu_buffer(
     device: &Arc<Device>,
-    size: (u32, u32),
+    layout: BoardLayout,
     transfer_src: bool,
 ) -> Arc<GpuBuffer> {
-    DeviceLocalBuffer::array(
+    try_create_gpu_buffer(device, layout, transfer_src)
+        .expect("Failed to create device local buffer")
+}
+
+/// Creates [`GpuBuffer`] with custom values.
+///
+/// # Errors
+///
+/// - when the buffer is bigger than a storage buffer binding may be.
+/// - when the buffer allocation fails.
+#[inline]
+pub fn try_create_gpu_buffer(
+    device: &Arc<Device>,
+    layout: BoardLayout,
+    transfer_src: bool,
+) -> Result<Arc<GpuBuffer>, Error> {
+    // The whole buffer is bound at once, a bigger one would be read past the binding range.
+    let requested = layout.len().saturating_mul(4);
+    let max_range = device
+        .physical_device()
+        .properties()
+        .max_storage_buffer_range;
+    if requested > u64::from(max_range) {
+        return Err(Error::BufferTooLarge {
+            size: layout.size(),
+            requested,
+            max_range,
+        });
+    }
+    let buffer = DeviceLocalBuffer::array(
         device.clone(),
-        u64::from(size.0) * u64::from(size.1),
+        layout.len(),
         BufferUsage {
             storage_buffer: true,
             transfer_dst: true,
@@ -118,6 +280,273 @@ pub fn create_gpu_buffer(
             ..BufferUsage::empty()
         },
         device.active_queue_family_indices().iter().copied(),
-    )
-    .expect("Failed to create device local buffer")
+    )?;
+    Ok(buffer)
+}
+
+/// Checks whether the device supports square work groups of given size.
+///
+/// # Panics
+///
+/// - when the work group is bigger than the device limits.
+pub fn validate_workgroup(device: &Device, size: u32) {
+    if let Err(error) = try_validate_workgroup(device, size) {
+        panic!("{error}");
+    }
+}
+
+/// Checks whether the device supports square work groups of given size,
+/// including shared memory taken by the tile of the simulation shader.
+///
+/// # Errors
+///
+/// - when the work group is bigger than the device limits.
+/// - when the tile of the work group does not fit into shared memory.
+pub fn try_validate_workgroup(device: &Device, size: u32) -> Result<(), Error> {
+    let properties = device.physical_device().properties();
+    let [max_x, max_y, _] = properties.max_compute_work_group_size;
+    let max_invocations = properties.max_compute_work_group_invocations;
+    if size > max_x || size > max_y || size.saturating_mul(size) > max_invocations {
+        return Err(Error::UnsupportedWorkgroup {
+            size,
+            max_size: [max_x, max_y],
+            max_invocations,
+        });
+    }
+    // The tile holds a word for every invocation and a border of one word around them.
+    let requested = u64::from(size + 2).pow(2) * 4;
+    let available = properties.max_compute_shared_memory_size;
+    if requested > u64::from(available) {
+        return Err(Error::UnsupportedSharedMemory {
+            size,
+            requested,
+            available,
+        });
+    }
+    Ok(())
+}
+
+/// Checks whether the device can draw into framebuffers with given number of samples.
+///
+/// # Errors
+///
+/// - when the device does not support the number of samples, the error lists supported ones.
+pub fn try_validate_samples(device: &Device, samples: SampleCount) -> Result<(), Error> {
+    let counts = device
+        .physical_device()
+        .properties()
+        .framebuffer_color_sample_counts;
+    let supported: Vec<_> = [
+        (SampleCount::Sample1, counts.sample1),
+        (SampleCount::Sample2, counts.sample2),
+        (SampleCount::Sample4, counts.sample4),
+        (SampleCount::Sample8, counts.sample8),
+    ]
+    .into_iter()
+    .filter(|&(_, supported)| supported)
+    .map(|(count, _)| count)
+    .collect();
+    if supported.contains(&samples) {
+        Ok(())
+    } else {
+        Err(Error::UnsupportedSamples {
+            samples: samples as u32,
+            supported: supported.into_iter().map(|count| count as u32).collect(),
+        })
+    }
+}
+
+/// Checks whether the device supports everything the shaders need,
+/// a compute capable queue, enough storage buffers of the board size and push constants.
+///
+/// # Errors
+///
+/// - when any of the requirements is missing, the error lists all of them.
+pub fn try_validate_device(
+    compute_queue: &Queue,
+    layout: BoardLayout,
+    push_constants_size: u32,
+) -> Result<(), Error> {
+    let physical_device = compute_queue.device().physical_device();
+    let properties = physical_device.properties();
+    let mut missing = Vec::new();
+
+    let compute = physical_device
+        .queue_family_properties()
+        .get(compute_queue.queue_family_index() as usize)
+        .map_or(false, |family| family.queue_flags.compute);
+    if !compute {
+        missing.push("a queue supporting compute shaders".to_owned());
+    }
+    if properties.max_per_stage_descriptor_storage_buffers < STORAGE_BUFFERS {
+        missing.push(format!(
+            "{STORAGE_BUFFERS} storage buffers per shader, the device allows {}",
+            properties.max_per_stage_descriptor_storage_buffers
+        ));
+    }
+    let buffer_size = layout.len().saturating_mul(4);
+    if buffer_size > u64::from(properties.max_storage_buffer_range) {
+        missing.push(format!(
+            "storage buffers of {buffer_size} bytes, the device allows {}",
+            properties.max_storage_buffer_range
+        ));
+    }
+    if properties.max_push_constants_size < push_constants_size {
+        missing.push(format!(
+            "{push_constants_size} bytes of push constants, the device allows {}",
+            properties.max_push_constants_size
+        ));
+    }
+
+    if missing.is_empty() {
+        Ok(())
+    } else {
+        Err(Error::UnsupportedDevice {
+            name: properties.device_name.clone(),
+            missing,
+        })
+    }
+}
+
+/// Checks whether `requested` bytes fit into given fraction of the biggest device local heap.
+///
+/// # Errors
+///
+/// - when the requested memory is bigger than the allowed part of the heap.
+#[allow(clippy::cast_possible_truncation)]
+#[allow(clippy::cast_precision_loss)]
+#[allow(clippy::cast_sign_loss)]
+pub fn try_validate_memory(device: &Device, requested: u64, fraction: f64) -> Result<(), Error> {
+    let heap = device
+        .physical_device()
+        .memory_properties()
+        .memory_heaps
+        .iter()
+        .filter(|heap| heap.flags.device_local)
+        .map(|heap| heap.size)
+        .max()
+        .unwrap_or_default();
+    let available = (heap as f64 * fraction) as u64;
+    if requested <= available {
+        Ok(())
+    } else {
+        Err(Error::OutOfMemory {
+            requested,
+            available,
+        })
+    }
+}
+
+/// Uploads words of the board into given [`GpuBuffer`] and waits for the upload to finish.
+///
+/// # Panics
+///
+/// - when [`try_upload_words`] fails.
+pub fn upload_words(queue: &Arc<Queue>, destination: Arc<GpuBuffer>, words: Vec<u32>) {
+    try_upload_words(queue, destination, words, None).expect("Cannot upload board");
+}
+
+/// Uploads words of the board into given [`GpuBuffer`] and waits for the upload to finish,
+/// at most `timeout` when it is given, see [`try_wait`].
+///
+/// # Errors
+///
+/// - when the staging buffer creation fails.
+/// - when the command buffer recording fails.
+/// - when [`try_wait`] fails.
+///
+/// # Panics
+///
+/// - when the command buffer execution fails.
+pub fn try_upload_words(
+    queue: &Arc<Queue>,
+    destination: Arc<GpuBuffer>,
+    words: Vec<u32>,
+    timeout: Option<Duration>,
+) -> Result<(), Error> {
+    let device = queue.device().clone();
+
+    let staging = CpuAccessibleBuffer::from_iter(
+        device.clone(),
+        BufferUsage {
+            transfer_src: true,
+            ..BufferUsage::empty()
+        },
+        false,
+        words,
+    )?;
+
+    let mut builder = AutoCommandBufferBuilder::primary(
+        device,
+        queue.queue_family_index(),
+        CommandBufferUsage::OneTimeSubmit,
+    )?;
+    builder.copy_buffer(CopyBufferInfo::buffers(staging, destination))?;
+
+    let future = builder
+        .build()?
+        .execute(queue.clone())
+        .expect("Cannot execute command buffer");
+    try_wait(future, timeout)
+}
+
+/// Flushes given [`GpuFuture`] and waits for it to finish, at most `timeout` when it is given.
+///
+/// The fence is leaked when waiting fails, dropping it would wait for the GPU again
+/// without a timeout. [`Error::is_stall`] tells whether the GPU did not finish in time.
+///
+/// # Errors
+///
+/// - when the flush fails.
+/// - when the work does not finish in time or the device is lost.
+pub fn try_wait(future: impl GpuFuture, timeout: Option<Duration>) -> Result<(), Error> {
+    let fence = future.then_signal_fence_and_flush()?;
+    match fence.wait(timeout) {
+        Ok(()) => Ok(()),
+        Err(error) => {
+            mem::forget(fence);
+            Err(error.into())
+        }
+    }
+}
+
+/// Returns queue used for copies which do not have to run on the compute queue.
+///
+/// [`vulkano_util`] creates no dedicated transfer queue, so the graphics queue is used
+/// when it is separate from the compute queue, it supports transfers as well.
+/// Otherwise the compute queue is returned and all work runs on a single queue.
+#[must_use]
+pub fn transfer_queue(renderer: &VulkanoWindowRenderer) -> Arc<Queue> {
+    let compute = renderer.compute_queue();
+    let graphics = renderer.graphics_queue();
+    if Arc::ptr_eq(&compute, &graphics) {
+        compute
+    } else {
+        graphics
+    }
+}
+
+/// Returns [`PresentMode`] that should be used for given vsync setting.
+///
+/// With vsync enabled [`PresentMode::Fifo`] is used as it is always supported.
+/// Otherwise [`PresentMode::Mailbox`] is preferred over [`PresentMode::Immediate`].
+/// When the surface supports neither of them [`PresentMode::Fifo`] is returned.
+#[must_use]
+pub fn present_mode(renderer: &VulkanoWindowRenderer, vsync: bool) -> PresentMode {
+    if vsync {
+        return PresentMode::Fifo;
+    }
+
+    let supported: Vec<PresentMode> = renderer
+        .graphics_queue()
+        .device()
+        .physical_device()
+        .surface_present_modes(&renderer.surface())
+        .map(Iterator::collect)
+        .unwrap_or_default();
+
+    [PresentMode::Mailbox, PresentMode::Immediate]
+        .into_iter()
+        .find(|mode| supported.contains(mode))
+        .unwrap_or(PresentMode::Fifo)
 }
@@ -0,0 +1,135 @@
+use std::{error::Error, fmt, fs, io, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+/// Largest weight of a single neighbour, so that sums of all of them stay small.
+const MAX_WEIGHT: u32 = 1 << 16;
+
+/// This struct represents a weighted life rule, the live neighbours are summed
+/// with their own weights and the sum is compared to inclusive ranges
+/// instead of the birth and survival counts of the [`Rule`](crate::Rule).
+///
+/// Default weights of 1 with birth at 3 and survival at 2 to 3 reproduce standard Life.
+/// Dying states and the neighbourhood of the rule still apply, neighbours outside
+/// of the neighbourhood are never summed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WeightedRule {
+    /// Weights of neighbours row after row from the top left one, skipping the cell itself.
+    pub weights: [u32; 8],
+    /// Smallest and largest sum a dead cell is born with.
+    pub birth: [u32; 2],
+    /// Smallest and largest sum a live cell survives with.
+    pub survival: [u32; 2],
+}
+
+impl WeightedRule {
+    /// Loads a weighted rule from a TOML file like:
+    ///
+    /// ```toml
+    /// weights = [1, 2, 1, 2, 2, 1, 2, 1]
+    /// birth = [5, 6]
+    /// survival = [4, 7]
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// - when the file cannot be read.
+    /// - when the file is not a valid weighted rule.
+    pub fn load(path: &Path) -> Result<Self, WeightsError> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    /// Parses a weighted rule from TOML, see [`WeightedRule::load`].
+    ///
+    /// # Errors
+    ///
+    /// - when the text is not valid TOML with all three values.
+    /// - when any of the weights is too big or any of the ranges is empty.
+    pub fn parse(text: &str) -> Result<Self, WeightsError> {
+        let rule: Self = toml::from_str(text).map_err(WeightsError::Toml)?;
+        if let Some(&weight) = rule.weights.iter().find(|&&weight| weight > MAX_WEIGHT) {
+            return Err(WeightsError::WeightTooBig(weight));
+        }
+        for [min, max] in [rule.birth, rule.survival] {
+            if min > max {
+                return Err(WeightsError::EmptyRange(min, max));
+            }
+        }
+        Ok(rule)
+    }
+}
+
+impl Default for WeightedRule {
+    fn default() -> Self {
+        Self {
+            weights: [1; 8],
+            birth: [3, 3],
+            survival: [2, 3],
+        }
+    }
+}
+
+/// Error returned when a weighted rule cannot be loaded.
+#[derive(Debug)]
+pub enum WeightsError {
+    Io(io::Error),
+    Toml(toml::de::Error),
+    WeightTooBig(u32),
+    EmptyRange(u32, u32),
+}
+
+impl fmt::Display for WeightsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "cannot read weights: {error}"),
+            Self::Toml(error) => write!(f, "cannot parse weights: {error}"),
+            Self::WeightTooBig(weight) => {
+                write!(f, "weight {weight} is bigger than {MAX_WEIGHT}")
+            }
+            Self::EmptyRange(min, max) => {
+                write!(f, "range from {min} to {max} is empty")
+            }
+        }
+    }
+}
+
+impl Error for WeightsError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io(error) => Some(error),
+            Self::Toml(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for WeightsError {
+    fn from(error: io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_weights_and_ranges() {
+        let rule = WeightedRule::parse(
+            "weights = [1, 2, 1, 2, 2, 1, 2, 1]\nbirth = [5, 6]\nsurvival = [4, 7]\n",
+        )
+        .expect("Valid weights");
+        assert_eq!(rule.weights, [1, 2, 1, 2, 2, 1, 2, 1]);
+        assert_eq!(rule.birth, [5, 6]);
+        assert_eq!(rule.survival, [4, 7]);
+    }
+
+    #[test]
+    fn rejects_empty_range() {
+        let error = WeightedRule::parse(
+            "weights = [1, 1, 1, 1, 1, 1, 1, 1]\nbirth = [4, 3]\nsurvival = [2, 3]\n",
+        );
+        assert!(matches!(error, Err(WeightsError::EmptyRange(4, 3))));
+    }
+}
@@ -0,0 +1,115 @@
+//! Reads and writes cells of the board from the host to check oscillators
+//! and spaceships computed by the simulation pipeline.
+
+use game_of_life::{vulkan, BoardLayout, Neighbourhood, Rule, Simulation, Topology};
+use vulkano::sync::{self, GpuFuture};
+use vulkano_util::context::{VulkanoConfig, VulkanoContext};
+
+/// Dimensions of the board, small enough to be read after every generation.
+const SIZE: (u32, u32) = (16, 16);
+
+/// Horizontal blinker in the middle of the board.
+const BLINKER: [[u32; 2]; 3] = [[6, 7], [7, 7], [8, 7]];
+
+/// The same blinker one generation later.
+const BLINKER_NEXT: [[u32; 2]; 3] = [[7, 6], [7, 7], [7, 8]];
+
+/// Glider moving down and to the right.
+const GLIDER: [[u32; 2]; 5] = [[1, 0], [2, 1], [0, 2], [1, 2], [2, 2]];
+
+/// Returns values of cells of a board with given live cells.
+fn cells(cells: &[[u32; 2]]) -> Vec<u32> {
+    let (width, height) = SIZE;
+    let mut values = vec![0; (width * height) as usize];
+    for &[x, y] in cells {
+        values[(y * width + x) as usize] = 1;
+    }
+    values
+}
+
+/// Creates a bounded Conway's simulation of an empty board with given cell bits.
+fn simulation(context: &VulkanoContext, cell_bits: u32) -> Simulation {
+    let layout = BoardLayout::new(SIZE, cell_bits);
+    let buffer = vulkan::create_gpu_buffer(context.device(), layout, true);
+    Simulation::new(
+        context.compute_queue().clone(),
+        buffer,
+        layout,
+        Rule::default(),
+        Neighbourhood::Moore,
+        Topology::Bounded,
+        32,
+    )
+}
+
+/// Computes given number of generations and waits for them to finish.
+fn run(simulation: &mut Simulation, context: &VulkanoContext, generations: u64) {
+    let target = simulation.generation() + generations;
+    simulation
+        .run_to(target, sync::now(context.device().clone()).boxed())
+        .then_signal_fence_and_flush()
+        .expect("Cannot execute generations")
+        .wait(None)
+        .expect("Cannot wait for generations");
+}
+
+#[test]
+#[ignore = "needs a Vulkan device"]
+fn written_cells_are_read_back() {
+    let context = VulkanoContext::new(VulkanoConfig::default());
+
+    for cell_bits in [1, 8, 32] {
+        let mut simulation = simulation(&context, cell_bits);
+        simulation.write_cells(&cells(&GLIDER));
+
+        assert_eq!(
+            simulation.read_cells(),
+            cells(&GLIDER),
+            "cell bits: {cell_bits}"
+        );
+        assert_eq!(simulation.generation(), 0);
+    }
+}
+
+#[test]
+#[ignore = "needs a Vulkan device"]
+fn blinker_oscillates() {
+    let context = VulkanoContext::new(VulkanoConfig::default());
+
+    for cell_bits in [1, 8, 32] {
+        let mut simulation = simulation(&context, cell_bits);
+        simulation.write_cells(&cells(&BLINKER));
+
+        run(&mut simulation, &context, 1);
+        assert_eq!(
+            simulation.read_cells(),
+            cells(&BLINKER_NEXT),
+            "cell bits: {cell_bits}"
+        );
+        run(&mut simulation, &context, 1);
+        assert_eq!(
+            simulation.read_cells(),
+            cells(&BLINKER),
+            "cell bits: {cell_bits}"
+        );
+    }
+}
+
+#[test]
+#[ignore = "needs a Vulkan device"]
+fn glider_moves_diagonally() {
+    let context = VulkanoContext::new(VulkanoConfig::default());
+    let moved = GLIDER.map(|[x, y]| [x + 1, y + 1]);
+
+    for cell_bits in [1, 8, 32] {
+        let mut simulation = simulation(&context, cell_bits);
+        simulation.write_cells(&cells(&GLIDER));
+
+        run(&mut simulation, &context, 4);
+        assert_eq!(
+            simulation.read_cells(),
+            cells(&moved),
+            "cell bits: {cell_bits}"
+        );
+    }
+}
@@ -0,0 +1,60 @@
+//! Runs generations on boards whose size is not a multiple of the work group size,
+//! so that invocations past the edges of the board have to be skipped.
+
+use game_of_life::{vulkan, BoardLayout, Neighbourhood, Rule, Simulation, Topology};
+use vulkano::sync::{self, GpuFuture};
+use vulkano_util::context::{VulkanoConfig, VulkanoContext};
+
+/// Prime dimensions, neither a multiple of the work group nor of cells in a word.
+const SIZE: (u32, u32) = (97, 53);
+
+/// Blinkers touching the right and the bottom edge.
+const BLINKERS: [[u32; 2]; 6] = [[94, 1], [95, 1], [96, 1], [1, 50], [1, 51], [1, 52]];
+
+/// The same blinkers one generation later.
+const NEXT: [[u32; 2]; 6] = [[95, 0], [95, 1], [95, 2], [0, 51], [1, 51], [2, 51]];
+
+/// Returns words of a board with given live cells.
+fn board(layout: BoardLayout, cells: &[[u32; 2]]) -> Vec<u32> {
+    let len = usize::try_from(layout.len()).expect("Board does not fit in memory");
+    let mut words = vec![0; len];
+    for &[x, y] in cells {
+        layout.set(&mut words, x, y, 1);
+    }
+    words
+}
+
+#[test]
+#[ignore = "needs a Vulkan device"]
+fn generation_of_prime_sized_board_keeps_padding_clear() {
+    let context = VulkanoContext::new(VulkanoConfig::default());
+    let queue = context.compute_queue().clone();
+
+    for cell_bits in [1, 8, 32] {
+        let layout = BoardLayout::new(SIZE, cell_bits);
+        let buffer = vulkan::create_gpu_buffer(context.device(), layout, true);
+        vulkan::upload_words(&queue, buffer.clone(), board(layout, &BLINKERS));
+        let mut simulation = Simulation::new(
+            queue.clone(),
+            buffer,
+            layout,
+            Rule::default(),
+            Neighbourhood::Moore,
+            Topology::Bounded,
+            32,
+        );
+
+        simulation
+            .step(sync::now(queue.device().clone()).boxed())
+            .then_signal_fence_and_flush()
+            .expect("Cannot execute generation")
+            .wait(None)
+            .expect("Cannot wait for generation");
+
+        assert_eq!(
+            simulation.readback().read(),
+            board(layout, &NEXT),
+            "cell bits: {cell_bits}"
+        );
+    }
+}
@@ -0,0 +1,205 @@
+//! Compares generations computed by the simulation pipeline with a straightforward
+//! implementation of the same transition running on the host.
+
+use game_of_life::{vulkan, BoardLayout, Neighbourhood, Rule, Simulation, Topology, PRESETS};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use vulkano::sync::{self, GpuFuture};
+use vulkano_util::context::{VulkanoConfig, VulkanoContext};
+
+/// Dimensions of the board, odd so that edges do not line up with words.
+const SIZE: (u32, u32) = (37, 29);
+
+/// Number of generations compared after every upload.
+const GENERATIONS: u64 = 16;
+
+/// Seed of random boards, so failures can be reproduced.
+const SEED: u64 = 0x11FE;
+
+/// Known patterns placed near the top left corner of an empty board.
+const PATTERNS: [(&str, &[[u32; 2]]); 5] = [
+    ("block", &[[1, 1], [2, 1], [1, 2], [2, 2]]),
+    ("blinker", &[[1, 2], [2, 2], [3, 2]]),
+    ("toad", &[[2, 1], [3, 1], [4, 1], [1, 2], [2, 2], [3, 2]]),
+    ("glider", &[[1, 0], [2, 1], [0, 2], [1, 2], [2, 2]]),
+    ("r-pentomino", &[[2, 1], [3, 1], [1, 2], [2, 2], [2, 3]]),
+];
+
+/// Returns value of the cell at given position, following the edges of the topology.
+fn cell_at(cells: &[u32], topology: Topology, x: i64, y: i64) -> u32 {
+    let (width, height) = (i64::from(SIZE.0), i64::from(SIZE.1));
+    let outside_x = x < 0 || x >= width;
+    let outside_y = y < 0 || y >= height;
+    let (mut x, mut y) = (x, y);
+    match topology {
+        Topology::Bounded if outside_x || outside_y => return 0,
+        Topology::KleinBottle | Topology::CrossSurface if outside_x => y = height - 1 - y,
+        _ => (),
+    }
+    if topology == Topology::CrossSurface && outside_y {
+        x = width - 1 - x;
+    }
+    let (x, y) = (x.rem_euclid(width), y.rem_euclid(height));
+    cells[usize::try_from(y * width + x).expect("Index does not fit")]
+}
+
+/// Computes the next generation of given cells on the host.
+fn step(cells: &[u32], rule: Rule, neighbourhood: Neighbourhood, topology: Topology) -> Vec<u32> {
+    let (width, height) = (i64::from(SIZE.0), i64::from(SIZE.1));
+    let mut next = Vec::with_capacity(cells.len());
+    for y in 0..height {
+        for x in 0..width {
+            let mut sum = 0;
+            for dy in -1..=1 {
+                for dx in -1..=1 {
+                    let orthogonal = dx == 0 || dy == 0;
+                    if (dx, dy) != (0, 0)
+                        && (neighbourhood == Neighbourhood::Moore || orthogonal)
+                        && cell_at(cells, topology, x + dx, y + dy) == 1
+                    {
+                        sum += 1;
+                    }
+                }
+            }
+            let cell = cell_at(cells, topology, x, y);
+            next.push(match cell {
+                0 => (rule.birth() >> sum) & 1,
+                1 if (rule.survival() >> sum) & 1 == 1 => 1,
+                _ if cell + 1 < rule.states() => cell + 1,
+                _ => 0,
+            });
+        }
+    }
+    next
+}
+
+/// Returns values of cells of an empty board with given live cells.
+fn board(cells: &[[u32; 2]]) -> Vec<u32> {
+    let mut values = vec![0; (SIZE.0 * SIZE.1) as usize];
+    for &[x, y] in cells {
+        values[(y * SIZE.0 + x) as usize] = 1;
+    }
+    values
+}
+
+/// Returns values of cells of a board with every cell alive with probability of one half.
+fn random_board(rng: &mut StdRng) -> Vec<u32> {
+    (0..SIZE.0 * SIZE.1).map(|_| rng.gen_range(0..=1)).collect()
+}
+
+/// Uploads given cells and checks that every one of the following generations
+/// computed by the simulation matches the host.
+fn compare(
+    context: &VulkanoContext,
+    cells: Vec<u32>,
+    rule: Rule,
+    neighbourhood: Neighbourhood,
+    topology: Topology,
+    tiled: bool,
+    label: &str,
+) {
+    let layout = BoardLayout::new(SIZE, 32);
+    let buffer = vulkan::create_gpu_buffer(context.device(), layout, true);
+    let mut simulation = Simulation::new(
+        context.compute_queue().clone(),
+        buffer,
+        layout,
+        rule,
+        neighbourhood,
+        topology,
+        32,
+    );
+    simulation.set_tiled(tiled);
+    simulation.write_cells(&cells);
+
+    let mut expected = cells;
+    for generation in 1..=GENERATIONS {
+        simulation
+            .step(sync::now(context.device().clone()).boxed())
+            .then_signal_fence_and_flush()
+            .expect("Cannot execute generation")
+            .wait(None)
+            .expect("Cannot wait for generation");
+        expected = step(&expected, rule, neighbourhood, topology);
+        assert_eq!(
+            simulation.read_cells(),
+            expected,
+            "{label}, {topology:?}, {neighbourhood:?}, tiled {tiled}, generation {generation}"
+        );
+    }
+}
+
+#[test]
+fn reference_moves_glider_one_cell_diagonally() {
+    let glider = PATTERNS[3].1;
+    let moved: Vec<_> = glider.iter().map(|&[x, y]| [x + 1, y + 1]).collect();
+
+    let mut cells = board(glider);
+    for _ in 0..4 {
+        cells = step(
+            &cells,
+            Rule::default(),
+            Neighbourhood::Moore,
+            Topology::Bounded,
+        );
+    }
+
+    assert_eq!(cells, board(&moved));
+}
+
+#[test]
+#[ignore = "needs a Vulkan device"]
+fn known_patterns_match_reference() {
+    let context = VulkanoContext::new(VulkanoConfig::default());
+
+    for (name, pattern) in PATTERNS {
+        for topology in Topology::ALL {
+            compare(
+                &context,
+                board(pattern),
+                Rule::default(),
+                Neighbourhood::Moore,
+                topology,
+                false,
+                name,
+            );
+        }
+    }
+}
+
+#[test]
+#[ignore = "needs a Vulkan device"]
+fn random_boards_match_reference_for_presets() {
+    let context = VulkanoContext::new(VulkanoConfig::default());
+    let mut rng = StdRng::seed_from_u64(SEED);
+
+    for (name, rule) in PRESETS {
+        for neighbourhood in [Neighbourhood::Moore, Neighbourhood::VonNeumann] {
+            for topology in Topology::ALL {
+                let cells = random_board(&mut rng);
+                compare(&context, cells, rule, neighbourhood, topology, false, name);
+            }
+        }
+    }
+}
+
+#[test]
+#[ignore = "needs a Vulkan device"]
+fn tiled_random_boards_match_reference() {
+    let context = VulkanoContext::new(VulkanoConfig::default());
+    let mut rng = StdRng::seed_from_u64(SEED);
+
+    for neighbourhood in [Neighbourhood::Moore, Neighbourhood::VonNeumann] {
+        for topology in Topology::ALL {
+            let cells = random_board(&mut rng);
+            compare(
+                &context,
+                cells,
+                Rule::default(),
+                neighbourhood,
+                topology,
+                true,
+                "tiled",
+            );
+        }
+    }
+}